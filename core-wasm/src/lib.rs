@@ -0,0 +1,73 @@
+// wasm-bindgen wrapper around `core::GameState` for browser frontends.
+//
+// Build with `wasm-pack build core-wasm --target web`. The wrapper holds a
+// plain `core::GameState` and forwards to it, so `commit()` runs the exact
+// same encoding and SHA-256 as the native host and the guest: a digest
+// computed in the browser matches the one a native proof commits to.
+//
+// Ships are passed as their `ShipType::index()` (0 = Carrier .. 4 =
+// Destroyer) and directions as a `horizontal` flag, since wasm-bindgen
+// cannot export the core enums directly.
+
+use core::{Direction, GameState, HitType, Position, ShipType, NUM_SHIPS};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = GameState)]
+pub struct WasmGameState {
+    inner: GameState,
+}
+
+#[wasm_bindgen(js_class = GameState)]
+impl WasmGameState {
+    /// Empty board with a 16-byte pepper.
+    #[wasm_bindgen(constructor)]
+    pub fn new(pepper: &[u8]) -> Result<WasmGameState, JsError> {
+        let pepper: [u8; 16] = pepper.try_into().map_err(|_| JsError::new("pepper must be 16 bytes"))?;
+        Ok(Self { inner: GameState::new(pepper) })
+    }
+
+    /// Place the ship with index `ship` at (x, y). Returns false for an
+    /// unknown index or a placement `GameState::place_ship` rejects.
+    #[wasm_bindgen(js_name = placeShip)]
+    pub fn place_ship(&mut self, ship: usize, x: u32, y: u32, horizontal: bool) -> bool {
+        if ship >= NUM_SHIPS {
+            return false;
+        }
+        let direction = if horizontal { Direction::Horizontal } else { Direction::Vertical };
+        self.inner.place_ship(ShipType::ALL[ship], Position::new(x, y), direction)
+    }
+
+    /// Fire at (x, y). Returns "miss", "hit" or "sunk:<index>", or
+    /// `undefined` for an out-of-bounds or repeated shot.
+    #[wasm_bindgen(js_name = applyShot)]
+    pub fn apply_shot(&mut self, x: u32, y: u32) -> Option<String> {
+        self.inner.apply_shot(Position::new(x, y)).map(|hit| match hit {
+            HitType::Miss => "miss".to_string(),
+            HitType::Hit => "hit".to_string(),
+            HitType::Sunk(t) => format!("sunk:{}", t.index()),
+            HitType::SunkHidden(_) => "sunk".to_string(),
+        })
+    }
+
+    /// Same rules as `core::GameState::check`.
+    pub fn check(&self) -> bool {
+        self.inner.check()
+    }
+
+    /// Hex-encoded `core::GameState::commit()`.
+    pub fn commit(&self) -> String {
+        self.inner.commit().to_string()
+    }
+
+    #[wasm_bindgen(js_name = allShipsSunk)]
+    pub fn all_ships_sunk(&self) -> bool {
+        self.inner.all_ships_sunk()
+    }
+}
+
+impl WasmGameState {
+    /// The wrapped native state, for Rust callers.
+    pub fn as_core(&self) -> &GameState {
+        &self.inner
+    }
+}
@@ -0,0 +1,21 @@
+// Browser half of the commit parity check in `web.rs`: the same board and
+// shots must give the native golden commit in a real browser too. Run with
+// `wasm-pack test --headless --chrome core-wasm` (or `--firefox`).
+#![cfg(target_arch = "wasm32")]
+
+use core_wasm::WasmGameState;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn commit_matches_native_in_browser() {
+    let mut board = WasmGameState::new(&[0xA5; 16]).unwrap();
+    for (ship, y) in [(0, 0), (1, 2), (2, 4), (3, 6), (4, 8)] {
+        assert!(board.place_ship(ship, 0, y, true));
+    }
+    assert_eq!(board.apply_shot(0, 0).as_deref(), Some("hit"));
+    assert_eq!(board.apply_shot(9, 9).as_deref(), Some("miss"));
+    // core's `test_commit_golden_vector`, computed natively
+    assert_eq!(board.commit(), "74fdc31ef231aa9990c2c0db3efab5125c6de5403fedc39fd3255882b8ef6974");
+}
@@ -0,0 +1,46 @@
+// Runs as a plain `cargo test` on the host and under `wasm-pack test --node
+// core-wasm` (see `browser.rs` for the browser run).
+
+use core::{Direction, GameState, Position, ShipType};
+use core_wasm::WasmGameState;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn commit_matches_native() {
+    let mut board = WasmGameState::new(&[0xA5; 16]).unwrap();
+    for (ship, y) in [(0, 0), (1, 2), (2, 4), (3, 6), (4, 8)] {
+        assert!(board.place_ship(ship, 0, y, true));
+    }
+    assert!(board.check());
+    assert_eq!(board.apply_shot(0, 0).as_deref(), Some("hit"));
+    assert_eq!(board.apply_shot(9, 9).as_deref(), Some("miss"));
+    assert_eq!(board.apply_shot(9, 9), None);
+
+    let mut native = GameState::new([0xA5; 16]);
+    assert!(native.place_ships(vec![
+        (ShipType::Carrier, Position::new(0, 0), Direction::Horizontal),
+        (ShipType::Battleship, Position::new(0, 2), Direction::Horizontal),
+        (ShipType::Cruiser, Position::new(0, 4), Direction::Horizontal),
+        (ShipType::Submarine, Position::new(0, 6), Direction::Horizontal),
+        (ShipType::Destroyer, Position::new(0, 8), Direction::Horizontal),
+    ]));
+    native.apply_shot(Position::new(0, 0));
+    native.apply_shot(Position::new(9, 9));
+
+    assert_eq!(board.as_core(), &native);
+    assert_eq!(board.commit(), native.commit().to_string());
+    // Same board and shots as core's `test_commit_golden_vector`
+    assert_eq!(board.commit(), "74fdc31ef231aa9990c2c0db3efab5125c6de5403fedc39fd3255882b8ef6974");
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn sinking_reports_ship_index() {
+    let mut board = WasmGameState::new(&[0; 16]).unwrap();
+    assert!(board.place_ship(4, 3, 3, false));
+    assert!(!board.place_ship(5, 0, 0, true));
+    assert_eq!(board.apply_shot(3, 3).as_deref(), Some("hit"));
+    assert_eq!(board.apply_shot(3, 4).as_deref(), Some("sunk:4"));
+}
@@ -0,0 +1,84 @@
+// Criterion benchmarks for the core hot paths: `commit()`, `apply_shot`
+// and random placement.
+//
+// Usage:
+//    cargo bench -p core --features rand
+//
+// `commit()` matters most: the guest recomputes it for every shot, so its
+// cost is paid once per proven round. Criterion keeps a baseline under
+// `target/criterion` and reports the change against it on the next run.
+//
+// Baseline (release build, x86_64 Linux, host-side SHA-256):
+//
+//    commit (full board, 10 shots)           337ns
+//    clone + apply_shot, 0% of board shot    53ns
+//    clone + apply_shot, 25% of board shot   44ns
+//    clone + apply_shot, 50% of board shot   40ns
+//    clone + apply_shot, 75% of board shot   43ns
+//    clone + apply_shot, 95% of board shot   40ns
+//    place_ships_randomly                    3.9µs
+//
+// `apply_shot` only touches the target cell and the fleet, so it is flat
+// across board fullness; the sweep is there to catch a change that makes
+// it scan the grid. `commit()` rehashes the whole 176-byte encoding and
+// dominates a shot's cost; see `host/examples/commit_bench.rs` for the
+// Merkle alternative.
+use core::{Direction, GameState, Position, ShipType, BOARD_SIZE};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn full_board() -> GameState {
+    let mut state = GameState::new([7; 16]);
+    assert!(state.place_ships(vec![
+        (ShipType::Carrier, Position::new(0, 0), Direction::Horizontal),
+        (ShipType::Battleship, Position::new(0, 2), Direction::Horizontal),
+        (ShipType::Cruiser, Position::new(0, 4), Direction::Horizontal),
+        (ShipType::Submarine, Position::new(0, 6), Direction::Horizontal),
+        (ShipType::Destroyer, Position::new(0, 8), Direction::Horizontal),
+    ]));
+    state
+}
+
+/// `full_board()` with the first `percent`% of cells (in reading order)
+/// already shot. The last cell, (9, 9), is always left open.
+fn board_shot(percent: usize) -> GameState {
+    let mut state = full_board();
+    let cells = BOARD_SIZE * BOARD_SIZE * percent / 100;
+    for i in 0..cells.min(BOARD_SIZE * BOARD_SIZE - 1) {
+        state.apply_shot(Position::new((i % BOARD_SIZE) as u32, (i / BOARD_SIZE) as u32));
+    }
+    state
+}
+
+fn commit(c: &mut Criterion) {
+    let mut state = full_board();
+    for x in 0..10 {
+        state.apply_shot(Position::new(x, 9 - x));
+    }
+    c.bench_function("commit (full board, 10 shots)", |b| b.iter(|| black_box(&state).commit()));
+}
+
+fn apply_shot(c: &mut Criterion) {
+    let last = Position::new(BOARD_SIZE as u32 - 1, BOARD_SIZE as u32 - 1);
+    let mut group = c.benchmark_group("clone + apply_shot");
+    for percent in [0, 25, 50, 75, 95] {
+        let board = board_shot(percent);
+        group.bench_with_input(BenchmarkId::new("% of board shot", percent), &board, |b, board| {
+            b.iter(|| black_box(board).clone().apply_shot(last))
+        });
+    }
+    group.finish();
+}
+
+fn place_ships_randomly(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(7);
+    c.bench_function("place_ships_randomly", |b| {
+        b.iter(|| {
+            let mut state = GameState::new([0; 16]);
+            black_box(state.place_ships_randomly(&mut rng))
+        })
+    });
+}
+
+criterion_group!(benches, commit, apply_shot, place_ships_randomly);
+criterion_main!(benches);
@@ -17,26 +17,194 @@ use {
     rand::seq::SliceRandom,
 };
 
+pub mod rules;
+pub use rules::{TurnMode, TurnRules, TurnState, TurnTransition};
+
 /// Board dimensions. Fixed-size board simplifies reasoning and
-/// serialization across prover/verifier.
+/// serialization across prover/verifier. Also the largest width and height
+/// a `BoardDims` may have: smaller boards use the top-left part of the
+/// same grid.
 pub const BOARD_SIZE: usize = 10;
 
 /// Number of distinct ship types used in the canonical setup.
 pub const NUM_SHIPS: usize = 5;
 
+/// Most shots one guest run will prove: one per cell of the largest board.
+/// A longer list has to repeat a cell, which `apply_shot` refuses anyway,
+/// so the cap costs honest players nothing and bounds a proof at about
+/// `BOARD_SIZE * BOARD_SIZE` rounds. The host checks it before proving and
+/// the guest panics past it.
+pub const MAX_SHOTS_PER_PROOF: usize = BOARD_SIZE * BOARD_SIZE;
+
 /// Canonical ship sizes (in reading order): Carrier, Battleship,
 /// Cruiser, Submarine, Destroyer.
 pub const SHIP_SIZES: [u8; NUM_SHIPS] = [5, 4, 3, 3, 2];
 
+/// Version byte leading `GameState::serialize_for_commit()`.
+pub const COMMIT_FORMAT_VERSION: u8 = 1;
+
+/// Version byte for states carrying a 32-byte `wide_pepper`. The layout is
+/// `COMMIT_FORMAT_VERSION`'s with the 16 pepper bytes replaced by 32.
+pub const COMMIT_FORMAT_WIDE_PEPPER: u8 = 2;
+
+/// HKDF salt and info for `GameState::with_pepper_from_seed`.
+const PEPPER_KDF_SALT: &[u8] = b"zk-battleship pepper salt";
+const PEPPER_KDF_INFO: &[u8] = b"commit pepper v2";
+
+/// Height of the grid Merkle tree used by `GameState::merkle_root()`.
+pub const GRID_TREE_DEPTH: usize = 7;
+const GRID_TREE_LEAVES: usize = 1 << GRID_TREE_DEPTH;
+const _: () = assert!(BOARD_SIZE * BOARD_SIZE <= GRID_TREE_LEAVES, "grid tree too small for the board");
+
+/// First byte of the `GameState::hash_ships_only()` preimage, keeping
+/// placement hashes distinct from full-state commitments.
+pub const PLACEMENT_DOMAIN: u8 = 0x50;
+
+/// First byte of the `FleetSpec::digest()` preimage.
+pub const FLEET_DOMAIN: u8 = 0x46;
+
+/// First byte of the `ShipTypeOpening::commitment()` preimage and of the
+/// message `DisclosureMode::opening` derives nonces from.
+pub const SHIP_TYPE_DOMAIN: u8 = 0x53;
+
+/// Byte used for a direction in commitment encodings.
+fn direction_byte(dir: Direction) -> u8 {
+    match dir {
+        Direction::Horizontal => 0,
+        Direction::Vertical => 1,
+    }
+}
+
+/// Byte used for a cell in commitment encodings.
+fn cell_byte(cell: &CellState) -> u8 {
+    match cell {
+        CellState::Empty => 0,
+        CellState::Miss => 1,
+        CellState::Hit | CellState::Sunk => 2,
+    }
+}
+
+/// Grid tree leaf: `sha256([0x00, cell_byte])`. The prefix keeps leaves
+/// distinct from interior `hash_pair` nodes.
+fn cell_leaf(cell: &CellState) -> Digest {
+    *risc0_zkvm::sha::Impl::hash_bytes(&[0x00, cell_byte(cell)])
+}
+
+/// HMAC-SHA256 (RFC 2104) over the zkVM SHA-256, so it also runs in the guest.
+fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(risc0_zkvm::sha::Impl::hash_bytes(key).as_bytes());
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+    let inner = risc0_zkvm::sha::Impl::hash_bytes(&[pad(0x36).as_slice(), msg].concat());
+    let outer = risc0_zkvm::sha::Impl::hash_bytes(&[pad(0x5c).as_slice(), inner.as_bytes()].concat());
+    outer.as_bytes().try_into().expect("SHA-256 digest is 32 bytes")
+}
+
+/// HKDF-SHA256 (RFC 5869) filling `out`, which must be at most 255 * 32 bytes.
+fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) {
+    let prk = hmac_sha256(salt, ikm);
+    let mut block: Vec<u8> = Vec::new();
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        block = hmac_sha256(&prk, &[block.as_slice(), info, &[i as u8 + 1]].concat()).to_vec();
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Horizontal,
     Vertical,
 }
 
+impl Direction {
+    /// The heading a ship extends in from its start: right for horizontal
+    /// ships, down for vertical ones.
+    pub const fn forward(self) -> Heading {
+        match self {
+            Direction::Horizontal => Heading::Right,
+            Direction::Vertical => Heading::Down,
+        }
+    }
+
+    /// The other axis, e.g. for a "rotate" action in a placement UI.
+    pub const fn perpendicular(self) -> Direction {
+        match self {
+            Direction::Horizontal => Direction::Vertical,
+            Direction::Vertical => Direction::Horizontal,
+        }
+    }
+}
+
 // Direction is intentionally a small enum so it serializes compactly and
 // can be used in Position/ship arithmetic without allocations.
 
+/// One way of walking along an axis, e.g. for an AI probing past either
+/// end of two collinear hits. `Up` and `Left` move towards (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Heading {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Heading {
+    /// The axis this heading walks along.
+    pub const fn axis(self) -> Direction {
+        match self {
+            Heading::Up | Heading::Down => Direction::Vertical,
+            Heading::Left | Heading::Right => Direction::Horizontal,
+        }
+    }
+
+    /// The same axis walked the other way.
+    pub const fn opposite(self) -> Heading {
+        match self {
+            Heading::Up => Heading::Down,
+            Heading::Down => Heading::Up,
+            Heading::Left => Heading::Right,
+            Heading::Right => Heading::Left,
+        }
+    }
+}
+
+/// Accepts `H`, `V`, `Horizontal` or `Vertical` in any case, ignoring
+/// surrounding whitespace.
+impl TryFrom<&str> for Direction {
+    type Error = ParseNameError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "h" | "horizontal" => Ok(Direction::Horizontal),
+            "v" | "vertical" => Ok(Direction::Vertical),
+            _ => Err(ParseNameError::Direction(s.to_string())),
+        }
+    }
+}
+
+/// Text that `Direction::try_from` or `ShipType::try_from` does not
+/// recognise; holds the input as given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseNameError {
+    Direction(String),
+    ShipType(String),
+}
+
+impl std::fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseNameError::Direction(s) => write!(f, "unknown direction {:?}: use H, V, Horizontal or Vertical", s),
+            ParseNameError::ShipType(s) => write!(f, "unknown ship type {:?}: use a name such as Carrier or one of the letters C, B, R, S, D", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseNameError {}
+
 // ============================================================================
 // Position: a safe, fixed-size coordinate type
 //
@@ -62,9 +230,59 @@ impl Position {
         }
     }
 
+    /// The neighbouring cell one step along `heading`, or `None` if that
+    /// leaves the `BOARD_SIZE` x `BOARD_SIZE` grid. Unlike `step`, this can
+    /// walk back towards (0, 0).
+    pub fn step_toward(self, heading: Heading) -> Option<Self> {
+        let next = match heading {
+            Heading::Up => Self { x: self.x, y: self.y.checked_sub(1)? },
+            Heading::Down => Self { x: self.x, y: self.y + 1 },
+            Heading::Left => Self { x: self.x.checked_sub(1)?, y: self.y },
+            Heading::Right => Self { x: self.x + 1, y: self.y },
+        };
+        next.in_bounds().then_some(next)
+    }
+
+    /// Within the `BOARD_SIZE` x `BOARD_SIZE` grid. A board with smaller
+    /// `BoardDims` checks `GameState::in_bounds` instead.
     pub fn in_bounds(&self) -> bool {
         self.x < BOARD_SIZE as u32 && self.y < BOARD_SIZE as u32
     }
+
+    /// Every on-board position, in reading order (row by row).
+    pub fn all_in_bounds() -> impl Iterator<Item = Position> {
+        (0..BOARD_SIZE as u32).flat_map(|y| (0..BOARD_SIZE as u32).map(move |x| Position::new(x, y)))
+    }
+
+    /// Lazily yield `len` positions starting at `self` and stepping along
+    /// `dir` (i.e. `self.step(dir, 0)`, `self.step(dir, 1)`, ...). Does not
+    /// allocate and performs no bounds checks.
+    pub fn iter_line(self, dir: Direction, len: u32) -> impl Iterator<Item = Position> {
+        (0..len).map(move |offset| self.step(dir, offset))
+    }
+
+    /// In-bounds cells sharing an edge with `self` (up, down, left, right).
+    /// Corners yield 2 cells, edges 3, interior cells 4.
+    pub fn orthogonal_neighbors(&self) -> Vec<Position> {
+        self.offsets(&[(0, -1), (0, 1), (-1, 0), (1, 0)])
+    }
+
+    /// In-bounds cells sharing an edge or a corner with `self`, in reading
+    /// order. Corners yield 3 cells, edges 5, interior cells 8.
+    pub fn all_neighbors(&self) -> Vec<Position> {
+        self.offsets(&[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)])
+    }
+
+    fn offsets(&self, deltas: &[(i64, i64)]) -> Vec<Position> {
+        deltas
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let x = u32::try_from(self.x as i64 + dx).ok()?;
+                let y = u32::try_from(self.y as i64 + dy).ok()?;
+                Some(Position::new(x, y)).filter(Position::in_bounds)
+            })
+            .collect()
+    }
 }
 
 impl From<(u32, u32)> for Position {
@@ -89,7 +307,10 @@ pub enum ShipType {
 }
 
 impl ShipType {
-    pub fn size(&self) -> u8 {
+    /// Every ship type, in `index()` order.
+    pub const ALL: [ShipType; NUM_SHIPS] = [ShipType::Carrier, ShipType::Battleship, ShipType::Cruiser, ShipType::Submarine, ShipType::Destroyer];
+
+    pub const fn size(&self) -> u8 {
         match self {
             ShipType::Carrier => 5,
             ShipType::Battleship => 4,
@@ -99,7 +320,7 @@ impl ShipType {
         }
     }
     /// Return a stable index for this ship type (0..NUM_SHIPS)
-    pub fn index(&self) -> usize {
+    pub const fn index(&self) -> usize {
         match self {
             ShipType::Carrier => 0,
             ShipType::Battleship => 1,
@@ -108,6 +329,68 @@ impl ShipType {
             ShipType::Destroyer => 4,
         }
     }
+
+    /// Letter for this ship type in ASCII layouts (see
+    /// `GameState::from_ascii`). Cruiser is `R` so it does not clash with
+    /// Carrier.
+    pub const fn letter(&self) -> char {
+        match self {
+            ShipType::Carrier => 'C',
+            ShipType::Battleship => 'B',
+            ShipType::Cruiser => 'R',
+            ShipType::Submarine => 'S',
+            ShipType::Destroyer => 'D',
+        }
+    }
+
+    /// Inverse of `letter()`.
+    pub fn from_letter(c: char) -> Option<ShipType> {
+        ShipType::ALL.into_iter().find(|t| t.letter() == c)
+    }
+}
+
+/// Accepts the type's name (`Carrier`) or its `letter()` (`C`) in any
+/// case, ignoring surrounding whitespace.
+impl TryFrom<&str> for ShipType {
+    type Error = ParseNameError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let name = s.trim();
+        let mut chars = name.chars();
+        let by_letter = match (chars.next(), chars.next()) {
+            (Some(c), None) => ShipType::from_letter(c.to_ascii_uppercase()),
+            _ => None,
+        };
+        by_letter
+            .or_else(|| ShipType::ALL.into_iter().find(|t| format!("{:?}", t).eq_ignore_ascii_case(name)))
+            .ok_or_else(|| ParseNameError::ShipType(s.to_string()))
+    }
+}
+
+/// True if `SHIP_SIZES` and `ShipType::ALL` agree with `ShipType::size()`
+/// and `ShipType::index()`.
+const fn ship_tables_agree() -> bool {
+    let mut i = 0;
+    while i < NUM_SHIPS {
+        let t = ShipType::ALL[i];
+        if t.index() != i || SHIP_SIZES[i] != t.size() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Editing one ship table without the other fails the build.
+const _: () = assert!(ship_tables_agree(), "SHIP_SIZES disagrees with ShipType::size()");
+
+/// Runtime counterpart of the compile-time table check, for debug builds
+/// and tests: `SHIP_SIZES[t.index()] == t.size()` for every `ShipType`.
+pub fn validate_ship_tables() {
+    for t in ShipType::ALL {
+        debug_assert_eq!(ShipType::ALL[t.index()], t, "ShipType::ALL out of index order at {:?}", t);
+        debug_assert_eq!(SHIP_SIZES[t.index()], t.size(), "SHIP_SIZES disagrees with {:?}.size()", t);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -141,45 +424,40 @@ impl Ship {
     /// not consult the board grid. Callers should ensure coordinate
     /// bounds as needed.
     pub fn check_hit(&mut self, shot: Position) -> bool {
-        let ship_x = self.position.x;
-        let ship_y = self.position.y;
-
-        match self.direction {
-            Direction::Horizontal => {
-                // Must be on the same row and not before the ship start
-                if shot.y != ship_y || shot.x < ship_x {
-                    return false;
-                }
-                let offset = (shot.x - ship_x) as usize;
-                if offset < self.ship_type.size() as usize {
-                    self.hits |= 1u8 << offset;
-                    return true;
-                }
-            }
-            Direction::Vertical => {
-                // Must be on the same column and not before the ship start
-                if shot.x != ship_x || shot.y < ship_y {
-                    return false;
-                }
-                let offset = (shot.y - ship_y) as usize;
-                if offset < self.ship_type.size() as usize {
-                    self.hits |= 1u8 << offset;
-                    return true;
-                }
+        match self.segment_index(shot) {
+            Some(offset) => {
+                self.hits |= 1u8 << offset;
+                true
             }
+            None => false,
         }
-        false
     }
 
-    // Get all coordinates this ship occupies
-    pub fn get_coordinates(&self) -> Vec<Position> {
-        let size = self.ship_type.size();
-        let mut coords = Vec::with_capacity(size as usize);
+    /// Whether the ship covers `pos`. Computed from position, direction and
+    /// size without building `get_coordinates()`, so it suits hot
+    /// membership tests such as placement.
+    pub fn footprint_contains(&self, pos: Position) -> bool {
+        self.segment_index(pos).is_some()
+    }
 
-        for offset in 0..size {
-            coords.push(self.position.step(self.direction, offset as u32));
+    /// Offset of `pos` along the ship (0 = `position`), or `None` if the
+    /// ship does not cover it.
+    fn segment_index(&self, pos: Position) -> Option<usize> {
+        // Must be on the ship's row (column) and not before its start
+        let (along, across, start_along, start_across) = match self.direction {
+            Direction::Horizontal => (pos.x, pos.y, self.position.x, self.position.y),
+            Direction::Vertical => (pos.y, pos.x, self.position.y, self.position.x),
+        };
+        if across != start_across || along < start_along {
+            return None;
         }
-        coords
+        let offset = (along - start_along) as usize;
+        (offset < self.ship_type.size() as usize).then_some(offset)
+    }
+
+    // Get all coordinates this ship occupies
+    pub fn get_coordinates(&self) -> Vec<Position> {
+        self.position.iter_line(self.direction, self.ship_type.size() as u32).collect()
     }
 }
 
@@ -188,16 +466,157 @@ pub enum CellState {
     Empty,
     Miss,
     Hit,
+    /// A cell of a sunk ship, replacing its `Hit` marks when
+    /// `GameState::mark_sunk_cells` is on. A view-layer distinction only:
+    /// it commits, hashes and snapshots exactly like `Hit`, so turning the
+    /// option on never changes `commit()`.
+    Sunk,
 }
 
 // Zero-Knowledge Types
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Hash)]
+/// Result of a shot.
+///
+/// (De)serialized by hand as a byte string, so JSON envelopes, bincode and
+/// the guest journal all carry the same bytes: a tag, then the payload.
+///
+/// | variant          | bytes                                   |
+/// |------------------|-----------------------------------------|
+/// | `Miss`           | `[0]`                                   |
+/// | `Hit`            | `[1]`                                   |
+/// | `Sunk(t)`        | `[2, t.index()]`                        |
+/// | `SunkHidden(d)`  | `[3]` followed by the 32 bytes of `d`   |
+///
+/// JSON shows this as an array of numbers, e.g. `[2,2]` for
+/// `Sunk(Cruiser)`. Tags and ship indices are part of the wire format and
+/// must not be renumbered.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum HitType {
     Miss,
     Hit,
     Sunk(ShipType),
+    /// A sinking under `DisclosureMode::HiddenShipType`: a commitment to
+    /// the ship type instead of the type itself, opened after the game
+    /// with a `ShipTypeOpening`.
+    SunkHidden(Digest),
+}
+
+impl HitType {
+    const TAG_MISS: u8 = 0;
+    const TAG_HIT: u8 = 1;
+    const TAG_SUNK: u8 = 2;
+    const TAG_SUNK_HIDDEN: u8 = 3;
+
+    /// The serialized form; see the table on `HitType`.
+    pub fn to_wire_bytes(&self) -> Vec<u8> {
+        match self {
+            HitType::Miss => vec![Self::TAG_MISS],
+            HitType::Hit => vec![Self::TAG_HIT],
+            HitType::Sunk(st) => vec![Self::TAG_SUNK, st.index() as u8],
+            HitType::SunkHidden(d) => [&[Self::TAG_SUNK_HIDDEN][..], d.as_bytes()].concat(),
+        }
+    }
+
+    /// Inverse of `to_wire_bytes`; `None` for an unknown tag, an unknown
+    /// ship index or the wrong length.
+    pub fn from_wire_bytes(bytes: &[u8]) -> Option<HitType> {
+        match bytes {
+            [Self::TAG_MISS] => Some(HitType::Miss),
+            [Self::TAG_HIT] => Some(HitType::Hit),
+            [Self::TAG_SUNK, index] => ShipType::ALL.get(*index as usize).map(|&st| HitType::Sunk(st)),
+            [Self::TAG_SUNK_HIDDEN, digest @ ..] if digest.len() == 32 => Digest::try_from(digest).ok().map(HitType::SunkHidden),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for HitType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_wire_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for HitType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct WireVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for WireVisitor {
+            type Value = HitType;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a HitType tag byte and payload")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<HitType, E> {
+                HitType::from_wire_bytes(bytes).ok_or_else(|| E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self))
+            }
+
+            // Self-describing formats such as JSON hand bytes over as a
+            // sequence of numbers
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<HitType, A::Error> {
+                let mut bytes = Vec::with_capacity(33);
+                while let Some(b) = seq.next_element::<u8>()? {
+                    bytes.push(b);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(WireVisitor)
+    }
+}
+
+/// Merkle inclusion proof for one grid cell (see `GameState::cell_proof`).
+/// `siblings[0]` is the sibling leaf, `siblings[GRID_TREE_DEPTH - 1]` the
+/// child of the root on the other side.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CellProof {
+    pub position: Position,
+    pub cell: CellState,
+    pub siblings: [Digest; GRID_TREE_DEPTH],
+}
+
+impl CellProof {
+    /// Grid root implied by this proof.
+    pub fn grid_root(&self) -> Digest {
+        let mut index = self.position.y as usize * BOARD_SIZE + self.position.x as usize;
+        let mut node = cell_leaf(&self.cell);
+        for sibling in &self.siblings {
+            node = if index & 1 == 0 {
+                *risc0_zkvm::sha::Impl::hash_pair(&node, sibling)
+            } else {
+                *risc0_zkvm::sha::Impl::hash_pair(sibling, &node)
+            };
+            index /= 2;
+        }
+        node
+    }
+
+    /// True if this proof opens `self.cell` at `self.position` under `grid_root`.
+    pub fn verify(&self, grid_root: &Digest) -> bool {
+        self.position.in_bounds() && self.grid_root() == *grid_root
+    }
+}
+
+/// Why `GameState::try_apply_shot` rejected a shot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShotError {
+    /// Outside the board's `dims`.
+    OutOfBounds,
+    /// The cell was shot before (or marked as water around a sunk ship).
+    AlreadyShot,
+}
+
+impl std::fmt::Display for ShotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ShotError::OutOfBounds => f.write_str("shot is out of bounds"),
+            ShotError::AlreadyShot => f.write_str("cell was already shot"),
+        }
+    }
 }
 
+impl std::error::Error for ShotError {}
+
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct RoundCommit {
     /// Bind the round commit to a match/session id so proofs cannot be replayed
@@ -209,6 +628,127 @@ pub struct RoundCommit {
     pub new_state: Digest,
     pub shot: Position,
     pub hit: HitType,
+    /// Every cell of the ship sunk by this shot, in ship order, when `hit`
+    /// is `Sunk`; empty otherwise. Sinking makes the footprint public, so
+    /// revealing it leaks nothing and lets the shooter mark the whole ship.
+    /// Defaults to empty when reading records made before it existed.
+    #[serde(default)]
+    pub sunk_cells: Vec<Position>,
+    /// Water cells around the sunk ship that the shot auto-marked as
+    /// `Miss` (see `GameState::mark_sunk_surroundings`), in reading order;
+    /// empty unless that rule is on and `hit` is `Sunk`.
+    #[serde(default)]
+    pub revealed_water: Vec<Position>,
+    /// Why the shot was rejected, if it was. The guest still proves such a
+    /// shot, as a `Miss` that leaves the board alone, but reports it here
+    /// so the shooter's peer can tell a fault from a real miss.
+    #[serde(default)]
+    pub invalid: Option<ShotError>,
+}
+
+impl RoundCommit {
+    /// Re-run this round on `pre`, the defender's board before the shot,
+    /// instead of trusting a proof: `pre` must commit to `old_state`, and
+    /// applying `shot` to a copy must give `hit`, `new_state` and the
+    /// revealed cells the guest would report. A rejected shot counts as a
+    /// `Miss` that leaves the board alone and must carry the reason in
+    /// `invalid`, as in the guest. A
+    /// `SunkHidden` result matches any sinking, since checking the type
+    /// needs its opening. Only useful where the verifier holds `pre`, such
+    /// as local tests.
+    pub fn verify_transition(&self, pre: &GameState) -> bool {
+        if pre.commit() != self.old_state {
+            return false;
+        }
+        if self.invalid != pre.check_shot(self.shot).err() {
+            return false;
+        }
+        let mut post = pre.clone();
+        let (hit, new_state) = post.apply_shot_committed(self.shot);
+        let hit_matches = match (&self.hit, hit.unwrap_or(HitType::Miss)) {
+            (HitType::SunkHidden(_), HitType::Sunk(_)) => true,
+            (claimed, actual) => *claimed == actual,
+        };
+        hit_matches && new_state == self.new_state && self.sunk_cells == post.sunk_footprint(&self.hit) && self.revealed_water == post.sunk_surroundings(&self.hit)
+    }
+
+    /// Whether `sunk_cells` has the shape the guest produces: empty unless
+    /// `hit` is `Sunk`, and otherwise a straight run of exactly that ship's
+    /// size, in bounds and covering `shot`. `revealed_water` must likewise
+    /// be empty unless `hit` is `Sunk`, and only name cells bordering the
+    /// ship.
+    pub fn reveal_is_well_formed(&self) -> bool {
+        let ship_type = match &self.hit {
+            HitType::Sunk(st) => *st,
+            // Includes `SunkHidden`: the footprint's length would give the hidden type away
+            _ => return self.sunk_cells.is_empty() && self.revealed_water.is_empty(),
+        };
+        let borders_ship = |w: &Position| !self.sunk_cells.contains(w) && self.sunk_cells.iter().any(|c| c.all_neighbors().contains(w));
+        if !self.revealed_water.iter().all(borders_ship) {
+            return false;
+        }
+        let cells = &self.sunk_cells;
+        if cells.len() != ship_type.size() as usize || !cells.contains(&self.shot) || !cells.iter().all(|c| c.in_bounds()) {
+            return false;
+        }
+        let start = cells[0];
+        let horizontal = cells.iter().enumerate().all(|(i, c)| c.y == start.y && c.x == start.x + i as u32);
+        let vertical = cells.iter().enumerate().all(|(i, c)| c.x == start.x && c.y == start.y + i as u32);
+        horizontal || vertical
+    }
+}
+
+// ============================================================================
+// BoardDims: the playable part of the grid
+//
+// The grid is always stored as `BOARD_SIZE` x `BOARD_SIZE` so the commit
+// layout never changes; a board of `width` x `height` uses columns
+// `0..width` and rows `0..height` and leaves the rest `Empty`.
+// ============================================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawBoardDims")]
+pub struct BoardDims {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Wire form of `BoardDims`; decoding goes through `BoardDims::new` so a
+/// peer cannot hand us a board larger than the fixed grid.
+#[derive(Deserialize)]
+struct RawBoardDims {
+    width: u32,
+    height: u32,
+}
+
+impl TryFrom<RawBoardDims> for BoardDims {
+    type Error = String;
+
+    fn try_from(raw: RawBoardDims) -> Result<Self, Self::Error> {
+        BoardDims::new(raw.width, raw.height).ok_or_else(|| format!("board dimensions {}x{} outside 1..={}", raw.width, raw.height, BOARD_SIZE))
+    }
+}
+
+impl Default for BoardDims {
+    fn default() -> Self {
+        Self { width: BOARD_SIZE as u32, height: BOARD_SIZE as u32 }
+    }
+}
+
+impl BoardDims {
+    /// `None` unless both sides are between 1 and `BOARD_SIZE`.
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let fits = |n: u32| (1..=BOARD_SIZE as u32).contains(&n);
+        (fits(width) && fits(height)).then_some(Self { width, height })
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    /// Every cell of the board, in reading order (row by row).
+    pub fn cells(self) -> impl Iterator<Item = Position> {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Position::new(x, y)))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -216,19 +756,255 @@ pub struct GameState {
     pub ships: Vec<Ship>,
     pub pepper: [u8; 16],
     pub grid: [[CellState; BOARD_SIZE]; BOARD_SIZE],
+    /// Shots in the order `apply_shot` accepted them, when recording is on
+    /// (`enable_history`). Not part of `serialize_for_commit()`, so it never
+    /// changes `commit()`; `None` means not recording.
+    #[serde(default)]
+    pub history: Option<Vec<(Position, HitType)>>,
+    /// Who fired at each cell and on which turn, for shots applied with
+    /// `apply_shot_by`; indexed `[y][x]` like `grid`. Not part of
+    /// `serialize_for_commit()`, so it never changes `commit()`.
+    #[serde(default)]
+    pub grid_meta: [[Option<ShotMeta>; BOARD_SIZE]; BOARD_SIZE],
+    /// Whether ships may share cells. Not part of `serialize_for_commit()`;
+    /// bound to the match by `FleetSpec::admits`, which the guest enforces
+    /// against the spec whose digest it commits.
+    #[serde(default)]
+    pub overlap: OverlapRule,
+    /// A 32-byte pepper replacing `pepper` in every commitment when set;
+    /// commitments then use `COMMIT_FORMAT_WIDE_PEPPER` and `pepper` is
+    /// ignored. Set by `with_pepper_from_seed`.
+    #[serde(default)]
+    pub wide_pepper: Option<[u8; 32]>,
+    /// Variant placement rules, enforced by `can_place_ship` and `check()`.
+    /// Not part of `serialize_for_commit()`; must equal
+    /// `FleetSpec::placement_rules`.
+    #[serde(default)]
+    pub placement_rules: Vec<PlacementConstraint>,
+    /// Whether `apply_shot` announces sinkings. Not part of
+    /// `serialize_for_commit()`, but taken from the committed `FleetSpec`.
+    /// The guest applies shots to the `GameState` it is given, so a proof's
+    /// `RoundCommit::hit` follows the same policy.
+    #[serde(default)]
+    pub reveal: RevealPolicy,
+    /// Whether a shot that sinks a ship also marks the water around it
+    /// (orthogonal and diagonal neighbours of the footprint) as `Miss`.
+    /// Only takes effect while `placement_rules` contains
+    /// `PlacementConstraint::NoTouching`, where those cells are known to be
+    /// empty anyway, and only for sinkings `reveal` announces. The marks
+    /// are grid cells, so they are covered by `commit()`; the flag itself
+    /// is fixed by the `FleetSpec`.
+    #[serde(default)]
+    pub mark_sunk_surroundings: bool,
+    /// Whether a shot that sinks a ship turns all of its `Hit` cells into
+    /// `CellState::Sunk`, for sinkings `reveal` announces. `Sunk` commits
+    /// like `Hit`, so this never changes `commit()`; not part of
+    /// `serialize_for_commit()`.
+    #[serde(default)]
+    pub mark_sunk_cells: bool,
+    /// Playable width and height; ships and shots must stay inside. Not
+    /// part of `serialize_for_commit()` (cells outside are always `Empty`);
+    /// `FleetSpec::dims` pins it.
+    #[serde(default)]
+    pub dims: BoardDims,
+}
+
+/// Whether ships may share cells. Under `Allowed` ("stacked" variants)
+/// placement and `check()` accept overlaps, and a shot on a shared cell
+/// hits every ship there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum OverlapRule {
+    #[default]
+    Forbidden,
+    Allowed,
+}
+
+/// What a shot's result reveals; see `GameState::reveal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum RevealPolicy {
+    /// The final segment of a ship reports `HitType::Sunk(ship_type)`.
+    #[default]
+    AnnounceSunk,
+    /// Strict fog of war: a sinking shot reports `HitType::Hit`, so the
+    /// shooter cannot tell it from any other hit. The ship is still sunk.
+    HitMissOnly,
+}
+
+impl RevealPolicy {
+    /// The result to report for a shot whose true outcome is `hit`.
+    pub fn reported(self, hit: HitType) -> HitType {
+        match (self, hit) {
+            (RevealPolicy::HitMissOnly, HitType::Sunk(_)) => HitType::Hit,
+            (_, hit) => hit,
+        }
+    }
+}
+
+/// What a proof's `RoundCommit::hit` says about a sinking. Chosen by the
+/// defender for each guest run; the shooter can tell which from the
+/// committed `hit` itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum DisclosureMode {
+    /// `HitType::Sunk(ship_type)` as `apply_shot` reports it.
+    #[default]
+    Open,
+    /// `HitType::SunkHidden`, committing to the ship type with a nonce
+    /// derived from `key`. The key must stay secret until the defender
+    /// opens the commitments after the game; anyone holding it can open
+    /// every one of them.
+    HiddenShipType { key: [u8; 32] },
+}
+
+impl DisclosureMode {
+    /// The result to publish for a shot whose result was `hit`.
+    pub fn disclose(&self, hit: HitType) -> HitType {
+        match (self, hit) {
+            (DisclosureMode::HiddenShipType { .. }, HitType::Sunk(st)) => HitType::SunkHidden(self.opening(st).expect("hidden mode always has an opening").commitment()),
+            (_, hit) => hit,
+        }
+    }
+
+    /// The opening for a hidden sinking of `ship_type`, or `None` under
+    /// `Open`. The nonce is `HMAC-SHA256(key, SHIP_TYPE_DOMAIN || index)`,
+    /// so the defender need not store anything besides the key.
+    pub fn opening(&self, ship_type: ShipType) -> Option<ShipTypeOpening> {
+        match self {
+            DisclosureMode::Open => None,
+            DisclosureMode::HiddenShipType { key } => Some(ShipTypeOpening { ship_type, nonce: hmac_sha256(key, &[SHIP_TYPE_DOMAIN, ship_type.index() as u8]) }),
+        }
+    }
+}
+
+/// Opens a `HitType::SunkHidden` commitment once the game is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShipTypeOpening {
+    pub ship_type: ShipType,
+    pub nonce: [u8; 32],
+}
+
+impl ShipTypeOpening {
+    /// SHA-256 of `SHIP_TYPE_DOMAIN`, `ShipType::index()` as a byte, then
+    /// `nonce`.
+    pub fn commitment(&self) -> Digest {
+        let mut out = Vec::with_capacity(2 + 32);
+        out.push(SHIP_TYPE_DOMAIN);
+        out.push(self.ship_type.index() as u8);
+        out.extend_from_slice(&self.nonce);
+        *risc0_zkvm::sha::Impl::hash_bytes(&out)
+    }
+
+    /// True if `hit` is a hidden sinking that this opening opens.
+    pub fn opens(&self, hit: &HitType) -> bool {
+        matches!(hit, HitType::SunkHidden(c) if *c == self.commitment())
+    }
+}
+
+/// A variant placement rule; see `GameState::placement_rules`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlacementConstraint {
+    /// At most this many ship cells in any single row or column, for
+    /// "spread out your fleet" variants.
+    MaxPerLine(u8),
+    /// Ships may not share an edge or a corner with another ship.
+    NoTouching,
+}
+
+/// When and by whom a cell was fired at; see `GameState::grid_meta`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ShotMeta {
+    pub turn: u32,
+    pub shooter: Player,
 }
 
 impl GameState {
     pub fn new(pepper: [u8; 16]) -> Self {
+        validate_ship_tables();
         Self {
             ships: Vec::new(),
             pepper,
             grid: [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE],
+            history: None,
+            grid_meta: [[None; BOARD_SIZE]; BOARD_SIZE],
+            overlap: OverlapRule::Forbidden,
+            wide_pepper: None,
+            placement_rules: Vec::new(),
+            reveal: RevealPolicy::AnnounceSunk,
+            mark_sunk_surroundings: false,
+            mark_sunk_cells: false,
+            dims: BoardDims::default(),
+        }
+    }
+
+    /// Empty `dims.width` x `dims.height` board.
+    pub fn with_dims(pepper: [u8; 16], dims: BoardDims) -> Self {
+        Self { dims, ..Self::new(pepper) }
+    }
+
+    /// Whether `pos` is on this board, i.e. inside `dims`.
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        self.dims.contains(pos)
+    }
+
+    /// Empty board whose 32-byte `wide_pepper` is derived from `seed` with
+    /// HKDF-SHA256, e.g. from external key material. The same seed always
+    /// yields the same pepper; the seed itself should be secret and high
+    /// entropy, since anyone holding it can recompute the pepper.
+    pub fn with_pepper_from_seed(seed: &[u8]) -> Self {
+        let mut pepper = [0u8; 32];
+        hkdf_sha256(PEPPER_KDF_SALT, seed, PEPPER_KDF_INFO, &mut pepper);
+        let mut state = GameState::new([0; 16]);
+        state.wide_pepper = Some(pepper);
+        state
+    }
+
+    /// Pepper bytes that go into commitments: `wide_pepper` if set,
+    /// otherwise `pepper`.
+    pub fn pepper_bytes(&self) -> &[u8] {
+        match &self.wide_pepper {
+            Some(wide) => wide,
+            None => &self.pepper,
+        }
+    }
+
+    /// Format byte leading this state's commitment encodings.
+    pub fn commit_format(&self) -> u8 {
+        if self.wide_pepper.is_some() { COMMIT_FORMAT_WIDE_PEPPER } else { COMMIT_FORMAT_VERSION }
+    }
+
+    /// Start recording accepted shots (see `history`). Shots applied before
+    /// this call are not recorded. Does nothing if already recording.
+    pub fn enable_history(&mut self) {
+        self.history.get_or_insert_with(Vec::new);
+    }
+
+    /// Recorded shots in the order they were taken; empty when not recording.
+    pub fn history(&self) -> &[(Position, HitType)] {
+        self.history.as_deref().unwrap_or(&[])
+    }
+
+    /// Rebuild the state by replaying `history()` onto a copy of this board
+    /// with the grid cleared and every ship unhit. For a state that recorded
+    /// from the start this reproduces `self`, including the history, but
+    /// not `grid_meta`.
+    pub fn replay_history(&self) -> GameState {
+        let mut fresh = GameState::new(self.pepper);
+        fresh.wide_pepper = self.wide_pepper;
+        fresh.ships = self.ships.iter().map(|s| Ship::new(s.ship_type, s.position, s.direction)).collect();
+        fresh.placement_rules = self.placement_rules.clone();
+        fresh.overlap = self.overlap;
+        fresh.reveal = self.reveal;
+        fresh.mark_sunk_surroundings = self.mark_sunk_surroundings;
+        fresh.mark_sunk_cells = self.mark_sunk_cells;
+        fresh.dims = self.dims;
+        fresh.enable_history();
+        for &(shot, _) in self.history() {
+            fresh.apply_shot(shot);
         }
+        fresh
     }
 
     // Note on `pepper` (ZK consideration):
-    // - `pepper` is included inside the serialized `GameState` used for
+    // - `pepper` is included in `serialize_for_commit()` and therefore in
     //   commitments. If the pepper must remain secret, the prover must
     //   not reveal it in outputs; if the pepper is public, it may be
     //   published alongside commitments. Keep prover and verifier logic
@@ -238,19 +1014,22 @@ impl GameState {
     /// `direction`. Checks include:
     ///  - start and end within board bounds
     ///  - that a ship of the same type isn't already placed
-    ///  - no coordinate overlap with existing ships
+    ///  - no coordinate overlap with existing ships, unless `overlap` is
+    ///    `OverlapRule::Allowed`
+    ///  - every rule in `placement_rules`, counting the existing ships plus
+    ///    the candidate
     pub fn can_place_ship(&self, ship_type: ShipType, pos: impl Into<Position>, direction: Direction) -> bool {
         let start: Position = pos.into();
         let size = ship_type.size();
 
         // Check start coordinates are within bounds
-        if !start.in_bounds() {
+        if !self.in_bounds(start) {
             return false;
         }
 
         // Calculate and check end coordinates based on direction
         let end = start.step(direction, (size - 1) as u32);
-        if !end.in_bounds() {
+        if !self.in_bounds(end) {
             return false;
         }
 
@@ -263,25 +1042,57 @@ impl GameState {
         let temp_ship = Ship::new(ship_type, start, direction);
         let new_coords = temp_ship.get_coordinates();
 
+        if !self.satisfies_placement_rules(&new_coords) {
+            return false;
+        }
+
+        if self.overlap == OverlapRule::Allowed {
+            return true;
+        }
+
         // Check if any of the coordinates overlap with existing ships
-        for existing_ship in &self.ships {
-            let existing_coords = existing_ship.get_coordinates();
-            for coord in &new_coords {
-                if existing_coords.contains(coord) {
-                    return false;
-                }
-            }
+        !self.ships.iter().any(|existing| new_coords.iter().any(|&c| existing.footprint_contains(c)))
+    }
+
+    /// Whether the placed ships plus the `extra` cells obey every rule in
+    /// `placement_rules`. Off-board cells are not counted.
+    fn satisfies_placement_rules(&self, extra: &[Position]) -> bool {
+        if self.placement_rules.is_empty() {
+            return true;
         }
+        let mut rows = [0u32; BOARD_SIZE];
+        let mut cols = [0u32; BOARD_SIZE];
+        let cells = self.ships.iter().flat_map(|s| s.get_coordinates()).chain(extra.iter().copied());
+        for p in cells.filter(Position::in_bounds) {
+            rows[p.y as usize] += 1;
+            cols[p.x as usize] += 1;
+        }
+        self.placement_rules.iter().all(|rule| match *rule {
+            PlacementConstraint::MaxPerLine(n) => rows.iter().chain(&cols).all(|&count| count <= n as u32),
+            PlacementConstraint::NoTouching => self.no_ships_touch(extra),
+        })
+    }
 
-        true
+    /// Whether no two ships, counting `extra` as one more ship, share a
+    /// cell, an edge or a corner.
+    fn no_ships_touch(&self, extra: &[Position]) -> bool {
+        let mut groups: Vec<Vec<Position>> = self.ships.iter().map(|s| s.get_coordinates()).collect();
+        if !extra.is_empty() {
+            groups.push(extra.to_vec());
+        }
+        let touch = |a: &Position, b: &Position| a.x.abs_diff(b.x) <= 1 && a.y.abs_diff(b.y) <= 1;
+        groups.iter().enumerate().all(|(i, a)| groups[i + 1..].iter().all(|b| !a.iter().any(|p| b.iter().any(|q| touch(p, q)))))
     }
 
     /// Attempt to place a ship; returns true on success. Delegates to
     /// `can_place_ship` for validation and mutates `self.ships` on success.
+    /// The ship is inserted in `ShipType::index()` order (see
+    /// `canonicalize`), so placement order does not affect `commit()`.
     pub fn place_ship(&mut self, ship_type: ShipType, pos: impl Into<Position>, direction: Direction) -> bool {
         let pos: Position = pos.into();
         if self.can_place_ship(ship_type, pos, direction) {
-            self.ships.push(Ship::new(ship_type, pos, direction));
+            let at = self.ships.partition_point(|s| s.ship_type.index() <= ship_type.index());
+            self.ships.insert(at, Ship::new(ship_type, pos, direction));
             true
         } else {
             false
@@ -302,14 +1113,62 @@ impl GameState {
         all_ok
     }
 
+    /// Quick check that `spec`'s fleet can be laid out on this board, in
+    /// the part of it inside `spec.dims`, under its `overlap` and
+    /// `placement_rules` (ships already placed are ignored). False if a
+    /// ship is longer than the region, the fleet has more cells than the
+    /// region (unless overlap is allowed), or a greedy layout, largest
+    /// ships first in reading order, does not fit them all. The greedy
+    /// pass can miss a layout for very dense fleets, so a false means "do
+    /// not bother retrying", not a proof.
+    pub fn fleet_is_feasible(&self, spec: &FleetSpec) -> bool {
+        self.fleet_fit(spec).is_ok()
+    }
+
+    /// `fleet_is_feasible` with the reason it fails.
+    fn fleet_fit(&self, spec: &FleetSpec) -> Result<(), PlacementError> {
+        spec.validate()?;
+        let (width, height) = (self.dims.width.min(spec.dims.width), self.dims.height.min(spec.dims.height));
+        if let Some(&st) = spec.ships.iter().find(|st| st.size() as u32 > width.max(height)) {
+            return Err(PlacementError::Unplaceable(st));
+        }
+        let cells: u32 = spec.ships.iter().map(|st| st.size() as u32).sum();
+        if self.overlap != OverlapRule::Allowed && cells > width * height {
+            return Err(PlacementError::Overfull { cells, available: width * height });
+        }
+
+        let mut scratch = GameState { ships: Vec::new(), ..self.clone() };
+        let mut by_size = spec.ships.clone();
+        by_size.sort_by_key(|st| std::cmp::Reverse(st.size()));
+        for st in by_size {
+            let placed = self.dims.cells().filter(|p| p.x < width && p.y < height).any(|pos| {
+                [Direction::Horizontal, Direction::Vertical].into_iter().any(|dir| {
+                    Ship::new(st, pos, dir).get_coordinates().iter().all(|c| c.x < width && c.y < height) && scratch.place_ship(st, pos, dir)
+                })
+            });
+            if !placed {
+                return Err(PlacementError::Unplaceable(st));
+            }
+        }
+        Ok(())
+    }
+
+    /// The classic fleet on this board's own dimensions.
     #[cfg(feature = "rand")]
+    fn own_fleet(&self) -> FleetSpec {
+        FleetSpec::with_ships(ShipType::ALL.to_vec(), self.dims)
+    }
+
     #[cfg(feature = "rand")]
     /// Try to place all ships randomly using the provided RNG. On failure
-    /// clears `self.ships` and returns false.
+    /// clears `self.ships` and returns false, straight away if
+    /// `fleet_is_feasible` says the fleet cannot fit.
     pub fn place_ships_randomly<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
-        let mut positions: Vec<Position> = (0..BOARD_SIZE as u32)
-            .flat_map(|x| (0..BOARD_SIZE as u32).map(move |y| Position::new(x, y)))
-            .collect();
+        if !self.fleet_is_feasible(&self.own_fleet()) {
+            self.ships.clear();
+            return false;
+        }
+        let mut positions: Vec<Position> = self.dims.cells().collect();
         positions.shuffle(rng);
 
         self.ships.clear();
@@ -342,17 +1201,138 @@ impl GameState {
         true
     }
 
+    #[cfg(feature = "rand")]
+    /// Place all ships so that every legal layout is equally likely.
+    ///
+    /// `place_ships_randomly` puts each ship at the first free spot in a
+    /// shuffled scan, horizontal first, so orientations are skewed and
+    /// later ships are squeezed by earlier ones. Here every ship instead
+    /// draws one of its in-bounds placements uniformly, and a layout with
+    /// any overlap (or broken `placement_rules`) is thrown away and redrawn
+    /// from scratch. Rejection sampling keeps the accepted layouts uniform.
+    /// Gives up after a bounded number of layouts, or at once if
+    /// `fleet_is_feasible` fails, clearing `self.ships` and returning false.
+    pub fn place_ships_uniformly<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        const MAX_LAYOUTS: u32 = 10_000;
+        if !self.fleet_is_feasible(&self.own_fleet()) {
+            self.ships.clear();
+            return false;
+        }
+        let candidates: Vec<Vec<(Position, Direction)>> = ShipType::ALL
+            .iter()
+            .map(|&ship_type| {
+                self.dims
+                    .cells()
+                    .flat_map(|pos| [(pos, Direction::Horizontal), (pos, Direction::Vertical)])
+                    .filter(|&(pos, dir)| Ship::new(ship_type, pos, dir).get_coordinates().iter().all(|&c| self.in_bounds(c)))
+                    .collect()
+            })
+            .collect();
+
+        for _ in 0..MAX_LAYOUTS {
+            self.ships.clear();
+            let all_placed = ShipType::ALL.iter().zip(&candidates).all(|(&ship_type, options)| {
+                let &(pos, dir) = options.choose(rng).expect("every ship fits on an empty board");
+                self.place_ship(ship_type, pos, dir)
+            });
+            if all_placed {
+                return true;
+            }
+        }
+
+        self.ships.clear();
+        false
+    }
+
+    #[cfg(feature = "rand")]
+    /// Like `place_ships_randomly`, but keeps the ships already placed and
+    /// only places the missing types around them, e.g. to finish a board a
+    /// player started by hand. On failure the fleet is restored to what it
+    /// was and false is returned.
+    pub fn place_remaining_randomly<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        let mut positions: Vec<Position> = self.dims.cells().collect();
+        positions.shuffle(rng);
+
+        let kept = self.ships.clone();
+        for ship_type in ShipType::ALL {
+            if self.ships.iter().any(|s| s.ship_type == ship_type) {
+                continue;
+            }
+            let placed = positions.iter().any(|&pos| {
+                [Direction::Horizontal, Direction::Vertical].into_iter().any(|dir| self.place_ship(ship_type, pos, dir))
+            });
+            if !placed {
+                self.ships = kept;
+                return false;
+            }
+        }
+        true
+    }
+
+    #[cfg(feature = "rand")]
+    /// Like `place_ships_randomly`, but every ship must avoid the cells
+    /// forbidden by `constraints`. Each attempt reshuffles candidate
+    /// positions; after `constraints.max_attempts` failed attempts
+    /// `self.ships` is cleared and false is returned. A fleet that fails
+    /// `fleet_is_feasible` even without the constraints returns false
+    /// without any attempts.
+    pub fn place_ships_randomly_constrained<R: Rng + ?Sized>(&mut self, rng: &mut R, constraints: &PlacementConstraints) -> bool {
+        if !self.fleet_is_feasible(&self.own_fleet()) {
+            self.ships.clear();
+            return false;
+        }
+        let mut positions: Vec<Position> = self.dims.cells().collect();
+
+        for _ in 0..constraints.max_attempts {
+            positions.shuffle(rng);
+            self.ships.clear();
+            let all_placed = [
+                ShipType::Carrier,
+                ShipType::Battleship,
+                ShipType::Cruiser,
+                ShipType::Submarine,
+                ShipType::Destroyer,
+            ]
+            .into_iter()
+            .all(|ship_type| {
+                positions.iter().any(|&pos| {
+                    [Direction::Horizontal, Direction::Vertical].into_iter().any(|dir| {
+                        self.can_place_ship(ship_type, pos, dir)
+                            && Ship::new(ship_type, pos, dir).get_coordinates().iter().all(|&c| constraints.allows(ship_type, c))
+                            && self.place_ship(ship_type, pos, dir)
+                    })
+                })
+            });
+            if all_placed {
+                return true;
+            }
+        }
+
+        self.ships.clear();
+        false
+    }
+
+    /// True once exactly the ships of `fleet` are placed, inside its
+    /// region, and the board passes `check()`. Games must not start (or
+    /// commit) before this holds.
+    pub fn is_placement_complete(&self, fleet: &FleetSpec) -> bool {
+        self.ships.len() == fleet.ships.len() && fleet.admits(self) && self.check()
+    }
+
     /// Run a full consistency check on the game state:
     /// - all ships within bounds
-    /// - no overlaps
-    /// - exactly one of each ship type present
+    /// - no overlaps (unless `overlap` is `OverlapRule::Allowed`)
+    /// - at most one ship of each type
+    /// - every rule in `placement_rules`
+    ///
+    /// It does not ask for any particular fleet; see `is_placement_complete`.
     pub fn check(&self) -> bool {
         // Check all ships are within bounds and don't overlap
         for (i, ship_i) in self.ships.iter().enumerate() {
             let coords_i = ship_i.get_coordinates();
 
             // Check bounds
-            if coords_i.iter().any(|pos| !pos.in_bounds()) {
+            if coords_i.iter().any(|&pos| !self.in_bounds(pos)) {
                 return false;
             }
 
@@ -363,18 +1343,13 @@ impl GameState {
                 }
 
                 let coords_j = ship_j.get_coordinates();
-                if coords_i.iter().any(|coord| coords_j.contains(coord)) {
+                if self.overlap == OverlapRule::Forbidden && coords_i.iter().any(|coord| coords_j.contains(coord)) {
                     return false;
                 }
             }
         }
 
-        // Check if all ship types are present
-        let mut found_types = [false; NUM_SHIPS];
-        for ship in &self.ships {
-            found_types[ship.ship_type.index()] = true;
-        }
-        found_types.iter().all(|&present| present)
+        self.satisfies_placement_rules(&[])
     }
 
     /// Apply a shot at `shot` and update `self.grid` and any hit ship.
@@ -385,77 +1360,1109 @@ impl GameState {
     /// - `Some(HitType::Miss)` if in-bounds and no ship was hit
     /// - `None` for out-of-bounds shots or if the cell was already shot
     pub fn apply_shot(&mut self, shot: impl Into<Position>) -> Option<HitType> {
-        let shot: Position = shot.into();
-        if !shot.in_bounds() {
+        self.apply_shot_with_meta(shot.into(), None, self.reveal)
+    }
+
+    /// Whether `apply_shot` would accept a shot at `shot`, and if not, why.
+    pub fn check_shot(&self, shot: Position) -> Result<(), ShotError> {
+        if !self.in_bounds(shot) {
+            return Err(ShotError::OutOfBounds);
+        }
+        if self.grid[shot.y as usize][shot.x as usize] != CellState::Empty {
+            return Err(ShotError::AlreadyShot);
+        }
+        Ok(())
+    }
+
+    /// `apply_shot` that says why a rejected shot was rejected.
+    pub fn try_apply_shot(&mut self, shot: impl Into<Position>) -> Result<HitType, ShotError> {
+        let shot = shot.into();
+        self.check_shot(shot)?;
+        Ok(self.apply_shot(shot).expect("check_shot accepted the shot"))
+    }
+
+    /// `apply_shot` followed by `commit()`: the shot's result and the
+    /// commitment to the board after it, which is unchanged if the shot
+    /// was rejected. Callers that need both, like the guest, can carry the
+    /// digest forward as the next shot's starting commitment instead of
+    /// committing again.
+    pub fn apply_shot_committed(&mut self, shot: impl Into<Position>) -> (Option<HitType>, Digest) {
+        let hit = self.apply_shot(shot);
+        (hit, self.commit())
+    }
+
+    /// `apply_shot` under `RevealPolicy::HitMissOnly` whatever `reveal`
+    /// says: a sinking shot returns `HitType::Hit`.
+    pub fn apply_shot_no_reveal(&mut self, shot: impl Into<Position>) -> Option<HitType> {
+        self.apply_shot_with_meta(shot.into(), None, RevealPolicy::HitMissOnly)
+    }
+
+    /// `apply_shot`, also recording `meta` in `grid_meta` if the shot is
+    /// accepted.
+    pub fn apply_shot_by(&mut self, shot: impl Into<Position>, meta: ShotMeta) -> Option<HitType> {
+        self.apply_shot_with_meta(shot.into(), Some(meta), self.reveal)
+    }
+
+    fn apply_shot_with_meta(&mut self, shot: Position, meta: Option<ShotMeta>, reveal: RevealPolicy) -> Option<HitType> {
+        if self.check_shot(shot).is_err() {
             return None;
         }
 
         let cell = &mut self.grid[shot.y as usize][shot.x as usize];
-        if *cell != CellState::Empty {
-            return None; // Already shot here
-        }
 
-        // Check if we hit any ships
+        // Check if we hit any ships. With stacked ships every ship on the
+        // cell takes the hit and the first one sunk is reported.
+        let mut hit = HitType::Miss;
         for ship in &mut self.ships {
             if ship.check_hit(shot) {
-                *cell = CellState::Hit;
-                if ship.is_sunk() {
-                    return Some(HitType::Sunk(ship.ship_type));
+                if !matches!(hit, HitType::Sunk(_)) {
+                    hit = if ship.is_sunk() { HitType::Sunk(ship.ship_type) } else { HitType::Hit };
                 }
-                return Some(HitType::Hit);
+                if self.overlap == OverlapRule::Forbidden {
+                    break;
+                }
+            }
+        }
+        *cell = if hit == HitType::Miss { CellState::Miss } else { CellState::Hit };
+        self.grid_meta[shot.y as usize][shot.x as usize] = meta;
+        let hit = reveal.reported(hit);
+        if self.mark_sunk_cells {
+            for pos in self.sunk_footprint(&hit) {
+                self.grid[pos.y as usize][pos.x as usize] = CellState::Sunk;
+            }
+        }
+        for water in self.sunk_surroundings(&hit) {
+            let cell = &mut self.grid[water.y as usize][water.x as usize];
+            if *cell == CellState::Empty {
+                *cell = CellState::Miss;
             }
         }
 
-        *cell = CellState::Miss;
-        Some(HitType::Miss)
+        if let Some(history) = &mut self.history {
+            history.push((shot, hit.clone()));
+        }
+        Some(hit)
+    }
+
+    /// Sort `ships` by `ShipType::index()`. The commitment encodings list
+    /// ships in `ships` order, so boards with the same layout only commit
+    /// equal once canonical. `place_ship` keeps this order already; call
+    /// this on boards whose `ships` were built or edited directly.
+    pub fn canonicalize(&mut self) {
+        self.ships.sort_by_key(|s| s.ship_type.index());
+    }
+
+    /// "Same board" equality: true if both boards `commit()` the same once
+    /// canonicalized. Unlike the derived `==` it ignores ship order and
+    /// everything outside `serialize_for_commit()` (history, `grid_meta`,
+    /// rule flags, `dims`), and a `Sunk` cell equals a `Hit`.
+    pub fn board_eq(&self, other: &Self) -> bool {
+        let canonical = |state: &Self| {
+            let mut state = state.clone();
+            state.canonicalize();
+            state.serialize_for_commit()
+        };
+        canonical(self) == canonical(other)
     }
 
+    /// Hash of `serialize_for_commit()`. Host and guest must agree on this,
+    /// so the encoding is versioned rather than derived from the struct.
     pub fn commit(&self) -> Digest {
-        let bytes = bincode::serialize(self).expect("serialization should succeed");
-        *risc0_zkvm::sha::Impl::hash_bytes(&bytes)
+        *risc0_zkvm::sha::Impl::hash_bytes(&self.serialize_for_commit())
     }
-}
 
-#[cfg(feature = "rand")]
-impl Distribution<GameState> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> GameState {
-        let mut positions: Vec<Position> = (0..BOARD_SIZE as u32)
-            .flat_map(|x| (0..BOARD_SIZE as u32).map(move |y| Position::new(x, y)))
+    /// Board with the ships drawn in `layout`: `BOARD_SIZE` rows of
+    /// `BOARD_SIZE` cells, `.` for water and a ship's `ShipType::letter()`
+    /// for each of its cells. Surrounding whitespace and blank lines are
+    /// ignored, so indented raw strings work. Each letter's cells must form
+    /// one straight run of the ship's length; ships not drawn are left out.
+    /// The pepper is all zeros and no shots are recorded.
+    pub fn from_ascii(layout: &str) -> Result<GameState, ParseError> {
+        let rows: Vec<&str> = layout.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        if rows.len() != BOARD_SIZE {
+            return Err(ParseError::RowCount(rows.len()));
+        }
+        // Cells of each ship type, in reading order
+        let mut cells: [Vec<Position>; NUM_SHIPS] = Default::default();
+        for (y, row) in rows.iter().enumerate() {
+            let width = row.chars().count();
+            if width != BOARD_SIZE {
+                return Err(ParseError::RowWidth { row: y, width });
+            }
+            for (x, ch) in row.chars().enumerate() {
+                let pos = Position::new(x as u32, y as u32);
+                if ch == '.' {
+                    continue;
+                }
+                match ShipType::from_letter(ch) {
+                    Some(t) => cells[t.index()].push(pos),
+                    None => return Err(ParseError::UnknownChar { pos, ch }),
+                }
+            }
+        }
+
+        let mut state = GameState::new([0; 16]);
+        for ship_type in ShipType::ALL {
+            let run = &cells[ship_type.index()];
+            if run.is_empty() {
+                continue;
+            }
+            if run.len() != ship_type.size() as usize {
+                return Err(ParseError::WrongLength { ship_type, found: run.len() });
+            }
+            // In reading order a straight ship starts at its first cell
+            let direction = if run[1].x == run[0].x { Direction::Vertical } else { Direction::Horizontal };
+            if Ship::new(ship_type, run[0], direction).get_coordinates() != *run {
+                return Err(ParseError::NotStraight(ship_type));
+            }
+            // One letter per cell, so ships cannot overlap
+            let placed = state.place_ship(ship_type, run[0], direction);
+            debug_assert!(placed);
+        }
+        Ok(state)
+    }
+
+    /// Compact text form of the board in `from_ascii`'s grid: one line per
+    /// row, `X` for a hit, `#` for a cell of a sunk ship (`CellState::Sunk`),
+    /// `o` for a miss, a ship's `ShipType::letter()` for an unshot ship cell
+    /// when `reveal` is set, and `.` otherwise. Unlike `display_board` in
+    /// the host there are no headers, so the revealed output of an unshot
+    /// board parses back with `from_ascii` to the same layout. Once any
+    /// shot has landed it does not: `from_ascii` rejects `X`, `#` and `o`.
+    pub fn to_ascii(&self, reveal: bool) -> String {
+        let occupancy = self.occupancy();
+        let mut out = String::with_capacity(BOARD_SIZE * (BOARD_SIZE + 1));
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                out.push(match (cell, occupancy[y][x]) {
+                    (CellState::Hit, _) => 'X',
+                    (CellState::Sunk, _) => '#',
+                    (CellState::Miss, _) => 'o',
+                    (CellState::Empty, Some(t)) if reveal => t.letter(),
+                    (CellState::Empty, _) => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Whether this board is the one `expected` commits to, i.e.
+    /// `commit() == *expected`. A verifier checks a board revealed at the
+    /// end of the game with this against the last commitment it accepted.
+    pub fn matches_commit(&self, expected: &Digest) -> bool {
+        self.commit() == *expected
+    }
+
+    /// Stable byte encoding of the commitment-relevant fields, independent
+    /// of `#[derive(Serialize)]`. Layout (`COMMIT_FORMAT_VERSION` 1, all
+    /// integers little-endian):
+    ///
+    /// 1. version byte
+    /// 2. ship count as u32, then per ship: `ShipType::index()` u8, x u32,
+    ///    y u32, direction u8 (0 = Horizontal, 1 = Vertical), hits u8
+    /// 3. the 16 pepper bytes
+    /// 4. the grid in row-major order, one byte per cell
+    ///    (0 = Empty, 1 = Miss, 2 = Hit)
+    ///
+    /// A state with a `wide_pepper` leads with `COMMIT_FORMAT_WIDE_PEPPER`
+    /// and writes its 32 bytes in part 3 instead.
+    ///
+    /// Adding fields to `GameState` does not change this output; changing
+    /// the layout must bump `COMMIT_FORMAT_VERSION`.
+    pub fn serialize_for_commit(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 4 + self.ships.len() * 11 + self.pepper_bytes().len() + BOARD_SIZE * BOARD_SIZE);
+        self.encode_fleet(&mut out);
+        for row in &self.grid {
+            for cell in row {
+                out.push(cell_byte(cell));
+            }
+        }
+        out
+    }
+
+    /// Placement commitment: a hash of the ship layout and pepper only.
+    ///
+    /// Unlike `commit()` it ignores the grid and each ship's `hits`, so it
+    /// stays the same for the whole game. Sent as the `BoardReady`
+    /// commitment; a reveal-phase verifier recomputes it from the revealed
+    /// layout. Layout: `PLACEMENT_DOMAIN`, `COMMIT_FORMAT_VERSION`, ship
+    /// count as u32, then per ship `ShipType::index()` u8, x u32, y u32,
+    /// direction u8, then the pepper bytes (integers little-endian). The
+    /// version byte is `commit_format()`, as in `serialize_for_commit`.
+    pub fn hash_ships_only(&self) -> Digest {
+        let mut out = Vec::with_capacity(2 + 4 + self.ships.len() * 10 + self.pepper_bytes().len());
+        out.push(PLACEMENT_DOMAIN);
+        out.push(self.commit_format());
+        out.extend_from_slice(&(self.ships.len() as u32).to_le_bytes());
+        for ship in &self.ships {
+            out.push(ship.ship_type.index() as u8);
+            out.extend_from_slice(&ship.position.x.to_le_bytes());
+            out.extend_from_slice(&ship.position.y.to_le_bytes());
+            out.push(direction_byte(ship.direction));
+        }
+        out.extend_from_slice(self.pepper_bytes());
+        *risc0_zkvm::sha::Impl::hash_bytes(&out)
+    }
+
+    /// Parts 1-3 of the `serialize_for_commit` layout: everything but the grid.
+    fn encode_fleet(&self, out: &mut Vec<u8>) {
+        out.push(self.commit_format());
+        out.extend_from_slice(&(self.ships.len() as u32).to_le_bytes());
+        for ship in &self.ships {
+            out.push(ship.ship_type.index() as u8);
+            out.extend_from_slice(&ship.position.x.to_le_bytes());
+            out.extend_from_slice(&ship.position.y.to_le_bytes());
+            out.push(direction_byte(ship.direction));
+            out.push(ship.hits);
+        }
+        out.extend_from_slice(self.pepper_bytes());
+    }
+
+    // ------------------------------------------------------------------
+    // Merkle-ized commitment
+    //
+    // An alternative to `commit()` for long games: the grid is a Merkle
+    // tree with one leaf per cell, so a shot only rehashes the path from
+    // its cell to the root. `merkle_root() = hash_pair(fleet_digest(),
+    // grid_merkle_root())`. The fleet (ships + pepper) is small and is
+    // rehashed whole because a hit also changes a ship's `hits` mask.
+    // ------------------------------------------------------------------
+
+    /// Hash of the ships and pepper (the `serialize_for_commit` layout
+    /// without the grid).
+    pub fn fleet_digest(&self) -> Digest {
+        let mut out = Vec::with_capacity(1 + 4 + self.ships.len() * 11 + self.pepper_bytes().len());
+        self.encode_fleet(&mut out);
+        *risc0_zkvm::sha::Impl::hash_bytes(&out)
+    }
+
+    /// All levels of the grid tree, leaves first and the root last.
+    fn grid_tree(&self) -> Vec<Vec<Digest>> {
+        let mut level: Vec<Digest> = (0..GRID_TREE_LEAVES)
+            .map(|i| {
+                if i < BOARD_SIZE * BOARD_SIZE {
+                    cell_leaf(&self.grid[i / BOARD_SIZE][i % BOARD_SIZE])
+                } else {
+                    Digest::ZERO
+                }
+            })
             .collect();
-        positions.shuffle(rng);
+        let mut levels = Vec::with_capacity(GRID_TREE_DEPTH + 1);
+        while level.len() > 1 {
+            let next = level.chunks(2).map(|pair| *risc0_zkvm::sha::Impl::hash_pair(&pair[0], &pair[1])).collect();
+            levels.push(level);
+            level = next;
+        }
+        levels.push(level);
+        levels
+    }
 
-        let mut state = GameState::new(rng.gen());
-        
-        // Place each ship type using the existing validation helpers so we
-        // ensure bounds and overlap rules are respected. Try positions in
-        // shuffled order and both orientations; panic if we cannot place a
-        // required ship.
-        for ship_type in [
-            ShipType::Carrier,
-            ShipType::Battleship,
-            ShipType::Cruiser,
-            ShipType::Submarine,
-            ShipType::Destroyer,
-        ] {
-            let mut placed = false;
-            for &pos in &positions {
-                for dir in [Direction::Horizontal, Direction::Vertical] {
-                    if state.place_ship(ship_type, pos, dir) {
-                        placed = true;
-                        break;
-                    }
+    /// Root of the grid tree, computed from scratch.
+    pub fn grid_merkle_root(&self) -> Digest {
+        self.grid_tree().last().expect("tree has a root level")[0]
+    }
+
+    /// Merkle commitment to the whole state, computed from scratch.
+    pub fn merkle_root(&self) -> Digest {
+        *risc0_zkvm::sha::Impl::hash_pair(&self.fleet_digest(), &self.grid_merkle_root())
+    }
+
+    /// Inclusion proof for the cell at `pos`, or `None` if out of bounds.
+    pub fn cell_proof(&self, pos: Position) -> Option<CellProof> {
+        if !pos.in_bounds() {
+            return None;
+        }
+        let tree = self.grid_tree();
+        let mut index = pos.y as usize * BOARD_SIZE + pos.x as usize;
+        let mut siblings = [Digest::ZERO; GRID_TREE_DEPTH];
+        for (depth, sibling) in siblings.iter_mut().enumerate() {
+            *sibling = tree[depth][index ^ 1];
+            index /= 2;
+        }
+        Some(CellProof { position: pos, cell: self.grid[pos.y as usize][pos.x as usize], siblings })
+    }
+
+    /// Apply a shot using an inclusion proof instead of rehashing the state.
+    ///
+    /// `proof` must open the shot cell against `grid_root`, the current
+    /// grid root. On success the shot is applied exactly as `apply_shot`
+    /// would and the result, the new `merkle_root()`, and the updated proof
+    /// for the cell are returned. The cost is one path recomputation to
+    /// check `proof`, one to derive the new root, and one `fleet_digest`,
+    /// independent of the board size. Returns `None` if the proof is for
+    /// another cell, does not match `grid_root`, or the shot is invalid.
+    pub fn apply_shot_merkle(&mut self, shot: Position, proof: &CellProof, grid_root: &Digest) -> Option<(HitType, Digest, CellProof)> {
+        if proof.position != shot || !shot.in_bounds() || proof.cell != self.grid[shot.y as usize][shot.x as usize] || !proof.verify(grid_root) {
+            return None;
+        }
+        let hit = self.apply_shot(shot)?;
+        let updated = CellProof { position: shot, cell: self.grid[shot.y as usize][shot.x as usize], siblings: proof.siblings };
+        let root = *risc0_zkvm::sha::Impl::hash_pair(&self.fleet_digest(), &updated.grid_root());
+        Some((hit, root, updated))
+    }
+
+    /// Cells to reveal for a shot that resulted in `hit`: the sunk ship's
+    /// footprint for `Sunk`, nothing otherwise. Used by the guest to fill
+    /// `RoundCommit::sunk_cells`.
+    pub fn sunk_footprint(&self, hit: &HitType) -> Vec<Position> {
+        match hit {
+            HitType::Sunk(st) => self.ships.iter().find(|s| s.ship_type == *st).map(|s| s.get_coordinates()).unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Water around the ship sunk by a shot that resulted in `hit`, in
+    /// reading order: the in-bounds cells touching its footprint that no
+    /// ship covers. Empty unless `mark_sunk_surroundings` is on, the
+    /// `NoTouching` rule is active and `hit` is `Sunk`. `apply_shot` marks
+    /// these cells `Miss`; the guest uses this to fill
+    /// `RoundCommit::revealed_water`.
+    pub fn sunk_surroundings(&self, hit: &HitType) -> Vec<Position> {
+        if !self.mark_sunk_surroundings || !self.placement_rules.contains(&PlacementConstraint::NoTouching) {
+            return Vec::new();
+        }
+        let footprint = self.sunk_footprint(hit);
+        let mut water: Vec<Position> = footprint.iter().flat_map(Position::all_neighbors).filter(|p| self.in_bounds(*p) && !footprint.contains(p) && self.ship_at(*p).is_none()).collect();
+        water.sort_by_key(|p| (p.y, p.x));
+        water.dedup();
+        water
+    }
+
+    /// The grid alone, one byte per cell in row-major order: 0 for
+    /// `Empty`, 1 for `Miss`, 2 for `Hit` (the cell bytes of
+    /// `serialize_for_commit()`). Ships, pepper and everything else are
+    /// left out, so boards that look the same to a shooter have equal
+    /// snapshots.
+    pub fn grid_snapshot(&self) -> Vec<u8> {
+        self.grid.iter().flatten().map(cell_byte).collect()
+    }
+
+    /// Overwrite the grid from a `grid_snapshot()`. Only `grid` changes:
+    /// ships keep their hit masks, so this is for shooter-side views and
+    /// other grid-only states. Rejects a snapshot of the wrong length or
+    /// with a byte other than 0, 1 or 2, leaving the grid unchanged.
+    pub fn apply_grid_snapshot(&mut self, snapshot: &[u8]) -> Result<(), DecodeError> {
+        if snapshot.len() != BOARD_SIZE * BOARD_SIZE {
+            return Err(DecodeError::Malformed(format!("grid snapshot has {} cells, expected {}", snapshot.len(), BOARD_SIZE * BOARD_SIZE)));
+        }
+        let mut grid = [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE];
+        for (i, &b) in snapshot.iter().enumerate() {
+            grid[i / BOARD_SIZE][i % BOARD_SIZE] = match b {
+                0 => CellState::Empty,
+                1 => CellState::Miss,
+                2 => CellState::Hit,
+                _ => return Err(DecodeError::Malformed(format!("grid snapshot byte {} at cell {} is not a cell state", b, i))),
+            };
+        }
+        self.grid = grid;
+        Ok(())
+    }
+
+    /// This board flipped left to right (`x` becomes `width - 1 - x`).
+    /// Ships keep their direction and hits, the grid, `grid_meta` and
+    /// history move with them, and everything else is copied, so a board
+    /// that passes `check()` still does. Applying it twice gives `self`
+    /// back. The commitment changes, of course. `None` if `dims` is not a
+    /// valid `BoardDims` or a ship or history entry lies outside it, as
+    /// such a board has no mirror image.
+    pub fn mirror_horizontal(&self) -> Option<GameState> {
+        self.transformed(true, false)
+    }
+
+    /// This board flipped top to bottom (`y` becomes `height - 1 - y`);
+    /// otherwise as `mirror_horizontal`.
+    pub fn mirror_vertical(&self) -> Option<GameState> {
+        self.transformed(false, true)
+    }
+
+    /// This board turned half a turn: both mirrors at once. Its own
+    /// inverse.
+    pub fn rotate_180(&self) -> Option<GameState> {
+        self.transformed(true, true)
+    }
+
+    fn transformed(&self, flip_x: bool, flip_y: bool) -> Option<GameState> {
+        let (width, height) = (self.dims.width, self.dims.height);
+        BoardDims::new(width, height)?;
+        // Checked so a ship starting near u32::MAX cannot overflow its end
+        let ship_fits = |s: &Ship| {
+            let last = s.ship_type.size() as u32 - 1;
+            match s.direction {
+                Direction::Horizontal => s.position.x.checked_add(last).is_some_and(|end| end < width) && s.position.y < height,
+                Direction::Vertical => s.position.y.checked_add(last).is_some_and(|end| end < height) && s.position.x < width,
+            }
+        };
+        if !self.ships.iter().all(ship_fits) || !self.history().iter().all(|&(pos, _)| self.dims.contains(pos)) {
+            return None;
+        }
+        let map = |p: Position| Position::new(if flip_x { width - 1 - p.x } else { p.x }, if flip_y { height - 1 - p.y } else { p.y });
+        let mut out = self.clone();
+        out.ships = self
+            .ships
+            .iter()
+            .map(|s| {
+                let size = s.ship_type.size();
+                // A flip along the ship's own axis swaps its ends, so the
+                // old end is the new start and the segments run backwards
+                let reversed = match s.direction { Direction::Horizontal => flip_x, Direction::Vertical => flip_y };
+                if reversed {
+                    let end = s.position.step(s.direction, size as u32 - 1);
+                    Ship { position: map(end), hits: s.hits.reverse_bits() >> (8 - size), ..s.clone() }
+                } else {
+                    Ship { position: map(s.position), ..s.clone() }
                 }
-                if placed {
-                    break;
+            })
+            .collect();
+        out.grid = [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE];
+        out.grid_meta = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for p in self.dims.cells() {
+            let q = map(p);
+            out.grid[q.y as usize][q.x as usize] = self.grid[p.y as usize][p.x as usize];
+            out.grid_meta[q.y as usize][q.x as usize] = self.grid_meta[p.y as usize][p.x as usize];
+        }
+        if let Some(history) = &mut out.history {
+            for (pos, _) in history.iter_mut() {
+                *pos = map(*pos);
+            }
+        }
+        Some(out)
+    }
+
+    /// Record an observed cell, as a shooter does in its view of the
+    /// opponent's board. Overwriting an `Empty` cell or repeating the same
+    /// observation is fine; changing an already-known cell means the
+    /// opponent (or local bookkeeping) contradicted itself and is rejected
+    /// with the grid unchanged. Panics if `pos` is out of bounds.
+    pub fn merge_observation(&mut self, pos: Position, cell_state: CellState) -> Result<(), Contradiction> {
+        let cell = &mut self.grid[pos.y as usize][pos.x as usize];
+        if *cell != CellState::Empty && *cell != cell_state {
+            return Err(Contradiction { position: pos, recorded: *cell, claimed: cell_state });
+        }
+        *cell = cell_state;
+        Ok(())
+    }
+
+    /// Type of the ship covering `pos`, if any. For whole-board passes
+    /// build `occupancy()` once instead of calling this per cell.
+    pub fn ship_at(&self, pos: Position) -> Option<ShipType> {
+        self.ships.iter().find(|s| s.footprint_contains(pos)).map(|s| s.ship_type)
+    }
+
+    /// Ship type covering each cell, indexed `[y][x]` like `grid`. Built on
+    /// each call rather than cached, since `ships` is public and may change
+    /// between calls. Off-board segments are skipped.
+    pub fn occupancy(&self) -> [[Option<ShipType>; BOARD_SIZE]; BOARD_SIZE] {
+        let mut map = [[None; BOARD_SIZE]; BOARD_SIZE];
+        for ship in &self.ships {
+            for p in ship.get_coordinates().into_iter().filter(Position::in_bounds) {
+                map[p.y as usize][p.x as usize] = Some(ship.ship_type);
+            }
+        }
+        map
+    }
+
+    /// True when at least one ship is placed and every placed ship is sunk.
+    /// An empty fleet is never considered defeated.
+    pub fn all_ships_sunk(&self) -> bool {
+        !self.ships.is_empty() && self.ships.iter().all(|s| s.is_sunk())
+    }
+
+    /// Number of placed ships that are still afloat.
+    pub fn ships_remaining(&self) -> usize {
+        self.ships.iter().filter(|s| !s.is_sunk()).count()
+    }
+
+    /// `(ship_type, hits, size)` for every placed ship, in
+    /// `ShipType::index()` order; `hits == size` once it is sunk.
+    pub fn fleet_health(&self) -> Vec<(ShipType, u8, u8)> {
+        let mut health: Vec<(ShipType, u8, u8)> = self
+            .ships
+            .iter()
+            .map(|s| {
+                let size = s.ship_type.size();
+                let mask = ((1u16 << size) - 1) as u8;
+                (s.ship_type, (s.hits & mask).count_ones() as u8, size)
+            })
+            .collect();
+        health.sort_by_key(|&(st, _, _)| st.index());
+        health
+    }
+
+    /// Every cell not yet fired at, in reading order (row by row). A
+    /// scanning opponent can pick from this instead of walking the grid.
+    /// It only reads `grid`, so it works the same on a shooter's view of
+    /// the opponent (filled in with `merge_observation`): there it is the
+    /// set of cells the shooter has not tried yet.
+    pub fn unshot_cells(&self) -> Vec<Position> {
+        self.dims.cells().filter(|p| self.grid[p.y as usize][p.x as usize] == CellState::Empty).collect()
+    }
+
+    /// Number of cells not yet fired at; zero once the board is exhausted.
+    pub fn remaining_unshot(&self) -> usize {
+        self.dims.cells().filter(|p| self.grid[p.y as usize][p.x as usize] == CellState::Empty).count()
+    }
+
+    /// Apply a whole salvo atomically. Every shot must be in bounds, on a
+    /// cell not yet shot, and distinct from the others in the salvo;
+    /// otherwise nothing is applied and `None` is returned. On success the
+    /// per-shot results are returned in salvo order.
+    pub fn apply_salvo(&mut self, shots: &[Position]) -> Option<Vec<HitType>> {
+        for (i, shot) in shots.iter().enumerate() {
+            if !self.in_bounds(*shot) || self.grid[shot.y as usize][shot.x as usize] != CellState::Empty {
+                return None;
+            }
+            if shots[..i].contains(shot) {
+                return None;
+            }
+        }
+        shots.iter().map(|&shot| self.apply_shot(shot)).collect()
+    }
+
+    /// Decode a state from untrusted `bincode::serialize` output, e.g. from
+    /// the network. Never panics: besides undecodable input it rejects
+    /// trailing bytes, input over `MAX_STATE_BYTES`, a fleet that fails
+    /// `check()`, and a grid that disagrees with the ships' hit masks.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<GameState, DecodeError> {
+        use bincode::Options;
+        let state: GameState = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .with_limit(MAX_STATE_BYTES)
+            .deserialize(bytes)
+            .map_err(|e| DecodeError::Malformed(e.to_string()))?;
+        // `check()` steps along each ship from its start, so reject starts
+        // that would overflow before calling it.
+        if state.ships.iter().any(|s| !s.position.in_bounds()) || !state.check() {
+            return Err(DecodeError::InvalidFleet);
+        }
+        if let Some(pos) = state.first_inconsistent_cell() {
+            return Err(DecodeError::InconsistentGrid(pos));
+        }
+        Ok(state)
+    }
+
+    /// First cell where the grid and the ships disagree: a ship segment
+    /// must be `Hit` (or `Sunk`, once the ship is) exactly when its hit bit
+    /// is set and `Empty` otherwise, open water must never be `Hit` or `Sunk`, and a ship's mask must not have bits
+    /// past its size. History entries and `grid_meta` must only name cells
+    /// already shot.
+    /// Assumes every ship lies on the board (see `check()`).
+    fn first_inconsistent_cell(&self) -> Option<Position> {
+        let mut on_ship = [[false; BOARD_SIZE]; BOARD_SIZE];
+        for ship in &self.ships {
+            let size = ship.ship_type.size() as u32;
+            if size < 8 && ship.hits >> size != 0 {
+                return Some(ship.position);
+            }
+            for (i, pos) in ship.position.iter_line(ship.direction, size).enumerate() {
+                on_ship[pos.y as usize][pos.x as usize] = true;
+                let consistent = match self.grid[pos.y as usize][pos.x as usize] {
+                    CellState::Empty => ship.hits & (1 << i) == 0,
+                    CellState::Hit => ship.hits & (1 << i) != 0,
+                    CellState::Sunk => ship.is_sunk(),
+                    CellState::Miss => false,
+                };
+                if !consistent {
+                    return Some(pos);
+                }
+            }
+        }
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if (matches!(cell, CellState::Hit | CellState::Sunk) && !on_ship[y][x]) || (*cell == CellState::Empty && self.grid_meta[y][x].is_some()) {
+                    return Some(Position::new(x as u32, y as u32));
+                }
+            }
+        }
+        self.history().iter().map(|&(pos, _)| pos).find(|pos| !pos.in_bounds() || self.grid[pos.y as usize][pos.x as usize] == CellState::Empty)
+    }
+}
+
+/// Largest input `GameState::try_from_bytes` will decode.
+pub const MAX_STATE_BYTES: u64 = 64 * 1024;
+
+/// Why `GameState::try_from_bytes` rejected its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Not a bincode-encoded `GameState`, too large, has trailing bytes, or
+    /// has `dims` outside what `BoardDims::new` accepts.
+    Malformed(String),
+    /// Ships are off the board, overlap, or are not exactly one of each type.
+    InvalidFleet,
+    /// The grid disagrees with the ships' hit masks at this cell.
+    InconsistentGrid(Position),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::Malformed(e) => write!(f, "malformed game state: {}", e),
+            DecodeError::InvalidFleet => f.write_str("game state has an invalid fleet"),
+            DecodeError::InconsistentGrid(pos) => write!(f, "grid disagrees with ship hits at ({}, {})", pos.x, pos.y),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Why `GameState::from_ascii` rejected a layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The layout does not have `BOARD_SIZE` non-blank rows.
+    RowCount(usize),
+    /// This row does not have `BOARD_SIZE` cells.
+    RowWidth { row: usize, width: usize },
+    /// A cell that is neither `.` nor a ship letter.
+    UnknownChar { pos: Position, ch: char },
+    /// The ship has the wrong number of cells.
+    WrongLength { ship_type: ShipType, found: usize },
+    /// The ship's cells do not form one straight, unbroken line.
+    NotStraight(ShipType),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::RowCount(n) => write!(f, "layout has {} rows, expected {}", n, BOARD_SIZE),
+            ParseError::RowWidth { row, width } => write!(f, "row {} has {} cells, expected {}", row, width, BOARD_SIZE),
+            ParseError::UnknownChar { pos, ch } => write!(f, "unexpected {:?} at ({}, {})", ch, pos.x, pos.y),
+            ParseError::WrongLength { ship_type, found } => write!(f, "{:?} has {} cells, expected {}", ship_type, found, ship_type.size()),
+            ParseError::NotStraight(st) => write!(f, "{:?} is not one straight line", st),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An observation that disagrees with what was already recorded for a cell;
+/// see `GameState::merge_observation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub position: Position,
+    pub recorded: CellState,
+    pub claimed: CellState,
+}
+
+impl std::fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "cell ({}, {}) was recorded as {:?} but is now claimed as {:?}", self.position.x, self.position.y, self.recorded, self.claimed)
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+/// Cells that random placement must avoid, either for every ship or only
+/// for a specific ship type. Used by `place_ships_randomly_constrained`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacementConstraints {
+    /// Cells no ship may occupy.
+    pub forbidden: Vec<Position>,
+    /// Cells the given ship type may not occupy.
+    pub forbidden_for: Vec<(ShipType, Position)>,
+    /// Number of reshuffled placement attempts before giving up.
+    pub max_attempts: u32,
+}
+
+impl Default for PlacementConstraints {
+    fn default() -> Self {
+        Self { forbidden: Vec::new(), forbidden_for: Vec::new(), max_attempts: 64 }
+    }
+}
+
+impl PlacementConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forbid every cell in the inclusive rectangle `min..=max` for all ships.
+    pub fn forbid_region(&mut self, min: Position, max: Position) -> &mut Self {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.forbidden.push(Position::new(x, y));
+            }
+        }
+        self
+    }
+
+    /// Forbid every cell in the inclusive rectangle `min..=max` for `ship_type` only.
+    pub fn forbid_region_for(&mut self, ship_type: ShipType, min: Position, max: Position) -> &mut Self {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.forbidden_for.push((ship_type, Position::new(x, y)));
+            }
+        }
+        self
+    }
+
+    /// Whether `ship_type` may occupy `pos` under these constraints.
+    pub fn allows(&self, ship_type: ShipType, pos: Position) -> bool {
+        !self.forbidden.contains(&pos) && !self.forbidden_for.contains(&(ship_type, pos))
+    }
+}
+
+/// Concise one-line summary for logs and test failures; use `{:?}` for the
+/// full dump including the grid and pepper.
+impl std::fmt::Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let types: Vec<ShipType> = self.ships.iter().map(|s| s.ship_type).collect();
+        let mut hits = 0usize;
+        let mut misses = 0usize;
+        for cell in self.grid.iter().flatten() {
+            match cell {
+                CellState::Hit | CellState::Sunk => hits += 1,
+                CellState::Miss => misses += 1,
+                CellState::Empty => {}
+            }
+        }
+        write!(
+            f,
+            "GameState {{ ships: {} {:?}, shots: {} (hits: {}, misses: {}), check: {} }}",
+            self.ships.len(),
+            types,
+            hits + misses,
+            hits,
+            misses,
+            if self.check() { "ok" } else { "failed" },
+        )
+    }
+}
+
+/// One of the two seats in a match. Player One moves first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    /// The opposing seat.
+    pub fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    /// 0 for Player One, 1 for Player Two, for indexing per-seat arrays.
+    pub fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+}
+
+/// Result of evaluating a pair of boards. `Draw` covers both fleets being
+/// destroyed at once and a move cap expiring with both fleets afloat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameOutcome {
+    Winner(Player),
+    Draw,
+    InProgress,
+}
+
+/// Decide the outcome from Player One's and Player Two's boards.
+///
+/// Deterministic regardless of turn order: if both fleets are sunk the game
+/// is a `Draw`; if exactly one is sunk the other player wins; otherwise the
+/// game is a `Draw` when `move_cap_reached` and `InProgress` if not.
+pub fn evaluate_outcome(p1: &GameState, p2: &GameState, move_cap_reached: bool) -> GameOutcome {
+    match (p1.all_ships_sunk(), p2.all_ships_sunk()) {
+        (true, true) => GameOutcome::Draw,
+        (false, true) => GameOutcome::Winner(Player::One),
+        (true, false) => GameOutcome::Winner(Player::Two),
+        (false, false) if move_cap_reached => GameOutcome::Draw,
+        (false, false) => GameOutcome::InProgress,
+    }
+}
+
+/// One player's shooting record over a game, derived from the shot history
+/// of the board they fired at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub shots: u32,
+    /// Shots that hit a ship, including the ones that sank it.
+    pub hits: u32,
+    /// Turns in which the player fired at least once.
+    pub turns: u32,
+    /// Ships sunk by the player, in the order they went down. Hidden
+    /// sinkings (`HitType::SunkHidden`) count as hits but are not listed.
+    pub sunk: Vec<ShipType>,
+}
+
+impl PlayerStats {
+    /// Stats for the player who fired `history` (`GameState::history()` of
+    /// the opponent's board). Turn boundaries follow `rules`: a new turn
+    /// starts with the first shot and after every shot that passed the turn.
+    pub fn from_history(history: &[(Position, HitType)], rules: &TurnRules) -> PlayerStats {
+        let mut stats = PlayerStats::default();
+        let mut in_turn = false;
+        for (_, hit) in history {
+            stats.shots += 1;
+            if !in_turn {
+                stats.turns += 1;
+            }
+            in_turn = rules.keeps_turn(hit);
+            match hit {
+                HitType::Miss => {}
+                HitType::Hit => stats.hits += 1,
+                HitType::Sunk(ship_type) => {
+                    stats.hits += 1;
+                    stats.sunk.push(*ship_type);
                 }
+                HitType::SunkHidden(_) => stats.hits += 1,
             }
+        }
+        stats
+    }
+
+    /// Fraction of shots that hit, or 0.0 before the first shot.
+    pub fn hit_rate(&self) -> f64 {
+        if self.shots == 0 { 0.0 } else { self.hits as f64 / self.shots as f64 }
+    }
+}
+
+/// A finished (or abandoned) game: the outcome plus each player's stats,
+/// indexed by `Player::index()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameReport {
+    pub outcome: GameOutcome,
+    pub stats: [PlayerStats; 2],
+}
+
+impl GameReport {
+    /// Build the report from Player One's and Player Two's boards, both of
+    /// which must have recorded history since the first shot.
+    pub fn from_boards(p1: &GameState, p2: &GameState, rules: &TurnRules, move_cap_reached: bool) -> GameReport {
+        GameReport { outcome: evaluate_outcome(p1, p2, move_cap_reached), stats: [PlayerStats::from_history(p2.history(), rules), PlayerStats::from_history(p1.history(), rules)] }
+    }
+
+    pub fn stats(&self, player: Player) -> &PlayerStats {
+        &self.stats[player.index()]
+    }
+}
+
+/// Ships a generated board must contain, the `dims` region (from (0, 0))
+/// they must fit in, and the rules the match is played under. Ship types
+/// may appear at most once, as on a real board.
+///
+/// Also the match parameters both peers declare in `BoardReady` and the
+/// guest commits to (see `digest`), so they cannot play different boards
+/// or rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetSpec {
+    pub ships: Vec<ShipType>,
+    pub dims: BoardDims,
+    /// See `GameState::overlap`.
+    #[serde(default)]
+    pub overlap: OverlapRule,
+    /// See `GameState::placement_rules`.
+    #[serde(default)]
+    pub placement_rules: Vec<PlacementConstraint>,
+    /// See `GameState::reveal`.
+    #[serde(default)]
+    pub reveal: RevealPolicy,
+    /// See `GameState::mark_sunk_surroundings`.
+    #[serde(default)]
+    pub mark_sunk_surroundings: bool,
+    /// See `GameState::mark_sunk_cells`.
+    #[serde(default)]
+    pub mark_sunk_cells: bool,
+    /// Extra-shot rules for Classic mode.
+    #[serde(default)]
+    pub turn_rules: TurnRules,
+}
+
+impl FleetSpec {
+    /// The classic five-ship fleet on the full board under the standard rules.
+    pub fn classic() -> Self {
+        Self::with_ships(ShipType::ALL.to_vec(), BoardDims::default())
+    }
+
+    /// `ships` on a `dims` board under the standard rules.
+    pub fn with_ships(ships: Vec<ShipType>, dims: BoardDims) -> Self {
+        Self { ships, dims, overlap: OverlapRule::Forbidden, placement_rules: Vec::new(), reveal: RevealPolicy::AnnounceSunk, mark_sunk_surroundings: false, mark_sunk_cells: false, turn_rules: TurnRules::default() }
+    }
+
+    /// Commitment to the spec: SHA-256 of `FLEET_DOMAIN`, the width and
+    /// height as u32, the ship count as u32 and each `ShipType::index()`
+    /// as a byte in spec order, then one byte each for `overlap`,
+    /// `reveal`, `mark_sunk_surroundings`, `mark_sunk_cells`,
+    /// `extra_turn_on_hit` and `extra_turn_on_sunk`, and last the
+    /// constraint count as u32 with each of `placement_rules` in order as
+    /// a tag byte (0 `MaxPerLine` followed by its limit, 1 `NoTouching`).
+    /// Integers are little-endian.
+    pub fn digest(&self) -> Digest {
+        let mut out = Vec::with_capacity(1 + 12 + self.ships.len() + 6 + 4 + 2 * self.placement_rules.len());
+        out.push(FLEET_DOMAIN);
+        out.extend_from_slice(&self.dims.width.to_le_bytes());
+        out.extend_from_slice(&self.dims.height.to_le_bytes());
+        out.extend_from_slice(&(self.ships.len() as u32).to_le_bytes());
+        out.extend(self.ships.iter().map(|st| st.index() as u8));
+        out.push(match self.overlap { OverlapRule::Forbidden => 0, OverlapRule::Allowed => 1 });
+        out.push(match self.reveal { RevealPolicy::AnnounceSunk => 0, RevealPolicy::HitMissOnly => 1 });
+        out.push(self.mark_sunk_surroundings as u8);
+        out.push(self.mark_sunk_cells as u8);
+        out.push(self.turn_rules.extra_turn_on_hit as u8);
+        out.push(self.turn_rules.extra_turn_on_sunk as u8);
+        out.extend_from_slice(&(self.placement_rules.len() as u32).to_le_bytes());
+        for rule in &self.placement_rules {
+            match rule {
+                PlacementConstraint::MaxPerLine(n) => out.extend_from_slice(&[0, *n]),
+                PlacementConstraint::NoTouching => out.push(1),
+            }
+        }
+        *risc0_zkvm::sha::Impl::hash_bytes(&out)
+    }
+
+    /// `InvalidSpec` if a ship type is listed twice.
+    fn validate(&self) -> Result<(), PlacementError> {
+        if self.ships.iter().enumerate().any(|(i, st)| self.ships[..i].contains(st)) {
+            return Err(PlacementError::InvalidSpec);
+        }
+        Ok(())
+    }
+
+    /// Give `state` the spec's dimensions and board rules, leaving ships
+    /// and shots alone.
+    pub fn apply_rules(&self, state: &mut GameState) {
+        state.dims = self.dims;
+        state.overlap = self.overlap;
+        state.placement_rules = self.placement_rules.clone();
+        state.reveal = self.reveal;
+        state.mark_sunk_surroundings = self.mark_sunk_surroundings;
+        state.mark_sunk_cells = self.mark_sunk_cells;
+    }
+
+    /// True if `state` could be a board of this spec: it has the spec's
+    /// dims and board rules (see `apply_rules`), and every ship is of a
+    /// listed type and lies inside `dims`. Like `check()` it accepts a
+    /// fleet that is not fully placed yet.
+    pub fn admits(&self, state: &GameState) -> bool {
+        let rules_match = state.dims == self.dims
+            && state.overlap == self.overlap
+            && state.placement_rules == self.placement_rules
+            && state.reveal == self.reveal
+            && state.mark_sunk_surroundings == self.mark_sunk_surroundings
+            && state.mark_sunk_cells == self.mark_sunk_cells;
+        rules_match && state.ships.iter().all(|ship| self.ships.contains(&ship.ship_type) && ship.get_coordinates().iter().all(|&c| self.dims.contains(c)))
+    }
+}
+
+impl Default for FleetSpec {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+/// Why `FleetSpec::try_sample` could not produce a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementError {
+    /// No free spot was left for this ship.
+    Unplaceable(ShipType),
+    /// A ship type is listed twice.
+    InvalidSpec,
+    /// The ships need more cells than the board has, so no layout exists.
+    Overfull { cells: u32, available: u32 },
+}
+
+impl std::fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlacementError::Unplaceable(st) => write!(f, "no room left to place {:?}", st),
+            PlacementError::InvalidSpec => f.write_str("fleet spec lists a ship type twice"),
+            PlacementError::Overfull { cells, available } => write!(f, "fleet needs {} cells but the board has only {}", cells, available),
+        }
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+#[cfg(feature = "rand")]
+impl FleetSpec {
+    /// Randomly place the fleet. Ships go down in spec order, each at the
+    /// first fitting spot in one shuffled pass, so a dense fleet can fail
+    /// even when some arrangement exists; callers may retry with more
+    /// randomness. A fleet that fails `GameState::fleet_is_feasible` is
+    /// reported without sampling, and retrying it is pointless.
+    pub fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<GameState, PlacementError> {
+        let mut state = GameState::new(rng.gen());
+        self.apply_rules(&mut state);
+        state.fleet_fit(self)?;
+        let mut positions: Vec<Position> = self.dims.cells().collect();
+        positions.shuffle(rng);
+
+        // Try positions in shuffled order and both orientations; the
+        // state's own dims and rules keep every ship legal.
+        for &ship_type in &self.ships {
+            let placed = positions.iter().any(|&pos| {
+                [Direction::Horizontal, Direction::Vertical].into_iter().any(|dir| state.place_ship(ship_type, pos, dir))
+            });
             if !placed {
-                panic!("Failed to place {:?}", ship_type);
+                return Err(PlacementError::Unplaceable(ship_type));
             }
         }
+        Ok(state)
+    }
+
+    /// `try_sample` for specs that always fit, such as `classic()`.
+    /// Panics if placement fails.
+    pub fn must_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> GameState {
+        self.try_sample(rng).unwrap_or_else(|e| panic!("Failed to place fleet: {}", e))
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Distribution<GameState> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> GameState {
+        let state = FleetSpec::classic().must_sample(rng);
+        assert!(state.check());
+        state
+    }
+}
+
+/// A board as shown to spectators: ships and grid with the pepper zeroed.
+/// Without the pepper it cannot reproduce `commit()` or
+/// `hash_ships_only()`, so it is a separate type with no commitment
+/// methods rather than a `GameState`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpectatorBoard {
+    pub ships: Vec<Ship>,
+    pub grid: [[CellState; BOARD_SIZE]; BOARD_SIZE],
+    /// Always all zero.
+    pub pepper: [u8; 16],
+    /// Always true; marks `pepper` as removed rather than genuinely zero.
+    pub pepper_redacted: bool,
+}
+
+impl GameState {
+    /// This board with the pepper removed; see `SpectatorBoard`.
+    pub fn spectator_view(&self) -> SpectatorBoard {
+        SpectatorBoard { ships: self.ships.clone(), grid: self.grid, pepper: [0; 16], pepper_redacted: true }
+    }
+}
+
+#[cfg(feature = "json")]
+impl GameState {
+    /// `spectator_view()` as JSON, safe to share: the pepper that hides the
+    /// commitment is zeroed and flagged as redacted.
+    pub fn to_spectator_json(&self) -> String {
+        serde_json::to_string(&self.spectator_view()).expect("SpectatorBoard serializes to JSON")
+    }
+}
 
-        assert!(state.check());
-        state
+#[cfg(feature = "json")]
+impl SpectatorBoard {
+    /// Parse `GameState::to_spectator_json` output. Yields a
+    /// `SpectatorBoard`, never a `GameState`, and rejects input that is not
+    /// flagged as redacted or carries pepper bytes.
+    pub fn from_spectator_json(json: &str) -> serde_json::Result<SpectatorBoard> {
+        use serde::de::Error;
+        let board: SpectatorBoard = serde_json::from_str(json)?;
+        if !board.pepper_redacted || board.pepper != [0; 16] {
+            return Err(serde_json::Error::custom("spectator board must have a redacted, all-zero pepper"));
+        }
+        Ok(board)
     }
 }
 
@@ -479,6 +2486,15 @@ mod tests {
             ],
             pepper: [0; 16],
             grid: [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE],
+            history: None,
+            grid_meta: [[None; BOARD_SIZE]; BOARD_SIZE],
+            overlap: OverlapRule::Forbidden,
+            wide_pepper: None,
+            placement_rules: Vec::new(),
+            reveal: RevealPolicy::AnnounceSunk,
+            mark_sunk_surroundings: false,
+            mark_sunk_cells: false,
+            dims: BoardDims::default(),
         };
         assert!(state.check());
     }
@@ -492,12 +2508,192 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_fleet_spec_reports_unplaceable_fleet() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+
+        // A 5-cell carrier cannot fit in a 4x4 corner
+        let tiny = FleetSpec::with_ships(ShipType::ALL.to_vec(), BoardDims::new(4, 4).unwrap());
+        assert_eq!(tiny.try_sample(&mut rng), Err(PlacementError::Unplaceable(ShipType::Carrier)));
+        let doubled = FleetSpec::with_ships(vec![ShipType::Destroyer, ShipType::Destroyer], BoardDims::new(4, 4).unwrap());
+        assert_eq!(doubled.try_sample(&mut rng), Err(PlacementError::InvalidSpec));
+
+        let small = FleetSpec::with_ships(vec![ShipType::Submarine, ShipType::Destroyer], BoardDims::new(4, 4).unwrap());
+        let state = small.try_sample(&mut rng).expect("two small ships fit in 4x4");
+        assert_eq!(state.ships.len(), 2);
+        assert!(state.ships.iter().flat_map(|s| s.get_coordinates()).all(|c| c.x < 4 && c.y < 4));
+        assert!(FleetSpec::classic().must_sample(&mut rng).check());
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_overfull_fleet_is_infeasible_without_retrying() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+
+        // 17 ship cells on a 5x3 board: the Carrier fits a row, but not everything
+        let mut state = GameState::with_dims([0; 16], BoardDims::new(5, 3).unwrap());
+        assert!(!state.fleet_is_feasible(&FleetSpec::classic()));
+        assert_eq!(state.fleet_fit(&FleetSpec::classic()), Err(PlacementError::Overfull { cells: 17, available: 15 }));
+        let endless = PlacementConstraints { max_attempts: u32::MAX, ..PlacementConstraints::new() };
+        assert!(!state.place_ships_randomly_constrained(&mut rng, &endless));
+        assert!(!state.place_ships_uniformly(&mut rng));
+        assert!(!state.place_ships_randomly(&mut rng));
+        assert!(state.ships.is_empty());
+
+        assert!(GameState::new([0; 16]).fleet_is_feasible(&FleetSpec::classic()));
+        assert!(GameState::with_dims([0; 16], BoardDims::new(5, 4).unwrap()).fleet_is_feasible(&FleetSpec::classic()));
+    }
+
+    #[test]
+    fn test_fleet_spec_digest_and_admits() {
+        let classic = FleetSpec::classic();
+        let small = FleetSpec::with_ships(vec![ShipType::Submarine, ShipType::Destroyer], BoardDims::new(8, 8).unwrap());
+        assert_eq!(classic.digest(), FleetSpec::default().digest());
+        assert_ne!(classic.digest(), small.digest());
+        assert_ne!(small.digest(), FleetSpec { dims: BoardDims::default(), ..small.clone() }.digest());
+        assert_ne!(FleetSpec { dims: BoardDims::new(8, 6).unwrap(), ..small.clone() }.digest(), FleetSpec { dims: BoardDims::new(6, 8).unwrap(), ..small.clone() }.digest());
+
+        // Every rule is part of the commitment
+        let variants = [
+            FleetSpec { overlap: OverlapRule::Allowed, ..classic.clone() },
+            FleetSpec { placement_rules: vec![PlacementConstraint::NoTouching], ..classic.clone() },
+            FleetSpec { placement_rules: vec![PlacementConstraint::MaxPerLine(3)], ..classic.clone() },
+            FleetSpec { placement_rules: vec![PlacementConstraint::MaxPerLine(4)], ..classic.clone() },
+            FleetSpec { reveal: RevealPolicy::HitMissOnly, ..classic.clone() },
+            FleetSpec { mark_sunk_surroundings: true, ..classic.clone() },
+            FleetSpec { mark_sunk_cells: true, ..classic.clone() },
+            FleetSpec { turn_rules: TurnRules { extra_turn_on_hit: false, extra_turn_on_sunk: false }, ..classic.clone() },
+            FleetSpec { turn_rules: TurnRules { extra_turn_on_hit: true, extra_turn_on_sunk: true }, ..classic.clone() },
+        ];
+        let mut digests: Vec<Digest> = variants.iter().map(FleetSpec::digest).collect();
+        digests.push(classic.digest());
+        digests.sort_by_key(|d| d.as_bytes().to_vec());
+        digests.dedup();
+        assert_eq!(digests.len(), variants.len() + 1);
+
+        assert!(classic.admits(&full_fleet()));
+        assert!(!small.admits(&full_fleet()), "carrier is not in the small fleet");
+        let mut board = GameState::new([0; 16]);
+        assert!(board.place_ship(ShipType::Destroyer, Position::new(8, 0), Direction::Horizontal));
+        assert!(classic.admits(&board));
+        small.apply_rules(&mut board);
+        assert!(!small.admits(&board), "destroyer leaves the 8x8 region");
+        board.ships[0].position = Position::new(6, 0);
+        assert!(small.admits(&board));
+
+        // A board played under other rules than the spec's is not admitted,
+        // e.g. a stacked fleet that only passes `check()` with overlap allowed
+        let mut stacked = full_fleet();
+        stacked.overlap = OverlapRule::Allowed;
+        stacked.ships[1].position = stacked.ships[0].position;
+        assert!(stacked.check());
+        assert!(!classic.admits(&stacked));
+        assert!(FleetSpec { overlap: OverlapRule::Allowed, ..classic.clone() }.admits(&stacked));
+        let mut quiet = full_fleet();
+        quiet.reveal = RevealPolicy::HitMissOnly;
+        assert!(!classic.admits(&quiet));
+        let mut spread = full_fleet();
+        spread.placement_rules.clear();
+        assert!(!FleetSpec { placement_rules: vec![PlacementConstraint::NoTouching], ..classic.clone() }.admits(&spread));
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_uniform_placement_spreads_the_carrier() {
+        use rand::SeedableRng;
+        // The carrier goes first on an empty board, so greedy placement
+        // already picks its starting cell uniformly from the cells where it
+        // fits, but always lies horizontal when it can. Measure the
+        // placement (start cell and direction) against the uniform
+        // distribution over all 120 in-bounds carrier placements.
+        const SAMPLES: usize = 4000;
+        let slot = |pos: Position, dir: Direction| (pos.y as usize * BOARD_SIZE + pos.x as usize) * 2 + direction_byte(dir) as usize;
+        let distance_from_uniform = |place: &dyn Fn(&mut GameState, &mut rand::rngs::StdRng) -> bool| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+            let mut counts = vec![0usize; 2 * BOARD_SIZE * BOARD_SIZE];
+            for _ in 0..SAMPLES {
+                let mut state = GameState::new([0; 16]);
+                assert!(place(&mut state, &mut rng));
+                assert!(state.is_placement_complete(&FleetSpec::classic()));
+                let carrier = state.ships.iter().find(|s| s.ship_type == ShipType::Carrier).unwrap();
+                counts[slot(carrier.position, carrier.direction)] += 1;
+            }
+            // Total variation distance over the 120 in-bounds placements
+            let uniform = 1.0 / 120.0;
+            let mut tv = 0.0;
+            for pos in Position::all_in_bounds() {
+                for dir in [Direction::Horizontal, Direction::Vertical] {
+                    if !Ship::new(ShipType::Carrier, pos, dir).get_coordinates().iter().all(|c| c.in_bounds()) {
+                        continue;
+                    }
+                    let seen = counts[slot(pos, dir)] as f64 / SAMPLES as f64;
+                    tv += (seen - uniform).abs();
+                }
+            }
+            tv / 2.0
+        };
+        let greedy = distance_from_uniform(&|state, rng| state.place_ships_randomly(rng));
+        let uniform = distance_from_uniform(&|state, rng| state.place_ships_uniformly(rng));
+        // Greedy never places a vertical carrier where a horizontal one fits
+        // (TV about 0.3); the uniform sampler is close to sampling noise.
+        assert!(greedy > 0.25, "greedy distance {greedy}");
+        assert!(uniform < greedy / 2.0, "uniform {uniform} vs greedy {greedy}");
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_constrained_placement_avoids_top_row() {
+        use rand::SeedableRng;
+        let mut constraints = PlacementConstraints::new();
+        constraints.forbid_region(Position::new(0, 0), Position::new(BOARD_SIZE as u32 - 1, 0));
+        // Also keep the carrier out of the centre block.
+        constraints.forbid_region_for(ShipType::Carrier, Position::new(3, 3), Position::new(6, 6));
+
+        for seed in 0..20 {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut state = GameState::new([0; 16]);
+            assert!(state.place_ships_randomly_constrained(&mut rng, &constraints));
+            assert!(state.check());
+            for ship in &state.ships {
+                for c in ship.get_coordinates() {
+                    assert_ne!(c.y, 0, "{:?} placed on the forbidden top row", ship.ship_type);
+                    assert!(constraints.allows(ship.ship_type, c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_line_matches_get_coordinates() {
+        for ship_type in [ShipType::Carrier, ShipType::Cruiser, ShipType::Destroyer] {
+            for dir in [Direction::Horizontal, Direction::Vertical] {
+                let ship = Ship::new(ship_type, Position::new(2, 3), dir);
+                let line: Vec<Position> = ship.position.iter_line(dir, ship_type.size() as u32).collect();
+                assert_eq!(line.len(), ship_type.size() as usize);
+                assert_eq!(line, ship.get_coordinates());
+            }
+        }
+        assert_eq!(Position::new(0, 0).iter_line(Direction::Vertical, 0).count(), 0);
+    }
+
     #[test]
     fn test_shot_before_start_not_hit() {
         let mut state = GameState {
             ships: vec![Ship::new(ShipType::Cruiser, Position::new(5, 5), Direction::Horizontal)],
             pepper: [0; 16],
             grid: [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE],
+            history: None,
+            grid_meta: [[None; BOARD_SIZE]; BOARD_SIZE],
+            overlap: OverlapRule::Forbidden,
+            wide_pepper: None,
+            placement_rules: Vec::new(),
+            reveal: RevealPolicy::AnnounceSunk,
+            mark_sunk_surroundings: false,
+            mark_sunk_cells: false,
+            dims: BoardDims::default(),
         };
 
     // Shot before the ship's start should be a miss
@@ -563,6 +2759,11 @@ mod tests {
         // Second shot at same cell -> already shot -> None per API
         let res2 = state.apply_shot(Position::new(1, 1));
         assert_eq!(res2, None);
+
+        // try_apply_shot says why
+        assert_eq!(state.try_apply_shot(Position::new(1, 1)), Err(ShotError::AlreadyShot));
+        assert_eq!(state.try_apply_shot(Position::new(BOARD_SIZE as u32, 0)), Err(ShotError::OutOfBounds));
+        assert_eq!(state.try_apply_shot(Position::new(2, 1)), Ok(HitType::Miss));
     }
 
     #[test]
@@ -583,4 +2784,1121 @@ mod tests {
         let c3 = s2.commit();
         assert_ne!(c1, c3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_outcome_draw_when_move_cap_reached() {
+        let mut p1 = GameState::new([0; 16]);
+        assert!(p1.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Horizontal));
+        let mut p2 = GameState::new([0; 16]);
+        assert!(p2.place_ship(ShipType::Destroyer, Position::new(5, 5), Direction::Vertical));
+
+        // One hit each; both fleets survive.
+        assert_eq!(p1.apply_shot(Position::new(0, 0)), Some(HitType::Hit));
+        assert_eq!(p2.apply_shot(Position::new(5, 5)), Some(HitType::Hit));
+
+        assert_eq!(evaluate_outcome(&p1, &p2, false), GameOutcome::InProgress);
+        assert_eq!(evaluate_outcome(&p1, &p2, true), GameOutcome::Draw);
+
+        // Sinking p2's fleet decides the game even with the cap reached.
+        assert_eq!(p2.apply_shot(Position::new(5, 6)), Some(HitType::Sunk(ShipType::Destroyer)));
+        assert_eq!(evaluate_outcome(&p1, &p2, true), GameOutcome::Winner(Player::One));
+
+        // Both fleets gone at once is a draw, not an arbitrary winner.
+        assert_eq!(p1.apply_shot(Position::new(1, 0)), Some(HitType::Sunk(ShipType::Destroyer)));
+        assert_eq!(evaluate_outcome(&p1, &p2, false), GameOutcome::Draw);
+    }
+
+    fn full_fleet() -> GameState {
+        let mut state = GameState::new([0; 16]);
+        assert!(state.place_ships(vec![
+            (ShipType::Carrier, Position::new(0, 0), Direction::Horizontal),
+            (ShipType::Battleship, Position::new(0, 2), Direction::Horizontal),
+            (ShipType::Cruiser, Position::new(0, 4), Direction::Horizontal),
+            (ShipType::Submarine, Position::new(0, 6), Direction::Horizontal),
+            (ShipType::Destroyer, Position::new(0, 8), Direction::Horizontal),
+        ]));
+        state
+    }
+
+    #[test]
+    fn test_salvo_size_tracks_surviving_ships() {
+        let mut p1 = full_fleet();
+        let mut p2 = full_fleet();
+        let mode = TurnMode::Salvo;
+
+        // P1 fires a full 5-shot salvo.
+        assert_eq!(mode.shots_per_turn(&p1), 5);
+        let salvo: Vec<Position> = (5..10).map(|x| Position::new(x, 9)).collect();
+        let results = p2.apply_salvo(&salvo).expect("valid salvo");
+        assert_eq!(results, vec![HitType::Miss; 5]);
+
+        // P2's salvo sinks P1's destroyer.
+        assert_eq!(mode.shots_per_turn(&p2), 5);
+        let salvo = [(0u32, 8u32), (1, 8), (9, 0), (9, 1), (9, 2)].map(Position::from);
+        let results = p1.apply_salvo(&salvo).expect("valid salvo");
+        assert_eq!(results[1], HitType::Sunk(ShipType::Destroyer));
+
+        // P1 now has four ships left, so only four shots next turn.
+        assert_eq!(mode.shots_per_turn(&p1), 4);
+        assert_eq!(TurnMode::Classic.shots_per_turn(&p1), 1);
+    }
+
+    #[test]
+    fn test_display_summary() {
+        let mut state = full_fleet();
+        state.apply_shot(Position::new(0, 0));
+        state.apply_shot(Position::new(9, 9));
+        let summary = state.to_string();
+        assert!(summary.contains("ships: 5 "), "{summary}");
+        assert!(summary.contains("shots: 2 (hits: 1, misses: 1)"), "{summary}");
+        assert!(summary.contains("check: ok"), "{summary}");
+        assert!(GameState::new([0; 16]).to_string().contains("ships: 0 []"));
+    }
+
+    #[test]
+    fn test_salvo_rejects_duplicates_atomically() {
+        let mut state = full_fleet();
+        let salvo = [Position::new(0, 0), Position::new(0, 0)];
+        assert_eq!(state.apply_salvo(&salvo), None);
+        // Nothing was applied.
+        assert_eq!(state.grid[0][0], CellState::Empty);
+    }
+
+    #[test]
+    fn test_commit_golden_vector() {
+        let mut state = full_fleet();
+        state.pepper = [0xA5; 16];
+        state.apply_shot(Position::new(0, 0));
+        state.apply_shot(Position::new(9, 9));
+
+        let bytes = state.serialize_for_commit();
+        assert_eq!(bytes.len(), 1 + 4 + 5 * 11 + 16 + BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(&bytes[..5], &[COMMIT_FORMAT_VERSION, 5, 0, 0, 0]);
+        // Integers are written little-endian by hand, never in native order:
+        // the Battleship record is index 1, x = 0, y = 2, Horizontal, 0 hits.
+        assert_eq!(&bytes[16..27], &[1, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0]);
+        // Pinned digest: if this changes, every historical commitment breaks.
+        assert_eq!(state.commit().to_string(), "74fdc31ef231aa9990c2c0db3efab5125c6de5403fedc39fd3255882b8ef6974");
+    }
+
+    #[test]
+    fn test_neighbors_respect_board_edges() {
+        let corner = Position::new(0, 0);
+        assert_eq!(corner.orthogonal_neighbors(), vec![Position::new(0, 1), Position::new(1, 0)]);
+        assert_eq!(corner.all_neighbors().len(), 3);
+
+        let far_corner = Position::new(9, 9);
+        assert_eq!(far_corner.orthogonal_neighbors().len(), 2);
+        assert_eq!(Position::new(0, 5).orthogonal_neighbors().len(), 3);
+        assert_eq!(Position::new(0, 5).all_neighbors().len(), 5);
+
+        let center = Position::new(4, 4);
+        let orth = center.orthogonal_neighbors();
+        assert_eq!(orth, vec![Position::new(4, 3), Position::new(4, 5), Position::new(3, 4), Position::new(5, 4)]);
+        assert_eq!(center.all_neighbors().len(), 8);
+        assert!(center.all_neighbors().iter().all(|p| *p != center));
+    }
+
+    #[test]
+    fn test_next_state_table_under_default_rules() {
+        use Player::{One, Two};
+        let rules = TurnRules::default();
+        let table = [
+            (One, HitType::Miss, Two, false),
+            (One, HitType::Hit, One, true),
+            (One, HitType::Sunk(ShipType::Destroyer), Two, false),
+            (Two, HitType::Miss, One, false),
+            (Two, HitType::Hit, Two, true),
+            (Two, HitType::Sunk(ShipType::Carrier), One, false),
+        ];
+        for (current, outcome, next_player, extra_shot) in table {
+            assert_eq!(rules.next_state(current, &outcome), TurnTransition { next_player, extra_shot }, "{:?} after {:?}", current, outcome);
+            assert_eq!(rules.keeps_turn(&outcome), extra_shot);
+        }
+    }
+
+    #[test]
+    fn test_turn_rules_without_extra_turn_on_hit() {
+        let mut state = full_fleet();
+        let hit = state.apply_shot(Position::new(0, 0)).unwrap();
+        assert_eq!(hit, HitType::Hit);
+        assert!(TurnRules::default().keeps_turn(&hit));
+
+        let strict = TurnRules { extra_turn_on_hit: false, extra_turn_on_sunk: false };
+        assert!(!strict.keeps_turn(&hit), "a hit must pass the turn");
+        assert!(!strict.keeps_turn(&HitType::Miss));
+
+        let sunk = state.apply_shot(Position::new(0, 8)).and_then(|_| state.apply_shot(Position::new(1, 8))).unwrap();
+        assert_eq!(sunk, HitType::Sunk(ShipType::Destroyer));
+        assert!(!TurnRules::default().keeps_turn(&sunk));
+        assert!(TurnRules { extra_turn_on_hit: true, extra_turn_on_sunk: true }.keeps_turn(&sunk));
+    }
+
+    #[test]
+    fn test_is_placement_complete() {
+        let classic = FleetSpec::classic();
+        let mut state = GameState::new([0; 16]);
+        assert!(!state.is_placement_complete(&classic));
+        state.place_ship(ShipType::Carrier, Position::new(0, 0), Direction::Horizontal);
+        assert!(state.check(), "a partial fleet is still a legal board");
+        assert!(!state.is_placement_complete(&classic));
+        assert!(full_fleet().is_placement_complete(&classic));
+
+        // Smaller fleets are complete with exactly their own ships
+        let small = FleetSpec::with_ships(vec![ShipType::Carrier, ShipType::Destroyer], BoardDims::new(6, 6).unwrap());
+        small.apply_rules(&mut state);
+        assert!(!state.is_placement_complete(&small));
+        state.place_ship(ShipType::Destroyer, Position::new(0, 2), Direction::Vertical);
+        assert!(state.is_placement_complete(&small));
+        assert!(!full_fleet().is_placement_complete(&small));
+        let mut full_size = state.clone();
+        full_size.dims = BoardDims::default();
+        assert!(!full_size.is_placement_complete(&small), "the board must have the fleet's dims");
+        state.place_ship(ShipType::Submarine, Position::new(2, 2), Direction::Vertical);
+        assert!(!state.is_placement_complete(&small), "extra ships are not part of the fleet");
+    }
+
+    #[test]
+    fn test_merkle_incremental_matches_full_recompute() {
+        let mut state = full_fleet();
+        let mut grid_root = state.grid_merkle_root();
+        // 20 shots mixing hits and misses
+        let shots: Vec<Position> = (0..10).flat_map(|x| [Position::new(x, 0), Position::new(x, 9)]).collect();
+        for shot in shots {
+            let proof = state.cell_proof(shot).unwrap();
+            assert!(proof.verify(&grid_root));
+            let mut reference = state.clone();
+            let (hit, root, updated) = state.apply_shot_merkle(shot, &proof, &grid_root).unwrap();
+            assert_eq!(Some(hit), reference.apply_shot(shot));
+            assert_eq!(root, reference.merkle_root());
+            assert_eq!(updated, reference.cell_proof(shot).unwrap());
+            grid_root = updated.grid_root();
+        }
+    }
+
+    #[test]
+    fn test_merkle_rejects_stale_or_foreign_proof() {
+        let mut state = full_fleet();
+        let grid_root = state.grid_merkle_root();
+        let proof = state.cell_proof(Position::new(3, 3)).unwrap();
+        assert!(state.apply_shot_merkle(Position::new(4, 3), &proof, &grid_root).is_none());
+
+        let mut tampered = proof.clone();
+        tampered.cell = CellState::Hit;
+        assert!(state.apply_shot_merkle(Position::new(3, 3), &tampered, &grid_root).is_none());
+
+        // A proof from before the last shot no longer opens the new root
+        let (_, _, updated) = state.apply_shot_merkle(Position::new(3, 3), &proof, &grid_root).unwrap();
+        let fresh = state.cell_proof(Position::new(5, 5)).unwrap();
+        assert!(fresh.verify(&updated.grid_root()));
+        assert!(!proof.verify(&updated.grid_root()));
+        assert!(state.cell_proof(Position::new(10, 0)).is_none());
+    }
+
+    #[test]
+    fn test_ship_tables_consistent() {
+        validate_ship_tables();
+        assert!(ship_tables_agree());
+        for t in ShipType::ALL {
+            assert_eq!(SHIP_SIZES[t.index()], t.size(), "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn test_placement_hash_stable_across_shots() {
+        let mut state = full_fleet();
+        let placement = state.hash_ships_only();
+        let mut commits = vec![state.commit()];
+        for shot in [Position::new(0, 0), Position::new(9, 9), Position::new(0, 8), Position::new(1, 8)] {
+            state.apply_shot(shot);
+            assert_eq!(state.hash_ships_only(), placement);
+            assert!(!commits.contains(&state.commit()), "full commit must change after {:?}", shot);
+            commits.push(state.commit());
+        }
+
+        // A reveal with a different layout or pepper does not match
+        let mut moved = full_fleet();
+        moved.ships[4].position = Position::new(5, 8);
+        assert_ne!(moved.hash_ships_only(), placement);
+        let mut repeppered = full_fleet();
+        repeppered.pepper = [1; 16];
+        assert_ne!(repeppered.hash_ships_only(), placement);
+    }
+
+    #[test]
+    fn test_player_stats_from_history() {
+        let mut state = full_fleet();
+        state.enable_history();
+        // Destroyer at (0,8)-(1,8): miss, hit, sink, then a miss.
+        for shot in [Position::new(5, 5), Position::new(0, 8), Position::new(1, 8), Position::new(9, 9)] {
+            state.apply_shot(shot);
+        }
+        let stats = PlayerStats::from_history(state.history(), &TurnRules::default());
+        assert_eq!((stats.shots, stats.hits), (4, 2));
+        assert_eq!(stats.sunk, vec![ShipType::Destroyer]);
+        assert_eq!(stats.hit_rate(), 0.5);
+        // The opening miss is one turn; the hit keeps the turn, the sinking
+        // passes it, and the final miss is a third turn.
+        assert_eq!(stats.turns, 3);
+        assert_eq!(PlayerStats::from_history(&[], &TurnRules::default()).hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_replay_history_reproduces_grid() {
+        let mut state = full_fleet();
+        state.enable_history();
+        let before = state.commit();
+        for shot in [Position::new(0, 8), Position::new(5, 5), Position::new(1, 8), Position::new(0, 0)] {
+            state.apply_shot(shot);
+        }
+        assert_eq!(state.apply_shot(Position::new(5, 5)), None, "rejected shots are not recorded");
+        assert_eq!(state.history().len(), 4);
+        assert_eq!(state.history()[2], (Position::new(1, 8), HitType::Sunk(ShipType::Destroyer)));
+
+        let replayed = state.replay_history();
+        assert_eq!(replayed.grid, state.grid);
+        assert_eq!(replayed, state);
+
+        // Recording does not affect the commitment
+        let mut unrecorded = full_fleet();
+        assert_eq!(unrecorded.commit(), before);
+        for &(shot, _) in state.history() {
+            unrecorded.apply_shot(shot);
+        }
+        assert_eq!(unrecorded.commit(), state.commit());
+    }
+
+    #[test]
+    fn test_merge_observation_rejects_contradictions() {
+        let mut view = GameState::new([0; 16]);
+        let pos = Position::new(3, 4);
+        assert_eq!(view.merge_observation(pos, CellState::Miss), Ok(()));
+        // Repeating the same observation is a no-op
+        assert_eq!(view.merge_observation(pos, CellState::Miss), Ok(()));
+        assert_eq!(view.grid[4][3], CellState::Miss);
+
+        let err = view.merge_observation(pos, CellState::Hit).unwrap_err();
+        assert_eq!(err, Contradiction { position: pos, recorded: CellState::Miss, claimed: CellState::Hit });
+        assert_eq!(view.grid[4][3], CellState::Miss, "a contradiction must not overwrite the cell");
+    }
+
+    #[test]
+    fn test_unshot_cells_on_a_view_excludes_observed_cells() {
+        let mut view = GameState::new([0; 16]);
+        let observed = [(Position::new(0, 0), CellState::Miss), (Position::new(4, 2), CellState::Hit), (Position::new(9, 9), CellState::Miss)];
+        for &(pos, cell) in &observed {
+            view.merge_observation(pos, cell).unwrap();
+        }
+        let untried = view.unshot_cells();
+        assert_eq!(untried.len(), BOARD_SIZE * BOARD_SIZE - 3);
+        let missing: Vec<Position> = Position::all_in_bounds().filter(|p| !untried.contains(p)).collect();
+        assert_eq!(missing, observed.map(|(pos, _)| pos));
+    }
+
+    #[test]
+    fn test_mirrors_and_rotation_keep_the_board_valid_and_invert() {
+        let mut state = full_fleet();
+        state.enable_history();
+        for shot in [(1, 0), (4, 0), (0, 8), (1, 8), (7, 7)] {
+            state.apply_shot(Position::new(shot.0, shot.1)).unwrap();
+        }
+
+        for (name, transform) in [
+            ("mirror_horizontal", GameState::mirror_horizontal as fn(&GameState) -> Option<GameState>),
+            ("mirror_vertical", GameState::mirror_vertical),
+            ("rotate_180", GameState::rotate_180),
+        ] {
+            let transformed = transform(&state).unwrap();
+            assert!(transformed.check(), "{name}");
+            assert_eq!(GameState::try_from_bytes(&bincode::serialize(&transformed).unwrap()), Ok(transformed.clone()), "{name} left the grid and ships inconsistent");
+            assert_eq!(transformed.fleet_health(), state.fleet_health(), "{name}");
+            assert_ne!(transformed.commit(), state.commit(), "{name}");
+            assert_eq!(transform(&transformed), Some(state.clone()), "{name} is its own inverse");
+
+            // A ship or shot outside `dims` has no image; refuse instead of underflowing
+            let mut stray = state.clone();
+            stray.ships[4].position = Position::new(9, 8);
+            assert_eq!(transform(&stray), None, "{name} with a ship off the board");
+            let mut far = state.clone();
+            far.ships[4].position = Position::new(u32::MAX, 8);
+            assert_eq!(transform(&far), None, "{name} with an overflowing ship");
+            let mut stray_shot = state.clone();
+            stray_shot.history.as_mut().unwrap().push((Position::new(12, 0), HitType::Miss));
+            assert_eq!(transform(&stray_shot), None, "{name} with a shot off the board");
+            let mut shrunk = state.clone();
+            shrunk.dims = BoardDims { width: 6, height: 10 };
+            assert_eq!(transform(&shrunk), None, "{name} with ships past a narrower board");
+        }
+
+        // The carrier spans (0..5, 0) with hits at x = 1 and 4, which land
+        // on x = 8 and 5: segments 3 and 0 of the mirrored ship
+        let mirrored = state.mirror_horizontal().unwrap();
+        assert_eq!((mirrored.ships[0].position, mirrored.ships[0].hits), (Position::new(5, 0), 0b01001));
+        assert_eq!(mirrored.grid[0][8], CellState::Hit);
+        assert_eq!(mirrored.history()[0].0, Position::new(8, 0));
+        assert_eq!(state.rotate_180(), mirrored.mirror_vertical());
+
+        // Rectangular boards flip within their own height
+        let mut short = GameState::with_dims([0; 16], BoardDims::new(10, 8).unwrap());
+        assert!(short.place_ship(ShipType::Submarine, Position::new(2, 0), Direction::Vertical));
+        let flipped = short.mirror_vertical().unwrap();
+        assert_eq!(flipped.ships[0].position, Position::new(2, 5));
+        assert_eq!(flipped.mirror_vertical(), Some(short));
+    }
+
+    #[test]
+    fn test_grid_snapshot_round_trips() {
+        let mut state = full_fleet();
+        for shot in [Position::new(0, 8), Position::new(5, 5), Position::new(9, 9)] {
+            state.apply_shot(shot);
+        }
+        let snapshot = state.grid_snapshot();
+        assert_eq!(snapshot.len(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!((snapshot[8 * BOARD_SIZE], snapshot[5 * BOARD_SIZE + 5], snapshot[0]), (2, 1, 0));
+
+        let mut view = GameState::new([0; 16]);
+        view.apply_grid_snapshot(&snapshot).unwrap();
+        assert_eq!(view.grid, state.grid);
+        assert_eq!(view.grid_snapshot(), snapshot);
+
+        let mut bad = snapshot.clone();
+        bad[3] = 3;
+        assert!(view.apply_grid_snapshot(&bad).is_err());
+        assert!(view.apply_grid_snapshot(&snapshot[1..]).is_err());
+        assert_eq!(view.grid, state.grid, "a rejected snapshot leaves the grid alone");
+    }
+
+    #[test]
+    fn test_grid_snapshot_ignores_ship_placement() {
+        let mut a = GameState::new([1; 16]);
+        assert!(a.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Horizontal));
+        assert!(a.place_ship(ShipType::Carrier, Position::new(0, 5), Direction::Horizontal));
+        let mut b = GameState::new([2; 16]);
+        assert!(b.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Vertical));
+        assert!(b.place_ship(ShipType::Cruiser, Position::new(7, 7), Direction::Vertical));
+        // (0,0) hits both boards' Destroyers; (4,4) misses both
+        for board in [&mut a, &mut b] {
+            board.apply_shot(Position::new(0, 0));
+            board.apply_shot(Position::new(4, 4));
+        }
+        assert_ne!(a.commit(), b.commit());
+        assert_eq!(a.grid_snapshot(), b.grid_snapshot());
+    }
+
+    #[test]
+    fn test_apply_shot_committed_matches_a_separate_commit() {
+        let mut state = full_fleet();
+        let mut separate = state.clone();
+        for shot in [Position::new(0, 8), Position::new(5, 5), Position::new(1, 8), Position::new(5, 5), Position::new(10, 0)] {
+            let (hit, digest) = state.apply_shot_committed(shot);
+            assert_eq!(hit, separate.apply_shot(shot));
+            assert_eq!(digest, separate.commit(), "after {:?}", shot);
+        }
+        assert_eq!(state, separate);
+    }
+
+    #[test]
+    fn test_verify_transition_accepts_only_the_real_round() {
+        let mut pre = full_fleet();
+        pre.apply_shot(Position::new(0, 8));
+        let mut post = pre.clone();
+        let (hit, new_state) = post.apply_shot_committed(Position::new(1, 8));
+        let hit = hit.unwrap();
+        let valid = RoundCommit { match_id: Uuid::nil(), seq: 0, old_state: pre.commit(), new_state, shot: Position::new(1, 8), sunk_cells: post.sunk_footprint(&hit), revealed_water: vec![], invalid: None, hit };
+        assert!(valid.verify_transition(&pre));
+        assert!(!valid.verify_transition(&post), "wrong pre-image");
+
+        let tampered = [
+            RoundCommit { hit: HitType::Hit, sunk_cells: vec![], ..valid.clone() },
+            RoundCommit { new_state: pre.commit(), ..valid.clone() },
+            RoundCommit { shot: Position::new(5, 5), ..valid.clone() },
+            RoundCommit { sunk_cells: vec![Position::new(1, 8), Position::new(2, 8)], ..valid.clone() },
+        ];
+        for rc in &tampered {
+            assert!(!rc.verify_transition(&pre), "{:?}", rc);
+        }
+
+        // A repeated shot is the guest's unchanged-board Miss
+        let repeat = RoundCommit { old_state: post.commit(), new_state: post.commit(), hit: HitType::Miss, sunk_cells: vec![], invalid: Some(ShotError::AlreadyShot), ..valid.clone() };
+        assert!(repeat.verify_transition(&post));
+        assert!(!RoundCommit { invalid: None, ..repeat.clone() }.verify_transition(&post), "a masked repeat is not the guest's output");
+    }
+
+    #[test]
+    fn test_sunk_ship_reveals_its_footprint() {
+        let mut state = full_fleet();
+        let old_state = state.commit();
+        let hit = state.apply_shot(Position::new(0, 8)).unwrap();
+        assert!(state.sunk_footprint(&hit).is_empty(), "a plain hit reveals nothing");
+        let hit = state.apply_shot(Position::new(1, 8)).unwrap();
+        let cells = state.sunk_footprint(&hit);
+        assert_eq!(cells, vec![Position::new(0, 8), Position::new(1, 8)]);
+
+        let commit = RoundCommit { match_id: Uuid::nil(), seq: 0, old_state, new_state: state.commit(), shot: Position::new(1, 8), hit, sunk_cells: cells, revealed_water: vec![], invalid: None };
+        assert!(commit.reveal_is_well_formed());
+        let mut shifted = commit.clone();
+        shifted.sunk_cells = vec![Position::new(1, 8), Position::new(2, 8)];
+        assert!(shifted.reveal_is_well_formed(), "the proof, not the shape check, pins the exact cells");
+        let mut short = commit.clone();
+        short.sunk_cells.pop();
+        assert!(!short.reveal_is_well_formed());
+        let mut bent = commit.clone();
+        bent.sunk_cells[0] = Position::new(0, 7);
+        assert!(!bent.reveal_is_well_formed());
+        let mut leaky = commit.clone();
+        leaky.hit = HitType::Hit;
+        assert!(!leaky.reveal_is_well_formed(), "only a sinking shot may reveal cells");
+        let mut far = commit.clone();
+        far.revealed_water = vec![Position::new(5, 5)];
+        assert!(!far.reveal_is_well_formed(), "revealed water must border the sunk ship");
+    }
+
+    #[test]
+    fn test_sinking_marks_all_ship_cells_sunk() {
+        let mut plain = full_fleet();
+        let mut marked = full_fleet();
+        marked.mark_sunk_cells = true;
+        for state in [&mut plain, &mut marked] {
+            assert_eq!(state.apply_shot(Position::new(0, 8)), Some(HitType::Hit));
+            assert_eq!(state.apply_shot(Position::new(0, 4)), Some(HitType::Hit));
+        }
+        assert_eq!(marked.grid[8][0], CellState::Hit, "a damaged ship keeps plain hits");
+
+        for state in [&mut plain, &mut marked] {
+            assert_eq!(state.apply_shot(Position::new(1, 8)), Some(HitType::Sunk(ShipType::Destroyer)));
+        }
+        assert_eq!((marked.grid[8][0], marked.grid[8][1]), (CellState::Sunk, CellState::Sunk));
+        assert_eq!(marked.grid[4][0], CellState::Hit, "the cruiser is still afloat");
+        assert_eq!(plain.grid[8][0], CellState::Hit);
+
+        // A view-layer mark: same commitment, still a consistent board
+        assert_eq!(marked.commit(), plain.commit());
+        assert_eq!(GameState::try_from_bytes(&bincode::serialize(&marked).unwrap()), Ok(marked.clone()));
+        assert!(marked.to_ascii(false).starts_with(&"..........\n".repeat(4)));
+        assert!(marked.to_ascii(false).contains("##........"));
+
+        // Under fog of war the sinking is not announced, so nothing is marked
+        let mut fog = full_fleet();
+        fog.mark_sunk_cells = true;
+        fog.reveal = RevealPolicy::HitMissOnly;
+        fog.apply_shot(Position::new(0, 8));
+        fog.apply_shot(Position::new(1, 8));
+        assert_eq!(fog.grid[8][1], CellState::Hit);
+    }
+
+    #[test]
+    fn test_sinking_marks_surrounding_water_under_no_touching() {
+        let mut state = GameState::new([0; 16]);
+        state.placement_rules.push(PlacementConstraint::NoTouching);
+        state.mark_sunk_surroundings = true;
+        assert!(state.place_ship(ShipType::Destroyer, Position::new(3, 4), Direction::Horizontal));
+        assert!(!state.can_place_ship(ShipType::Submarine, Position::new(5, 5), Direction::Horizontal), "diagonal contact is touching");
+        assert!(state.place_ship(ShipType::Submarine, Position::new(6, 5), Direction::Horizontal));
+        state.apply_shot(Position::new(2, 4));
+
+        assert_eq!(state.apply_shot(Position::new(3, 4)), Some(HitType::Hit));
+        assert_eq!(state.grid[3][3], CellState::Empty, "nothing is marked before the ship sinks");
+        let hit = state.apply_shot(Position::new(4, 4)).unwrap();
+        assert_eq!(hit, HitType::Sunk(ShipType::Destroyer));
+        let water = state.sunk_surroundings(&hit);
+        assert_eq!(water.len(), 10);
+        assert_eq!(water[0], Position::new(2, 3));
+        assert!(water.contains(&Position::new(2, 4)), "an earlier miss is still reported");
+        for p in &water {
+            assert_eq!(state.grid[p.y as usize][p.x as usize], CellState::Miss, "{:?}", p);
+        }
+        assert_eq!(state.grid[5][6], CellState::Empty, "the Submarine is not touched");
+        let commit = RoundCommit { match_id: Uuid::nil(), seq: 0, old_state: Digest::default(), new_state: state.commit(), shot: Position::new(4, 4), sunk_cells: state.sunk_footprint(&hit), hit, revealed_water: water, invalid: None };
+        assert!(commit.reveal_is_well_formed());
+
+        // Without the no-touching rule nothing around a sunk ship is known
+        let mut plain = GameState::new([0; 16]);
+        plain.mark_sunk_surroundings = true;
+        assert!(plain.place_ship(ShipType::Destroyer, Position::new(3, 4), Direction::Horizontal));
+        plain.apply_shot(Position::new(3, 4));
+        let hit = plain.apply_shot(Position::new(4, 4)).unwrap();
+        assert!(plain.sunk_surroundings(&hit).is_empty());
+        assert_eq!(plain.grid[3][3], CellState::Empty);
+    }
+
+    #[test]
+    fn test_try_from_bytes_rejects_malformed_input() {
+        let mut valid = full_fleet();
+        valid.enable_history();
+        valid.apply_shot(Position::new(0, 8));
+        valid.apply_shot(Position::new(9, 9));
+        let bytes = bincode::serialize(&valid).unwrap();
+        assert_eq!(GameState::try_from_bytes(&bytes), Ok(valid.clone()));
+
+        // bincode layout: u64 ship count, 17 bytes per ship (x at +4), the
+        // 16 pepper bytes, then one u32 tag per grid cell.
+        let patched = |offset: usize, patch: &[u8]| {
+            let mut b = bytes.clone();
+            b[offset..offset + patch.len()].copy_from_slice(patch);
+            b
+        };
+        let grid_offset = 8 + NUM_SHIPS * 17 + 16;
+        let malformed: Vec<(&str, Vec<u8>)> = vec![
+            ("empty", vec![]),
+            ("truncated", bytes[..bytes.len() - 1].to_vec()),
+            ("trailing byte", [bytes.as_slice(), &[0]].concat()),
+            ("huge ship count", patched(0, &u64::MAX.to_le_bytes())),
+            ("unknown cell tag", patched(grid_offset, &7u32.to_le_bytes())),
+        ];
+        for (name, b) in malformed {
+            assert!(matches!(GameState::try_from_bytes(&b), Err(DecodeError::Malformed(_))), "{name}");
+        }
+        assert_eq!(GameState::try_from_bytes(&patched(12, &u32::MAX.to_le_bytes())), Err(DecodeError::InvalidFleet), "overflowing ship start");
+
+        // Dimensions past the fixed grid would let a ship run off its edge
+        let mut oversized = valid.clone();
+        oversized.dims = BoardDims { width: 12, height: 12 };
+        oversized.ships[0].position = Position::new(8, 0);
+        assert!(matches!(GameState::try_from_bytes(&bincode::serialize(&oversized).unwrap()), Err(DecodeError::Malformed(_))), "oversized dims");
+
+        let mut overlapping = valid.clone();
+        overlapping.ships[1].position = Position::new(0, 0);
+        let mut duplicate = valid.clone();
+        duplicate.ships[4].ship_type = ShipType::Submarine;
+        for state in [overlapping, duplicate] {
+            assert_eq!(GameState::try_from_bytes(&bincode::serialize(&state).unwrap()), Err(DecodeError::InvalidFleet));
+        }
+        // A smaller fleet is a board of its own, not a malformed one
+        let mut partial = full_fleet();
+        partial.ships.pop();
+        assert_eq!(GameState::try_from_bytes(&bincode::serialize(&partial).unwrap()).as_ref(), Ok(&partial));
+
+        let mut hit_on_water = valid.clone();
+        hit_on_water.grid[5][5] = CellState::Hit;
+        let mut miss_on_ship = valid.clone();
+        miss_on_ship.grid[0][0] = CellState::Miss;
+        let mut unmarked_hit = valid.clone();
+        unmarked_hit.ships[0].hits = 0b1;
+        let mut mask_past_size = valid.clone();
+        mask_past_size.ships[4].hits |= 0b100;
+        let mut phantom_history = valid.clone();
+        phantom_history.history.as_mut().unwrap().push((Position::new(5, 5), HitType::Miss));
+        for (state, pos) in [(hit_on_water, (5, 5)), (miss_on_ship, (0, 0)), (unmarked_hit, (0, 0)), (mask_past_size, (0, 8)), (phantom_history, (5, 5))] {
+            assert_eq!(GameState::try_from_bytes(&bincode::serialize(&state).unwrap()), Err(DecodeError::InconsistentGrid(Position::new(pos.0, pos.1))));
+        }
+
+        // Random byte flips: anything accepted must still be a sound board.
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..5000 {
+            let mut b = bytes.clone();
+            for _ in 0..1 + next() % 4 {
+                let i = (next() % b.len() as u64) as usize;
+                b[i] = next() as u8;
+            }
+            b.truncate(b.len() - (next() % 3) as usize);
+            if let Ok(state) = GameState::try_from_bytes(&b) {
+                assert!(state.check() && state.first_inconsistent_cell().is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_shot_meta_recorded_in_order() {
+        let mut state = full_fleet();
+        let before = state.commit();
+        let shots = [(Position::new(9, 9), Player::One), (Position::new(0, 0), Player::One), (Position::new(5, 5), Player::Two)];
+        for (turn, &(pos, shooter)) in shots.iter().enumerate() {
+            assert!(state.apply_shot_by(pos, ShotMeta { turn: turn as u32 + 1, shooter }).is_some());
+        }
+        for (turn, &(pos, shooter)) in shots.iter().enumerate() {
+            assert_eq!(state.grid_meta[pos.y as usize][pos.x as usize], Some(ShotMeta { turn: turn as u32 + 1, shooter }));
+        }
+        // A rejected repeat keeps the original mark; plain shots carry none
+        assert_eq!(state.apply_shot_by(Position::new(0, 0), ShotMeta { turn: 9, shooter: Player::Two }), None);
+        assert_eq!(state.grid_meta[0][0], Some(ShotMeta { turn: 2, shooter: Player::One }));
+        state.apply_shot(Position::new(1, 1));
+        assert_eq!(state.grid_meta[1][1], None);
+        assert_eq!(state.grid_meta.iter().flatten().filter(|m| m.is_some()).count(), 3);
+
+        // Metadata stays out of the commitment
+        let mut plain = full_fleet();
+        assert_eq!(plain.commit(), before);
+        for pos in [Position::new(9, 9), Position::new(0, 0), Position::new(5, 5), Position::new(1, 1)] {
+            plain.apply_shot(pos);
+        }
+        assert_eq!(plain.commit(), state.commit());
+    }
+
+    #[test]
+    fn test_ship_at_reports_occupying_type() {
+        let state = full_fleet();
+        assert_eq!(state.ship_at(Position::new(0, 0)), Some(ShipType::Carrier));
+        assert_eq!(state.ship_at(Position::new(4, 0)), Some(ShipType::Carrier));
+        assert_eq!(state.ship_at(Position::new(1, 8)), Some(ShipType::Destroyer));
+        assert_eq!(state.ship_at(Position::new(5, 0)), None);
+        assert_eq!(state.ship_at(Position::new(0, 1)), None);
+        assert_eq!(state.ship_at(Position::new(10, 0)), None);
+
+        let map = state.occupancy();
+        for p in Position::all_in_bounds() {
+            assert_eq!(map[p.y as usize][p.x as usize], state.ship_at(p));
+        }
+        assert_eq!(map.iter().flatten().filter(|c| c.is_some()).count(), SHIP_SIZES.iter().map(|&s| s as usize).sum::<usize>());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_spectator_json_redacts_pepper() {
+        let mut state = full_fleet();
+        state.pepper = [0xA5; 16];
+        state.apply_shot(Position::new(0, 0));
+        let json = state.to_spectator_json();
+
+        let raw: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(raw["pepper_redacted"], serde_json::Value::Bool(true));
+        assert!(raw["pepper"].as_array().unwrap().iter().all(|b| b.as_u64() == Some(0)));
+        assert!(!json.contains("165"), "pepper byte leaked: {json}");
+
+        let board = SpectatorBoard::from_spectator_json(&json).unwrap();
+        assert_eq!((board.ships, board.grid), (state.ships.clone(), state.grid));
+
+        // Anything carrying a pepper is refused
+        let mut leaky = raw.clone();
+        leaky["pepper"][0] = 7.into();
+        assert!(SpectatorBoard::from_spectator_json(&leaky.to_string()).is_err());
+        let mut unflagged = raw;
+        unflagged["pepper_redacted"] = false.into();
+        assert!(SpectatorBoard::from_spectator_json(&unflagged.to_string()).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_hit_type_json_and_bincode_carry_the_same_bytes() {
+        let sunk = HitType::Sunk(ShipType::Cruiser);
+        let json = serde_json::to_string(&sunk).unwrap();
+        assert_eq!(json, "[2,2]");
+        assert_eq!(serde_json::from_str::<HitType>(&json).unwrap(), sunk);
+        let bin = bincode::serialize(&sunk).unwrap();
+        assert_eq!(&bin[8..], &[2, 2], "length prefix, then tag and ship index");
+        assert_eq!(bincode::deserialize::<HitType>(&bin).unwrap(), sunk);
+
+        let hidden = HitType::SunkHidden(Digest::from([7u8; 32]));
+        for hit in [HitType::Miss, HitType::Hit, hidden] {
+            assert_eq!(serde_json::from_str::<HitType>(&serde_json::to_string(&hit).unwrap()).unwrap(), hit);
+            assert_eq!(bincode::deserialize::<HitType>(&bincode::serialize(&hit).unwrap()).unwrap(), hit);
+        }
+        assert!(serde_json::from_str::<HitType>("[2,5]").is_err(), "no sixth ship type");
+        assert!(serde_json::from_str::<HitType>("\"Miss\"").is_err());
+    }
+
+    #[test]
+    fn test_placement_order_does_not_change_commit() {
+        let placements = [
+            (ShipType::Carrier, Position::new(0, 0), Direction::Horizontal),
+            (ShipType::Battleship, Position::new(0, 2), Direction::Horizontal),
+            (ShipType::Cruiser, Position::new(0, 4), Direction::Horizontal),
+            (ShipType::Submarine, Position::new(0, 6), Direction::Horizontal),
+            (ShipType::Destroyer, Position::new(0, 8), Direction::Horizontal),
+        ];
+        let mut reversed = GameState::new([0; 16]);
+        assert!(reversed.place_ships(placements.iter().rev().cloned().collect()));
+        assert_eq!(reversed, full_fleet());
+        assert_eq!(reversed.commit(), full_fleet().commit());
+
+        // Ships pushed directly keep their order until canonicalized
+        let mut shuffled = GameState::new([0; 16]);
+        shuffled.ships = [4, 1, 3, 0, 2].iter().map(|&i| Ship::new(placements[i].0, placements[i].1, placements[i].2)).collect();
+        assert_ne!(shuffled.commit(), full_fleet().commit());
+        shuffled.canonicalize();
+        assert_eq!(shuffled.commit(), full_fleet().commit());
+        assert_eq!(shuffled.hash_ships_only(), full_fleet().hash_ships_only());
+    }
+
+    #[test]
+    fn test_board_eq_ignores_ship_order_but_not_the_board() {
+        let placements = [(ShipType::Carrier, 0), (ShipType::Battleship, 2), (ShipType::Cruiser, 4), (ShipType::Submarine, 6), (ShipType::Destroyer, 8)];
+        let mut shuffled = GameState::new([0; 16]);
+        shuffled.ships = [3, 0, 4, 2, 1].iter().map(|&i| Ship::new(placements[i].0, Position::new(0, placements[i].1), Direction::Horizontal)).collect();
+        let ordered = full_fleet();
+        assert_ne!(shuffled, ordered, "derive-equal compares ships in order");
+        assert!(shuffled.board_eq(&ordered) && ordered.board_eq(&shuffled));
+
+        // Fields outside the commitment do not matter; shots and the pepper do
+        let mut recorded = ordered.clone();
+        recorded.enable_history();
+        recorded.mark_sunk_surroundings = true;
+        assert!(recorded.board_eq(&shuffled));
+        let (mut shot, mut shuffled_shot) = (ordered.clone(), shuffled.clone());
+        shot.apply_shot(Position::new(0, 0));
+        assert!(!shot.board_eq(&shuffled));
+        shuffled_shot.apply_shot(Position::new(0, 0));
+        assert!(shot.board_eq(&shuffled_shot));
+        assert!(!GameState { pepper: [1; 16], ..ordered.clone() }.board_eq(&ordered));
+    }
+
+    #[test]
+    fn test_direction_and_ship_type_parse_from_text() {
+        for (text, dir) in [("H", Direction::Horizontal), ("v", Direction::Vertical), ("horizontal", Direction::Horizontal), (" VERTICAL ", Direction::Vertical), ("Horizontal", Direction::Horizontal)] {
+            assert_eq!(Direction::try_from(text), Ok(dir), "{:?}", text);
+        }
+        for (text, st) in [("C", ShipType::Carrier), ("b", ShipType::Battleship), ("R", ShipType::Cruiser), ("s", ShipType::Submarine), ("d", ShipType::Destroyer), ("carrier", ShipType::Carrier), ("CRUISER", ShipType::Cruiser), (" Destroyer", ShipType::Destroyer)] {
+            assert_eq!(ShipType::try_from(text), Ok(st), "{:?}", text);
+        }
+        for st in ShipType::ALL {
+            assert_eq!(ShipType::try_from(format!("{:?}", st).as_str()), Ok(st));
+            assert_eq!(ShipType::try_from(st.letter().to_string().as_str()), Ok(st));
+        }
+
+        for garbage in ["", "x", "HV", "diagonal", "horizontally"] {
+            assert_eq!(Direction::try_from(garbage), Err(ParseNameError::Direction(garbage.to_string())));
+        }
+        for garbage in ["", "X", "CB", "carriers", "sub", "5"] {
+            assert_eq!(ShipType::try_from(garbage), Err(ParseNameError::ShipType(garbage.to_string())));
+        }
+        let err = Direction::try_from("up").unwrap_err().to_string();
+        assert!(err.contains("\"up\"") && err.contains("H, V"), "{}", err);
+        let err = ShipType::try_from("Frigate").unwrap_err().to_string();
+        assert!(err.contains("\"Frigate\"") && err.contains("Carrier"), "{}", err);
+    }
+
+    #[test]
+    fn test_direction_perpendicular_and_heading_opposite() {
+        assert_eq!(Direction::Horizontal.perpendicular(), Direction::Vertical);
+        assert_eq!(Direction::Vertical.perpendicular(), Direction::Horizontal);
+        for dir in [Direction::Horizontal, Direction::Vertical] {
+            assert_eq!(dir.perpendicular().perpendicular(), dir);
+            assert_eq!(dir.forward().axis(), dir);
+            assert_eq!(dir.forward().opposite().axis(), dir);
+        }
+        assert_eq!(Heading::Up.opposite(), Heading::Down);
+        assert_eq!(Heading::Left.opposite(), Heading::Right);
+
+        // Stepping forward then back returns to the start; the forward step
+        // agrees with `Position::step`
+        let start = Position::new(4, 4);
+        for heading in [Heading::Up, Heading::Down, Heading::Left, Heading::Right] {
+            assert_ne!(heading.opposite(), heading);
+            assert_eq!(heading.opposite().opposite(), heading);
+            let next = start.step_toward(heading).unwrap();
+            assert_ne!(next, start);
+            assert_eq!(next.step_toward(heading.opposite()), Some(start));
+        }
+        assert_eq!(start.step_toward(Direction::Vertical.forward()), Some(start.step(Direction::Vertical, 1)));
+
+        // Probing past the board's edges stops instead of wrapping
+        assert_eq!(Position::new(0, 3).step_toward(Heading::Left), None);
+        assert_eq!(Position::new(3, 0).step_toward(Heading::Up), None);
+        assert_eq!(Position::new(BOARD_SIZE as u32 - 1, 3).step_toward(Heading::Right), None);
+    }
+
+    #[test]
+    fn test_fleet_health_counts_hits_per_ship() {
+        let mut state = full_fleet();
+        // Place order is not index order
+        state.ships.reverse();
+        for shot in [(1, 0), (3, 0), (0, 4), (9, 9)] {
+            state.apply_shot(Position::new(shot.0, shot.1)).unwrap();
+        }
+        assert_eq!(state.fleet_health(), vec![
+            (ShipType::Carrier, 2, 5),
+            (ShipType::Battleship, 0, 4),
+            (ShipType::Cruiser, 1, 3),
+            (ShipType::Submarine, 0, 3),
+            (ShipType::Destroyer, 0, 2),
+        ]);
+
+        state.apply_shot(Position::new(0, 8)).unwrap();
+        state.apply_shot(Position::new(1, 8)).unwrap();
+        assert_eq!(state.fleet_health()[4], (ShipType::Destroyer, 2, 2));
+    }
+
+    #[test]
+    fn test_stacked_ships_share_hits() {
+        let mut state = GameState::new([0; 16]);
+        assert!(state.place_ship(ShipType::Cruiser, Position::new(2, 2), Direction::Horizontal));
+        assert!(!state.can_place_ship(ShipType::Destroyer, Position::new(3, 1), Direction::Vertical), "overlap is forbidden by default");
+
+        state.overlap = OverlapRule::Allowed;
+        assert!(state.place_ship(ShipType::Destroyer, Position::new(3, 1), Direction::Vertical));
+        assert_eq!(state.apply_shot(Position::new(3, 2)), Some(HitType::Hit));
+        let cruiser = state.ships.iter().find(|s| s.ship_type == ShipType::Cruiser).unwrap();
+        let destroyer = state.ships.iter().find(|s| s.ship_type == ShipType::Destroyer).unwrap();
+        assert_eq!((cruiser.hits, destroyer.hits), (0b010, 0b10));
+
+        // Finishing the destroyer reports it sunk; the cruiser is still afloat
+        assert_eq!(state.apply_shot(Position::new(3, 1)), Some(HitType::Sunk(ShipType::Destroyer)));
+        assert_eq!(state.ships_remaining(), 1);
+
+        let mut stacked = full_fleet();
+        stacked.overlap = OverlapRule::Allowed;
+        stacked.ships[4].position = Position::new(0, 0);
+        assert!(stacked.check());
+        stacked.overlap = OverlapRule::Forbidden;
+        assert!(!stacked.check());
+    }
+
+    #[test]
+    fn test_replay_history_keeps_stacked_ships() {
+        let mut stacked = full_fleet();
+        stacked.overlap = OverlapRule::Allowed;
+        stacked.ships[4].position = Position::new(0, 0);
+        stacked.enable_history();
+        assert_eq!(stacked.apply_shot(Position::new(0, 0)), Some(HitType::Hit));
+        assert_eq!(stacked.apply_shot(Position::new(1, 0)), Some(HitType::Sunk(ShipType::Destroyer)));
+
+        let replayed = stacked.replay_history();
+        assert_eq!(replayed.overlap, OverlapRule::Allowed);
+        assert!(replayed.check());
+        assert_eq!(replayed, stacked);
+    }
+
+    #[test]
+    fn test_player_turn_sequence() {
+        assert_eq!(Player::One.other(), Player::Two);
+        assert_eq!(Player::Two.other(), Player::One);
+        assert_eq!((Player::One.index(), Player::Two.index()), (0, 1));
+
+        // Default rules: a hit shoots again, a miss or a sinking passes
+        let rules = TurnRules::default();
+        let results = [HitType::Miss, HitType::Hit, HitType::Hit, HitType::Sunk(ShipType::Destroyer), HitType::Hit, HitType::Miss];
+        let mut to_move = Player::One;
+        let mut shooters = Vec::new();
+        for hit in &results {
+            shooters.push(to_move);
+            to_move = rules.next_state(to_move, hit).next_player;
+        }
+        use Player::{One, Two};
+        assert_eq!(shooters, [One, Two, Two, Two, One, One]);
+        assert_eq!(to_move, Two);
+    }
+
+    #[test]
+    fn test_unshot_cells_until_board_exhausted() {
+        let mut state = full_fleet();
+        assert_eq!(state.remaining_unshot(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(state.unshot_cells()[..2], [Position::new(0, 0), Position::new(1, 0)]);
+
+        state.apply_shot(Position::new(1, 0));
+        assert_eq!(state.remaining_unshot(), BOARD_SIZE * BOARD_SIZE - 1);
+        assert!(!state.unshot_cells().contains(&Position::new(1, 0)));
+
+        while let Some(&pos) = state.unshot_cells().first() {
+            assert!(state.apply_shot(pos).is_some());
+        }
+        assert_eq!(state.remaining_unshot(), 0);
+        assert!(state.unshot_cells().is_empty());
+        assert!(state.all_ships_sunk());
+    }
+
+    #[test]
+    fn test_pepper_from_seed_uses_wide_commit_format() {
+        // RFC 5869 test case 1
+        let mut okm = [0u8; 42];
+        hkdf_sha256(&(0..=0x0c).collect::<Vec<u8>>(), &[0x0b; 22], &(0xf0..=0xf9).collect::<Vec<u8>>(), &mut okm);
+        let expected = "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+        assert_eq!(okm.iter().map(|b| format!("{:02x}", b)).collect::<String>(), expected);
+
+        let place = |mut state: GameState| {
+            assert!(state.place_ships(full_fleet().ships.iter().map(|s| (s.ship_type, s.position, s.direction)).collect()));
+            state
+        };
+        let a = place(GameState::with_pepper_from_seed(b"seed a"));
+        let b = place(GameState::with_pepper_from_seed(b"seed b"));
+        assert_eq!(a.wide_pepper, place(GameState::with_pepper_from_seed(b"seed a")).wide_pepper);
+        assert_ne!(a.wide_pepper, b.wide_pepper);
+        assert_ne!(a.commit(), b.commit());
+        assert_ne!(a.hash_ships_only(), b.hash_ships_only());
+
+        let bytes = a.serialize_for_commit();
+        assert_eq!(bytes[0], COMMIT_FORMAT_WIDE_PEPPER);
+        assert_eq!(bytes.len(), 1 + 4 + 5 * 11 + 32 + BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(a.replay_history().commit(), a.commit());
+        // `pepper` is ignored once a wide pepper is set
+        let mut c = a.clone();
+        c.pepper = [9; 16];
+        assert_eq!(c.commit(), a.commit());
+        assert_eq!(full_fleet().serialize_for_commit()[0], COMMIT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_all_in_bounds_covers_board_once() {
+        let all: Vec<Position> = Position::all_in_bounds().collect();
+        assert_eq!(all.len(), BOARD_SIZE * BOARD_SIZE);
+        assert!(all.iter().all(Position::in_bounds));
+        assert_eq!(all.iter().collect::<std::collections::HashSet<_>>().len(), all.len());
+        assert_eq!(all[..2], [Position::new(0, 0), Position::new(1, 0)]);
+        assert_eq!(all.last(), Some(&Position::new(BOARD_SIZE as u32 - 1, BOARD_SIZE as u32 - 1)));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_place_remaining_keeps_manual_ships() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut state = GameState::new([0; 16]);
+            assert!(state.place_ship(ShipType::Carrier, Position::new(2, 3), Direction::Vertical));
+            let carrier = state.ships[0].clone();
+
+            assert!(state.place_remaining_randomly(&mut rng));
+            assert!(state.is_placement_complete(&FleetSpec::classic()));
+            assert!(state.check());
+            assert_eq!(state.ships.iter().find(|s| s.ship_type == ShipType::Carrier), Some(&carrier));
+        }
+
+        // A complete board is left alone
+        let mut full = full_fleet();
+        let before = full.clone();
+        assert!(full.place_remaining_randomly(&mut StdRng::seed_from_u64(1)));
+        assert_eq!(full, before);
+    }
+
+    #[test]
+    fn test_max_per_line_limits_ship_cells() {
+        let mut state = GameState::new([0; 16]);
+        state.placement_rules.push(PlacementConstraint::MaxPerLine(7));
+        // Row 0 fills up to the limit: Carrier (5) + Destroyer (2)
+        assert!(state.place_ship(ShipType::Carrier, Position::new(0, 0), Direction::Horizontal));
+        assert!(state.place_ship(ShipType::Destroyer, Position::new(6, 0), Direction::Horizontal));
+        // A vertical Submarine would add an eighth cell to row 0
+        assert!(!state.can_place_ship(ShipType::Submarine, Position::new(9, 0), Direction::Vertical));
+        assert!(state.place_ship(ShipType::Submarine, Position::new(9, 1), Direction::Vertical));
+
+        // Columns count too
+        let mut column = GameState::new([0; 16]);
+        column.placement_rules.push(PlacementConstraint::MaxPerLine(5));
+        assert!(column.place_ship(ShipType::Carrier, Position::new(0, 0), Direction::Vertical));
+        assert!(!column.can_place_ship(ShipType::Destroyer, Position::new(0, 5), Direction::Vertical));
+        assert!(column.can_place_ship(ShipType::Destroyer, Position::new(1, 5), Direction::Vertical));
+
+        // check() enforces the rule on a deserialized board as well
+        let mut board = full_fleet();
+        assert!(board.check());
+        board.placement_rules.push(PlacementConstraint::MaxPerLine(4));
+        assert!(!board.check());
+        board.placement_rules[0] = PlacementConstraint::MaxPerLine(5);
+        assert!(board.check());
+    }
+
+    #[test]
+    fn test_hit_miss_only_hides_sinking() {
+        let mut state = GameState::new([0; 16]);
+        state.reveal = RevealPolicy::HitMissOnly;
+        assert!(state.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Horizontal));
+        assert_eq!(state.apply_shot(Position::new(0, 0)), Some(HitType::Hit));
+        assert_eq!(state.apply_shot(Position::new(1, 0)), Some(HitType::Hit));
+        assert!(state.ships[0].is_sunk());
+        assert!(state.all_ships_sunk());
+        // Nothing is revealed for a masked result
+        assert!(state.sunk_footprint(&HitType::Hit).is_empty());
+
+        // apply_shot_no_reveal masks regardless of the policy
+        let mut announced = GameState::new([0; 16]);
+        assert!(announced.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Horizontal));
+        assert_eq!(announced.apply_shot(Position::new(0, 0)), Some(HitType::Hit));
+        assert_eq!(announced.clone().apply_shot(Position::new(1, 0)), Some(HitType::Sunk(ShipType::Destroyer)));
+        assert_eq!(announced.apply_shot_no_reveal(Position::new(1, 0)), Some(HitType::Hit));
+        assert!(announced.ships[0].is_sunk());
+        // The policy is not committed to
+        assert_eq!(announced.commit(), state.commit());
+
+        // Replaying a recorded game keeps the policy, so results stay masked
+        let mut recorded = GameState::new([0; 16]);
+        recorded.reveal = RevealPolicy::HitMissOnly;
+        assert!(recorded.place_ship(ShipType::Destroyer, Position::new(0, 0), Direction::Horizontal));
+        recorded.enable_history();
+        recorded.apply_shot(Position::new(0, 0));
+        recorded.apply_shot(Position::new(1, 0));
+        let replayed = recorded.replay_history();
+        assert_eq!(replayed.history(), [(Position::new(0, 0), HitType::Hit), (Position::new(1, 0), HitType::Hit)]);
+        assert_eq!(replayed, recorded);
+    }
+
+    #[test]
+    fn test_footprint_contains_matches_coordinates() {
+        let probes: Vec<Position> = (0..14).flat_map(|y| (0..14).map(move |x| Position::new(x, y))).collect();
+        for ship_type in ShipType::ALL {
+            for direction in [Direction::Horizontal, Direction::Vertical] {
+                for start in [Position::new(0, 0), Position::new(3, 7), Position::new(9, 9), Position::new(8, 2)] {
+                    let ship = Ship::new(ship_type, start, direction);
+                    let coords = ship.get_coordinates();
+                    for &pos in &probes {
+                        assert_eq!(ship.footprint_contains(pos), coords.contains(&pos), "{:?} at {:?} {:?}, probe {:?}", ship_type, start, direction, pos);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_commit_detects_moved_ship() {
+        let mut board = full_fleet();
+        board.apply_shot(Position::new(4, 4));
+        let committed = board.commit();
+        assert!(board.matches_commit(&committed));
+
+        let mut moved = board.clone();
+        moved.ships[4].position = Position::new(1, 8);
+        assert!(moved.check());
+        assert!(!moved.matches_commit(&committed));
+    }
+
+    #[test]
+    fn test_from_ascii_builds_fleet() {
+        let state = GameState::from_ascii(
+            "
+            CCCCC.....
+            ..........
+            B.........
+            B...RRR...
+            B.........
+            B......S..
+            .......S..
+            .......S..
+            ..........
+            ........DD
+            ",
+        )
+        .unwrap();
+        assert!(state.is_placement_complete(&FleetSpec::classic()));
+        assert_eq!(state.ships[0], Ship::new(ShipType::Carrier, Position::new(0, 0), Direction::Horizontal));
+        assert_eq!(state.ships[1], Ship::new(ShipType::Battleship, Position::new(0, 2), Direction::Vertical));
+        assert_eq!(state.ship_at(Position::new(5, 3)), Some(ShipType::Cruiser));
+        assert_eq!(state.ship_at(Position::new(7, 7)), Some(ShipType::Submarine));
+        assert_eq!(state.ship_at(Position::new(9, 9)), Some(ShipType::Destroyer));
+
+        let empty = "..........\n".repeat(BOARD_SIZE);
+        let bent = format!("RR........\n.R........\n{}", "..........\n".repeat(BOARD_SIZE - 2));
+        assert_eq!(GameState::from_ascii(&bent), Err(ParseError::NotStraight(ShipType::Cruiser)));
+        let short = empty.replacen("..........", "CCCC......", 1);
+        assert_eq!(GameState::from_ascii(&short), Err(ParseError::WrongLength { ship_type: ShipType::Carrier, found: 4 }));
+        let gap = empty.replacen("..........", "RR.R......", 1);
+        assert_eq!(GameState::from_ascii(&gap), Err(ParseError::NotStraight(ShipType::Cruiser)));
+        assert_eq!(GameState::from_ascii(&empty.replacen('.', "x", 1)), Err(ParseError::UnknownChar { pos: Position::new(0, 0), ch: 'x' }));
+        assert_eq!(GameState::from_ascii("...."), Err(ParseError::RowCount(1)));
+        assert!(GameState::from_ascii(&empty).unwrap().ships.is_empty());
+    }
+
+    #[test]
+    fn test_to_ascii_round_trips_with_from_ascii() {
+        let mut board = full_fleet();
+        board.ships[1] = Ship::new(ShipType::Battleship, Position::new(9, 3), Direction::Vertical);
+        board.canonicalize();
+        let text = board.to_ascii(true);
+        assert_eq!(text.lines().next(), Some("CCCCC....."));
+        let parsed = GameState::from_ascii(&text).unwrap();
+        assert_eq!(parsed.ships, board.ships);
+        assert_eq!(parsed.to_ascii(true), text);
+
+        // Hidden ships print as water; shots always show
+        board.apply_shot(Position::new(0, 0));
+        board.apply_shot(Position::new(5, 5));
+        let hidden = board.to_ascii(false);
+        assert_eq!(hidden.lines().next(), Some("X........."));
+        assert_eq!(hidden.lines().nth(5), Some(".....o...."));
+        assert_eq!(hidden.matches(|c: char| c.is_ascii_uppercase() && c != 'X').count(), 0);
+        assert_eq!(board.to_ascii(true).lines().next(), Some("XCCCC....."));
+    }
+}
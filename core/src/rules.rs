@@ -0,0 +1,117 @@
+// Turn logic shared by every game loop.
+//
+// The local hot-seat game, the scripted demo, `game_round` and the
+// networked coordinator all decide whose turn it is with
+// `TurnRules::next_state`, and the guest checks turn order with the same
+// `keeps_turn`, so a rule change here applies everywhere at once. Under
+// the default rules a hit earns another shot; a miss or a sinking passes
+// the turn.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, HitType, Player};
+
+/// How many shots a player fires per turn.
+///
+/// - `Classic`: one shot; a non-sinking hit grants another shot.
+/// - `Salvo`: one shot per surviving ship, all fired before the turn
+///   passes, with no extra shot on a hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum TurnMode {
+    #[default]
+    Classic,
+    Salvo,
+}
+
+impl TurnMode {
+    /// Number of shots `shooter` must submit for one turn under this mode.
+    pub fn shots_per_turn(&self, shooter: &GameState) -> usize {
+        match self {
+            TurnMode::Classic => 1,
+            TurnMode::Salvo => shooter.ships_remaining(),
+        }
+    }
+}
+
+/// Classic-mode rules for whether a shot earns its shooter another shot.
+/// The default is the standard game: a hit shoots again, a miss or a
+/// sinking passes the turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TurnRules {
+    pub extra_turn_on_hit: bool,
+    pub extra_turn_on_sunk: bool,
+}
+
+impl Default for TurnRules {
+    fn default() -> Self {
+        Self { extra_turn_on_hit: true, extra_turn_on_sunk: false }
+    }
+}
+
+impl TurnRules {
+    /// True if the shooter fires again after a shot that resulted in `hit`.
+    /// A miss always passes the turn.
+    pub fn keeps_turn(&self, hit: &HitType) -> bool {
+        match hit {
+            HitType::Miss => false,
+            HitType::Hit => self.extra_turn_on_hit,
+            HitType::Sunk(_) | HitType::SunkHidden(_) => self.extra_turn_on_sunk,
+        }
+    }
+
+    /// What happens after `current`'s shot resulted in `outcome`: every
+    /// game loop, local or networked, advances the turn through here.
+    pub fn next_state(&self, current: Player, outcome: &HitType) -> TurnTransition {
+        let extra_shot = self.keeps_turn(outcome);
+        TurnTransition { next_player: if extra_shot { current } else { current.other() }, extra_shot }
+    }
+
+    /// Who moves after `shooter`'s shot resulted in `hit`.
+    pub fn next_to_move(&self, shooter: Player, hit: &HitType) -> Player {
+        self.next_state(shooter, hit).next_player
+    }
+}
+
+/// Result of `TurnRules::next_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnTransition {
+    /// Who shoots next.
+    pub next_player: Player,
+    /// The shooter goes again (`next_player` is the shooter).
+    pub extra_shot: bool,
+}
+
+/// Where a game's turn order stands. Game loops that must survive a
+/// disconnect keep one up to date with `advance` and save it with the
+/// session, so a player resumed in the middle of a hit streak still holds
+/// the extra shot they earned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TurnState {
+    /// Who shoots next.
+    pub to_move: Player,
+    /// Extra shots `to_move` has earned in a row without the turn passing;
+    /// 0 at the start of a turn.
+    pub streak: u32,
+}
+
+impl Default for TurnState {
+    /// Player One to open the game.
+    fn default() -> Self {
+        Self { to_move: Player::One, streak: 0 }
+    }
+}
+
+impl TurnState {
+    /// Move past `shooter`'s shot that resulted in `hit`.
+    pub fn advance(&mut self, rules: &TurnRules, shooter: Player, hit: &HitType) -> TurnTransition {
+        let transition = rules.next_state(shooter, hit);
+        self.streak = if transition.extra_shot { self.streak + 1 } else { 0 };
+        self.to_move = transition.next_player;
+        transition
+    }
+
+    /// Hand the turn to `player` with no streak, e.g. after a salvo.
+    pub fn pass_to(&mut self, player: Player) {
+        *self = Self { to_move: player, streak: 0 };
+    }
+}
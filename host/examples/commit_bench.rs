@@ -0,0 +1,88 @@
+// Compare the full-state commitment with the Merkle-ized one over a
+// 20-shot game.
+//
+// Usage:
+//    cargo run -p host --release --example commit_bench
+//
+// Wall-clock numbers are for the host. Inside the guest, cost is dominated
+// by SHA-256 compressions, so the example also prints compression counts
+// per shot for a range of board sizes and the size at which the Merkle
+// update becomes cheaper. The counts assume `hash_pair` is a single
+// compression, as in the zkVM's accelerated SHA-256.
+use core::{Direction, GameState, Position, ShipType, BOARD_SIZE, GRID_TREE_DEPTH};
+use std::time::Instant;
+
+const ROUNDS: u32 = 200;
+
+fn board() -> GameState {
+    let mut state = GameState::new([7; 16]);
+    assert!(state.place_ships(vec![
+        (ShipType::Carrier, Position::new(0, 0), Direction::Horizontal),
+        (ShipType::Battleship, Position::new(0, 2), Direction::Horizontal),
+        (ShipType::Cruiser, Position::new(0, 4), Direction::Horizontal),
+        (ShipType::Submarine, Position::new(0, 6), Direction::Horizontal),
+        (ShipType::Destroyer, Position::new(0, 8), Direction::Horizontal),
+    ]));
+    state
+}
+
+/// SHA-256 compressions needed to hash `len` bytes.
+fn blocks(len: usize) -> usize {
+    (len + 9).div_ceil(64)
+}
+
+/// Full re-hash per shot: one pass over the whole `serialize_for_commit` output.
+fn full_cost(n: usize) -> usize {
+    blocks(1 + 4 + 5 * 11 + 16 + n * n)
+}
+
+/// Merkle update per shot: check the old path and derive the new one
+/// (leaf plus one compression per level each), rehash the fleet, and
+/// combine fleet and grid roots.
+fn merkle_cost(n: usize) -> usize {
+    let depth = (n * n).next_power_of_two().trailing_zeros() as usize;
+    2 * (depth + 1) + blocks(1 + 4 + 5 * 11 + 16) + 1
+}
+
+fn main() {
+    let shots: Vec<Position> = (0..10).flat_map(|x| [Position::new(x, 0), Position::new(x, 9)]).collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut state = board();
+        for &shot in &shots {
+            state.apply_shot(shot);
+            std::hint::black_box(state.commit());
+        }
+    }
+    let full = start.elapsed() / ROUNDS;
+
+    // Proofs are host-side witness data; only the guest-side update is timed.
+    let mut witnesses = Vec::new();
+    let mut state = board();
+    for &shot in &shots {
+        witnesses.push((state.cell_proof(shot).unwrap(), state.grid_merkle_root()));
+        state.apply_shot(shot);
+    }
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut state = board();
+        for (&shot, (proof, root)) in shots.iter().zip(&witnesses) {
+            std::hint::black_box(state.apply_shot_merkle(shot, proof, root).expect("valid witness"));
+        }
+    }
+    let merkle = start.elapsed() / ROUNDS;
+
+    println!("20-shot game on {0}x{0} (tree depth {1}):", BOARD_SIZE, GRID_TREE_DEPTH);
+    println!("  full re-hash: {:?} per game, {} compressions per shot", full, full_cost(BOARD_SIZE));
+    println!("  merkle:       {:?} per game, {} compressions per shot", merkle, merkle_cost(BOARD_SIZE));
+
+    println!("\nboard  full  merkle");
+    for n in (8..=64).step_by(8) {
+        println!("{:>5}  {:>4}  {:>6}", n, full_cost(n), merkle_cost(n));
+    }
+    match (BOARD_SIZE..=256).find(|&n| merkle_cost(n) < full_cost(n)) {
+        Some(n) => println!("Merkle updates are cheaper from {0}x{0} boards upward.", n),
+        None => println!("Merkle updates are never cheaper up to 256x256."),
+    }
+}
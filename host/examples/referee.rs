@@ -0,0 +1,48 @@
+// Verification-only referee: checks both players' proofs for one turn and
+// reports the adjudicated result. Uses only `host::verify`, so it builds
+// without the `prove` feature and never links the prover.
+//
+// Usage:
+//    cargo run -p host --no-default-features --example referee -- <proof_a.json> <proof_b.json>
+//
+// Each file holds a JSON-serialized `ProofData` (as sent in `ShotResult`),
+// where proof A covers the shot fired at player A's board and proof B the
+// shot fired at player B's board.
+use anyhow::{bail, Context, Result};
+use core::{HitType, RoundCommit};
+use host::network_protocol::ProofData;
+use host::verify::verify_proofdata;
+
+fn load(path: &str) -> Result<ProofData> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+    serde_json::from_str(&json).with_context(|| format!("parsing ProofData from {}", path))
+}
+
+fn describe(commit: &RoundCommit) -> String {
+    match &commit.hit {
+        HitType::Miss => format!("miss at {},{}", commit.shot.x, commit.shot.y),
+        HitType::Hit => format!("hit at {},{}", commit.shot.x, commit.shot.y),
+        HitType::Sunk(st) => format!("sank {:?} at {},{}", st, commit.shot.x, commit.shot.y),
+        HitType::SunkHidden(_) => format!("sank a hidden ship at {},{}", commit.shot.x, commit.shot.y),
+    }
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        bail!("usage: {} <proof_a.json> <proof_b.json>", args[0]);
+    }
+    let a = load(&args[1])?;
+    let b = load(&args[2])?;
+
+    let ca = verify_proofdata(&a).context("player A's proof rejected")?;
+    let cb = verify_proofdata(&b).context("player B's proof rejected")?;
+    if ca.match_id != cb.match_id {
+        bail!("proofs belong to different matches ({} vs {})", ca.match_id, cb.match_id);
+    }
+
+    println!("Match {}: both proofs verified.", ca.match_id);
+    println!("  Player A's board: {} (seq {}, new commit {})", describe(&ca), ca.seq, ca.new_state);
+    println!("  Player B's board: {} (seq {}, new commit {})", describe(&cb), cb.seq, cb.new_state);
+    Ok(())
+}
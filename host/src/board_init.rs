@@ -1,16 +1,19 @@
 // Helpers for interactively initializing a player's board.
 //
-// This file provides a small, self-contained `PlayerBoard` type and
-// interactive helpers that prompt a human user (stdin) for placement of
-// the canonical five ships. It does not depend on the `core` crate so it
-// can be used independently; the types are intentionally similar so they
-// are easy to map to `GameState` if you later wire them together.
+// `prompt_place_ships` prompts a human user (stdin) for placement of the
+// canonical five ships and returns a `core::GameState`, the same type the
+// guest commits to, so boards built here can be committed and played
+// without conversion.
+//
+// Random placement draws from an RNG passed in by the caller; only the
+// binary creates a `thread_rng()`. `place_ships_from` runs the same prompts
+// over any input, so tests can script a session with a seeded `StdRng`.
 //
 // Usage (from a host binary):
-//    let board = board_init::prompt_place_ships("Player 1");
+//    let board = board_init::prompt_place_ships("Player 1", &mut rand::thread_rng());
 //
-use std::io::{self, Write};
-use rand::thread_rng;
+use std::io::{self, BufRead, Write};
+use rand::Rng;
 
 // Use the canonical `core` crate types so host code and guest code share the
 // same definitions and behavior.
@@ -21,8 +24,15 @@ use core::{GameState, ShipType, Direction, Position, BOARD_SIZE};
 /// This function mirrors the previous interactive helper but now uses the
 /// `core::GameState` as the authoritative structure. The returned
 /// `GameState` will have `ships` populated; the `grid` remains empty until
-/// shots are applied.
-pub fn prompt_place_ships(player_name: &str) -> GameState {
+/// shots are applied. Random placement draws from `rng`.
+pub fn prompt_place_ships<R: Rng + ?Sized>(player_name: &str, rng: &mut R) -> GameState {
+    place_ships_from(player_name, &mut io::stdin().lock(), rng)
+}
+
+/// `prompt_place_ships` reading answers from `input` instead of stdin. If
+/// `input` runs out before the fleet is complete, the ships placed so far
+/// are returned.
+pub fn place_ships_from<I: BufRead, R: Rng + ?Sized>(player_name: &str, input: &mut I, rng: &mut R) -> GameState {
     let mut state = GameState::new([0u8; 16]);
     println!("{}: place your ships on a {}x{} board.", player_name, BOARD_SIZE, BOARD_SIZE);
     println!("Coordinates are 0-based: x in [0..{}], y in [0..{}].", BOARD_SIZE-1, BOARD_SIZE-1);
@@ -36,14 +46,17 @@ pub fn prompt_place_ships(player_name: &str) -> GameState {
         print!("Choose placement mode: (M)anual or (R)andom?: ");
         io::stdout().flush().ok();
         let mut choice = String::new();
-        if io::stdin().read_line(&mut choice).is_err() {
-            println!("Failed to read input, try again.");
-            continue;
+        match input.read_line(&mut choice) {
+            Ok(0) => return state,
+            Ok(_) => {}
+            Err(_) => {
+                println!("Failed to read input, try again.");
+                continue;
+            }
         }
         let choice = choice.trim().to_uppercase();
         if choice == "R" || choice == "RANDOM" {
-            let mut rng = thread_rng();
-            if state.place_ships_randomly(&mut rng) {
+            if state.place_ships_randomly(rng) {
                 println!("Random placement complete:");
                 crate::visualize::display_board(&state, true);
                 return state;
@@ -59,16 +72,29 @@ pub fn prompt_place_ships(player_name: &str) -> GameState {
         }
     }
 
-    for &st in [ShipType::Carrier, ShipType::Battleship, ShipType::Cruiser, ShipType::Submarine, ShipType::Destroyer].iter() {
+    'ships: for &st in [ShipType::Carrier, ShipType::Battleship, ShipType::Cruiser, ShipType::Submarine, ShipType::Destroyer].iter() {
         loop {
-            print!("Place {} (size {}) as: x y H/V: ", format!("{:?}", st), st.size());
+            print!("Place {} (size {}) as: x y H/V (or R to fill the rest randomly): ", format!("{:?}", st), st.size());
             io::stdout().flush().ok();
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                println!("Failed to read input, try again.");
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                Ok(0) => return state,
+                Ok(_) => {}
+                Err(_) => {
+                    println!("Failed to read input, try again.");
+                    continue;
+                }
+            }
+            if line.trim().eq_ignore_ascii_case("R") {
+                if state.place_remaining_randomly(rng) {
+                    println!("Remaining ships placed randomly:");
+                    crate::visualize::display_board(&state, true);
+                    break 'ships;
+                }
+                println!("Could not fit the remaining ships around yours; keep placing manually.");
                 continue;
             }
-            let parts: Vec<_> = input.trim().split_whitespace().collect();
+            let parts: Vec<_> = line.trim().split_whitespace().collect();
             if parts.len() != 3 {
                 println!("Expected three tokens: x y H/V");
                 continue;
@@ -81,10 +107,9 @@ pub fn prompt_place_ships(player_name: &str) -> GameState {
                 Ok(v) => v,
                 Err(_) => { println!("Invalid y"); continue; }
             };
-            let dir = match parts[2].to_uppercase().as_str() {
-                "H" => Direction::Horizontal,
-                "V" => Direction::Vertical,
-                _ => { println!("Invalid direction, use H or V"); continue; }
+            let dir = match Direction::try_from(parts[2]) {
+                Ok(d) => d,
+                Err(e) => { println!("{}", e); continue; }
             };
             let pos = Position::new(x, y);
             if !state.can_place_ship(st, pos, dir) {
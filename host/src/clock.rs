@@ -0,0 +1,90 @@
+// Per-player thinking time.
+//
+// A `Clock` runs for at most one player at a time. Coordinators start it
+// for a player while waiting for that player's shot (stdin, a UI command,
+// or the opponent's next request on the network) and stop it before
+// proving, so totals measure deliberation rather than prover speed.
+use core::Player;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Monotonic time since an arbitrary fixed origin. Swappable so tests can
+/// drive a `Clock` deterministically.
+pub trait TimeSource: Send {
+    fn now(&self) -> Duration;
+}
+
+/// `TimeSource` backed by `std::time::Instant`.
+pub struct SystemTimeSource {
+    origin: Instant,
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Accumulated thinking time for both players.
+pub struct Clock {
+    source: Box<dyn TimeSource>,
+    /// Player currently on the clock and when they started.
+    running: Option<(Player, Duration)>,
+    totals: [Duration; 2],
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::with_source(Box::new(SystemTimeSource::default()))
+    }
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source(source: Box<dyn TimeSource>) -> Self {
+        Self { source, running: None, totals: [Duration::ZERO; 2] }
+    }
+
+    /// Put `player` on the clock. Does nothing if they already are; if the
+    /// other player was running, their time is banked first.
+    pub fn start(&mut self, player: Player) {
+        match self.running {
+            Some((p, _)) if p == player => {}
+            _ => {
+                self.stop();
+                self.running = Some((player, self.source.now()));
+            }
+        }
+    }
+
+    /// Bank the running player's time and stop the clock.
+    pub fn stop(&mut self) {
+        if let Some((player, since)) = self.running.take() {
+            self.totals[player.index()] += self.source.now().saturating_sub(since);
+        }
+    }
+
+    /// Time banked for `player`, plus the current stretch if they are running.
+    pub fn total(&self, player: Player) -> Duration {
+        let running = match self.running {
+            Some((p, since)) if p == player => self.source.now().saturating_sub(since),
+            _ => Duration::ZERO,
+        };
+        self.totals[player.index()] + running
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Thinking time: P1 {:.1}s, P2 {:.1}s", self.total(Player::One).as_secs_f64(), self.total(Player::Two).as_secs_f64())
+    }
+}
@@ -0,0 +1,96 @@
+// Setup check behind `host doctor`.
+//
+// A missing or mismatched RISC Zero toolchain otherwise only shows up
+// mid-game as "prover unavailable". `run` walks the same path a real turn
+// takes on a fixed one-shot board: backend selection, guest execution,
+// proving, then the shooter's checks on the receipt. It stops at the first
+// stage that fails and says what to fix. Only a receipt that verifies and
+// proves the expected shot counts as healthy.
+
+use crate::error::ProofError;
+use crate::proofs::{execute_only, produce_and_verify_proof_with, verify_shot_result_for_shooter, GuestInput, ProverBackend};
+use core::{Direction, DisclosureMode, FleetSpec, GameState, HitType, Position, ShipType, TurnState};
+use std::fmt;
+
+/// The step of `run` that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoctorStage {
+    /// The requested backend cannot be used by this build or configuration.
+    Backend,
+    /// The guest could not be executed, even without proving.
+    Execute,
+    /// Execution worked but proving (or local receipt verification) failed.
+    Prove,
+    /// A receipt was produced but does not prove the test shot.
+    Verify,
+}
+
+/// Why `run` failed: the stage, the underlying error and what to do
+/// about it.
+#[derive(Debug)]
+pub struct DoctorFailure {
+    pub stage: DoctorStage,
+    pub error: ProofError,
+}
+
+impl DoctorFailure {
+    /// A hint for fixing the setup, based on the stage and error.
+    pub fn guidance(&self) -> &'static str {
+        match (self.stage, &self.error) {
+            (DoctorStage::Backend, _) if cfg!(not(feature = "cuda")) => "Rebuild with `cargo build -p host --features cuda` for GPU proving (needs the CUDA toolkit), or set a non-empty Bonsai api_url and api_key.",
+            (DoctorStage::Backend, _) => "Set a non-empty Bonsai api_url and api_key, or use the local prover.",
+            (DoctorStage::Execute, ProofError::Prover { .. }) => "The guest did not run. Install the RISC Zero toolchain (`curl -L https://risczero.com/install | bash`, then `rzup install`) and rebuild so `methods` embeds the guest.",
+            (DoctorStage::Execute, _) => "The guest ran but its journal was not understood. Rebuild `methods` and `host` together so both use the same `core`.",
+            (DoctorStage::Prove, ProofError::ReceiptInvalid(_)) | (DoctorStage::Verify, _) => "The receipt does not match this build's METHOD_ID. Rebuild `methods` and `host` together, e.g. `cargo clean -p methods && cargo build`.",
+            (DoctorStage::Prove, _) => "Execution works but proving does not. Check that `r0vm` is on PATH and matches the risc0-zkvm version (`rzup install` updates it); with `--features cuda`, check the CUDA driver.",
+        }
+    }
+}
+
+impl fmt::Display for DoctorFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} stage failed: {}", self.stage, self.error)
+    }
+}
+
+impl std::error::Error for DoctorFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The process exit status for `host doctor`: 0 only if `result` is `Ok`.
+pub fn exit_code(result: &Result<(), DoctorFailure>) -> i32 {
+    if result.is_ok() { 0 } else { 1 }
+}
+
+/// Prove one shot on a fixed board with `backend`, reporting each stage
+/// that passes to `status`. Returns the first stage that fails.
+pub fn run(backend: &ProverBackend, status: &mut dyn FnMut(&str)) -> Result<(), DoctorFailure> {
+    let fail = |stage| move |error| DoctorFailure { stage, error };
+    backend.ensure_available().map_err(fail(DoctorStage::Backend))?;
+    status(&format!("backend {:?} is available", backend));
+
+    let input = test_input();
+    let shot = input.shots[0];
+    let commits = execute_only(&input).map_err(fail(DoctorStage::Execute))?;
+    if commits.last().map(|rc| rc.hit != HitType::Hit).unwrap_or(true) {
+        return Err(fail(DoctorStage::Execute)(ProofError::Rejected(format!("guest execution did not report a hit at {:?}", shot))));
+    }
+    status("guest executes");
+
+    let receipt = produce_and_verify_proof_with(&input, backend).map_err(fail(DoctorStage::Prove))?;
+    status("proof produced and verified against METHOD_ID");
+
+    verify_shot_result_for_shooter(&receipt, input.initial.commit(), shot, Some(input.match_id), Some(input.seq), None).map_err(fail(DoctorStage::Verify))?;
+    status("receipt proves the test shot");
+    Ok(())
+}
+
+/// A full classic fleet and one shot that hits the Carrier.
+fn test_input() -> GuestInput {
+    let mut board = GameState::new([0; 16]);
+    let placed = board.place_ships(ShipType::ALL.iter().enumerate().map(|(i, &st)| (st, Position::new(0, 2 * i as u32), Direction::Horizontal)).collect());
+    assert!(placed, "the doctor board is a valid layout");
+    GuestInput { initial: board, shots: vec![Position::new(0, 0)], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: TurnState::default(), fleet: FleetSpec::classic(), disclosure: DisclosureMode::Open }
+}
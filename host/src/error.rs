@@ -0,0 +1,148 @@
+// Typed errors for the host library's public API.
+//
+// `NetworkConnection` messaging (framing, envelopes, handshakes) returns
+// `NetworkError`, and proving and verification (`crate::proofs`,
+// `crate::verify`) return `ProofError`, so embedders can `match` on the
+// failure and decide how to recover. Both convert into `anyhow::Error`
+// with `?`, which the binary and the game loops use internally. TLS and
+// socket setup (`NetworkConnection::host`, `connect`) still return
+// `anyhow::Result`: those failures are configuration problems to report,
+// not conditions to recover from.
+//
+// The `Display`/`Error` impls are written out by hand: derive macros such
+// as thiserror expand to `::core::fmt` paths, which resolve to our own
+// `core` crate in this workspace.
+use core::{FleetSpec, ShotError};
+use std::fmt;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Closed,
+    FrameTooLarge { len: usize, max: usize },
+    /// The envelope's HMAC does not match the match secret (forged,
+    /// tampered with, or from a peer bound to different boards).
+    AuthTokenInvalid,
+    MatchIdMismatch { expected: Uuid, got: Uuid },
+    UnexpectedSeq { expected: u64, got: u64 },
+    RateLimited { max_messages: u32, window: Duration },
+    /// The peer's `CoinReveal` nonce does not open the `coin_commitment`
+    /// from its `BoardReady`.
+    CoinRevealMismatch,
+    /// The peer's `BoardReady` declares other board parameters than ours.
+    FleetMismatch { ours: Box<FleetSpec>, theirs: Box<FleetSpec> },
+    /// Heartbeats are on and nothing, not even a ping, arrived from the
+    /// peer within the timeout; treat the connection as dropped.
+    PeerTimedOut { silent_for: Duration },
+    /// An incoming frame that is not a valid envelope.
+    Malformed(String),
+    Encode(String),
+    /// A well-formed envelope that is not allowed at this point, e.g. no
+    /// `BoardReady` during the handshake or a bad proof chunk.
+    Protocol(String),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Closed => f.write_str("connection closed by peer (EOF)"),
+            NetworkError::FrameTooLarge { len, max } => write!(f, "incoming frame of {} bytes exceeds limit of {} bytes", len, max),
+            NetworkError::AuthTokenInvalid => f.write_str("auth token missing or invalid"),
+            NetworkError::MatchIdMismatch { expected, got } => write!(f, "mismatched match_id: expected {} got {}", expected, got),
+            NetworkError::UnexpectedSeq { expected, got } => write!(f, "unexpected sequence number: expected {} got {}", expected, got),
+            NetworkError::RateLimited { max_messages, window } => write!(f, "rate limit exceeded: more than {} messages in {:?}", max_messages, window),
+            NetworkError::CoinRevealMismatch => f.write_str("peer's coin reveal does not match its commitment"),
+            NetworkError::FleetMismatch { ours, theirs } if (ours.dims, &ours.ships) == (theirs.dims, &theirs.ships) => write!(f, "peer plays the same board under other rules: {:?}, but we play {:?}", theirs, ours),
+            NetworkError::FleetMismatch { ours, theirs } => write!(f, "peer plays a {}x{} board with {:?}, but we play {}x{} with {:?}", theirs.dims.width, theirs.dims.height, theirs.ships, ours.dims.width, ours.dims.height, ours.ships),
+            NetworkError::PeerTimedOut { silent_for } => write!(f, "peer stopped responding: nothing received for {:?}", silent_for),
+            NetworkError::Malformed(msg) => write!(f, "failed to parse incoming envelope: {}", msg),
+            NetworkError::Encode(msg) => write!(f, "failed to encode outgoing message: {}", msg),
+            NetworkError::Protocol(msg) => write!(f, "protocol violation: {}", msg),
+            NetworkError::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetworkError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for NetworkError {
+    fn from(e: std::io::Error) -> Self {
+        NetworkError::Io(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProofError {
+    /// The requested prover backend cannot be used by this build or
+    /// configuration; nothing was proven.
+    BackendUnavailable(String),
+    /// The guest would reject this input (see
+    /// `crate::proofs::validate_guest_input`); nothing was proven.
+    InvalidInput(String),
+    Cancelled,
+    /// The prover ran but failed, e.g. r0vm is missing or the guest
+    /// rejected the input.
+    Prover { backend: String, source: Box<dyn std::error::Error + Send + Sync> },
+    /// The receipt does not verify against `METHOD_ID`.
+    ReceiptInvalid(String),
+    MalformedJournal(String),
+    /// The proof verifies but starts from a different commitment than ours.
+    BaseStateMismatch,
+    /// The proof verifies but is not bound to the expected match and sequence.
+    NotBound,
+    /// The proof verifies but its commits do not describe the expected shots.
+    Rejected(String),
+    /// The proof was made for other board parameters than the agreed
+    /// `FleetSpec` (see `crate::verify::check_fleet`).
+    FleetMismatch,
+    /// The proof was made for other ships than the placement commitment
+    /// the defender announced (see `crate::verify::check_placement`).
+    PlacementMismatch,
+    /// The proof was made for another turn than the shooter tracked, or
+    /// for shots fired by someone else.
+    TurnMismatch,
+    /// The proof verifies but the defender's guest rejected the shot, so
+    /// its `Miss` is not a real result.
+    InvalidShot(ShotError),
+    /// The proof data carries a layout version this build cannot read
+    /// (see `crate::network_protocol::PROOF_DATA_VERSION`).
+    UnsupportedVersion { found: u16, supported: u16 },
+    Encoding(String),
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::BackendUnavailable(msg) | ProofError::MalformedJournal(msg) | ProofError::Rejected(msg) | ProofError::Encoding(msg) => f.write_str(msg),
+            ProofError::InvalidInput(msg) => write!(f, "invalid guest input: {}", msg),
+            ProofError::Cancelled => f.write_str("proving cancelled"),
+            ProofError::Prover { backend, source } => write!(f, "prover failed ({} backend): {}", backend, source),
+            ProofError::ReceiptInvalid(msg) => write!(f, "receipt verification failed: {}", msg),
+            ProofError::BaseStateMismatch => f.write_str("mismatched base state in provided proof"),
+            ProofError::NotBound => f.write_str("receipt proof not bound to expected match_id/seq"),
+            ProofError::FleetMismatch => f.write_str("proof was made for a different board, fleet or rules than agreed"),
+            ProofError::PlacementMismatch => f.write_str("proof was made for a different ship placement than the opponent committed to"),
+            ProofError::TurnMismatch => f.write_str("proof was made for a different turn than ours"),
+            ProofError::InvalidShot(e) => write!(f, "proof reports the shot as invalid: {}", e),
+            ProofError::UnsupportedVersion { found, supported } => write!(f, "unsupported proof version {} (this build reads version {})", found, supported),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProofError::Prover { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -2,33 +2,165 @@
 // game_coordinator responsibilities into a single module to reduce
 // fragmentation and simplify imports.
 use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
 use crate::board_init::prompt_place_ships;
+use crate::clock::Clock;
+use crate::error::ProofError;
 use crate::visualize::{display_board, display_dual};
-use core::{GameState, Position, HitType, CellState};
+use core::{evaluate_outcome, DisclosureMode, FleetSpec, GameOutcome, GameReport, GameState, Player, Position, HitType, CellState, RoundCommit, ShotError, TurnMode, TurnRules, TurnState};
 use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
 use crate::network::NetworkConnection;
-use crate::network_protocol::GameMessage;
-use crate::proofs::{GuestInput, produce_and_verify_proof, extract_round_commits, proofdata_from_receipt, receipt_from_proofdata, verify_remote_round_proof, verify_shot_result_for_shooter};
+use crate::network_protocol::{GameMessage, ProofData, SetupProof};
+use crate::ui::{UiCommand, UiEvent};
+use crate::proofs::{GuestInput, check_fleet, check_placement, validate_guest_input, produce_and_verify_proof, extract_round_commits, proofdata_from_receipt, receipt_from_proofdata, verify_remote_round_proof, verify_shot_result_for_shooter};
+
+/// Parse a salvo entered as `x1 y1 x2 y2 ...`, requiring exactly `count`
+/// coordinate pairs. Bounds and repeat checks are left to the caller.
+fn parse_salvo(input: &str, count: usize) -> Option<Vec<Position>> {
+    let nums: Vec<u32> = input.split_whitespace().map(|t| t.parse().ok()).collect::<Option<_>>()?;
+    if nums.len() != count * 2 {
+        return None;
+    }
+    Some(nums.chunks(2).map(|p| Position::new(p[0], p[1])).collect())
+}
+
+/// One-line summary of a salvo's results, listing any sunk ships.
+fn salvo_summary(results: &[HitType]) -> String {
+    let hits = results.iter().filter(|h| **h != HitType::Miss).count();
+    let sunk: Vec<_> = results.iter().filter_map(|h| if let HitType::Sunk(st) = h { Some(*st) } else { None }).collect();
+    format!("Salvo (verified): {} hit(s), {} miss(es); sunk: {:?}", hits, results.len() - hits, sunk)
+}
+
+/// Where `GameCoordinator` gets the local player's shots and sends its
+/// progress: the terminal for `play_game`, channels for `play_game_channels`.
+trait PlayerIo {
+    fn log(&mut self, msg: &str);
+    fn show_boards(&mut self, local: &GameState, opponent_view: &GameState);
+    /// One of our shots was verified. The terminal already logs the result.
+    fn shot_resolved(&mut self, _pos: Position, _result: &HitType) {}
+    /// Next single shot. Bounds and repeats are checked by the caller.
+    fn next_shot(&mut self) -> Result<Position>;
+    /// Next salvo of exactly `count` shots. Bounds and repeats are checked by the caller.
+    fn next_salvo(&mut self, count: usize) -> Result<Vec<Position>>;
+}
+
+struct StdioIo;
+
+impl PlayerIo for StdioIo {
+    fn log(&mut self, msg: &str) {
+        println!("{}", msg);
+    }
+
+    fn show_boards(&mut self, local: &GameState, opponent_view: &GameState) {
+        display_dual(local, opponent_view, true);
+    }
+
+    fn next_shot(&mut self) -> Result<Position> {
+        loop {
+            println!("Your turn. Enter shot as 'x y':");
+            print!("> "); io::stdout().flush().ok();
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            let parts: Vec<_> = line.split_whitespace().collect();
+            if parts.len() != 2 { println!("Invalid input"); continue; }
+            // Unparseable coordinates become out-of-bounds and are rejected by the caller
+            let x: u32 = parts[0].parse().unwrap_or(999);
+            let y: u32 = parts[1].parse().unwrap_or(999);
+            return Ok(Position::new(x, y));
+        }
+    }
+
+    fn next_salvo(&mut self, count: usize) -> Result<Vec<Position>> {
+        loop {
+            println!("Your salvo. Enter {} shots as 'x1 y1 x2 y2 ...':", count);
+            print!("> "); io::stdout().flush().ok();
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            match parse_salvo(&line, count) {
+                Some(p) => return Ok(p),
+                None => println!("Please enter exactly {} coordinate pairs.", count),
+            }
+        }
+    }
+}
+
+/// `PlayerIo` over the GUI channels. Events are best-effort: a GUI that has
+/// gone away only matters once the game needs its next command.
+struct ChannelIo {
+    rx: Receiver<UiCommand>,
+    tx: Sender<UiEvent>,
+}
+
+impl ChannelIo {
+    fn recv(&mut self) -> Result<UiCommand> {
+        self.rx.recv().map_err(|_| anyhow::anyhow!("UI command channel closed"))
+    }
+}
+
+impl PlayerIo for ChannelIo {
+    fn log(&mut self, msg: &str) {
+        let _ = self.tx.send(UiEvent::Log(msg.to_string()));
+    }
+
+    fn show_boards(&mut self, local: &GameState, opponent_view: &GameState) {
+        let _ = self.tx.send(UiEvent::Boards { local: Box::new(local.clone()), opponent_view: Box::new(opponent_view.clone()) });
+    }
+
+    fn shot_resolved(&mut self, pos: Position, result: &HitType) {
+        let _ = self.tx.send(UiEvent::ShotResolved { pos, result: result.clone() });
+    }
+
+    fn next_shot(&mut self) -> Result<Position> {
+        let _ = self.tx.send(UiEvent::AwaitingShots { count: 1 });
+        loop {
+            match self.recv()? {
+                UiCommand::Shoot(pos) => return Ok(pos),
+                UiCommand::Salvo(positions) if positions.len() == 1 => return Ok(positions[0]),
+                UiCommand::Salvo(_) => self.log("Expected a single shot."),
+            }
+        }
+    }
+
+    fn next_salvo(&mut self, count: usize) -> Result<Vec<Position>> {
+        let _ = self.tx.send(UiEvent::AwaitingShots { count });
+        loop {
+            match self.recv()? {
+                UiCommand::Salvo(positions) if positions.len() == count => return Ok(positions),
+                UiCommand::Shoot(pos) if count == 1 => return Ok(vec![pos]),
+                _ => self.log(&format!("Expected a salvo of exactly {} shots.", count)),
+            }
+        }
+    }
+}
 
 /// Run the full interactive game implementing the requested turn rules.
-pub fn run_game_master_interactive() {
-    println!("=== Battleship: Game Master ===");
+/// In `TurnMode::Salvo` each turn is a single salvo proven in one receipt;
+/// in `TurnMode::Classic`, `rules` decides whether a hit or a sinking
+/// earns another shot. Random placement draws from `rng`.
+pub fn run_game_master_interactive<R: Rng + ?Sized>(mode: TurnMode, rules: TurnRules, rng: &mut R) {
+    println!("=== Battleship: Game Master ({:?}) ===", mode);
 
     println!("Player 1: place your ships");
-    let mut p1: GameState = prompt_place_ships("Player 1");
+    let mut p1: GameState = prompt_place_ships("Player 1", rng);
 
     println!("Player 2: place your ships");
-    let mut p2: GameState = prompt_place_ships("Player 2");
+    let mut p2: GameState = prompt_place_ships("Player 2", rng);
 
-    // 0 -> player1, 1 -> player2
-    let mut turn: usize = 0;
+    let mut turn = Player::One;
+    let mut clock = Clock::new();
 
     loop {
-        let (active_name, (active, opponent)) = if turn == 0 {
-            ("Player 1", (&mut p1, &mut p2))
-        } else {
-            ("Player 2", (&mut p2, &mut p1))
+        let shooter = turn;
+        let (active_name, (active, opponent)) = match turn {
+            Player::One => ("Player 1", (&mut p1, &mut p2)),
+            Player::Two => ("Player 2", (&mut p2, &mut p1)),
         };
 
         println!("\n--- {}'s turn ---", active_name);
@@ -36,8 +168,57 @@ pub fn run_game_master_interactive() {
         display_board(active, true);
         display_board(opponent, false);
 
+        if mode == TurnMode::Salvo {
+            let count = mode.shots_per_turn(active);
+            loop {
+                clock.start(shooter);
+                print!("{active_name}, enter {count} shots as 'x1 y1 x2 y2 ...': ");
+                io::stdout().flush().ok();
+                let mut input = String::new();
+                if io::stdin().read_line(&mut input).is_err() {
+                    println!("Failed to read input, try again.");
+                    continue;
+                }
+                let shots = match parse_salvo(&input, count) {
+                    Some(s) => s,
+                    None => { println!("Please enter exactly {count} coordinate pairs."); continue; }
+                };
+                // Dry-run on a copy to reject OOB, repeated, or duplicate shots before proving.
+                if opponent.clone().apply_salvo(&shots).is_none() {
+                    println!("Salvo contains an out-of-bounds, repeated, or duplicate shot.");
+                    continue;
+                }
+                clock.stop();
+                let receipt = match produce_and_verify_proof(&GuestInput { initial: opponent.clone(), shots: shots.clone(), match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: TurnState { to_move: shooter, streak: 0 }, fleet: FleetSpec { turn_rules: rules, ..FleetSpec::classic() }, disclosure: DisclosureMode::Open }) {
+                    Ok(r) => r,
+                    Err(e) => { println!("Failed to produce/verify proof locally: {e}"); println!("Rejecting salvo."); continue; }
+                };
+                let commits = match extract_round_commits(&receipt).and_then(|c| {
+                    crate::proofs::check_salvo_commits(&c, opponent.commit(), &shots, None, None).map(|_| c)
+                }) {
+                    Ok(c) => c,
+                    Err(e) => { println!("Proof verification failed: {e}"); println!("Rejecting salvo."); continue; }
+                };
+                let results = opponent.apply_salvo(&shots).expect("salvo validated above");
+                let proven: Vec<HitType> = commits.into_iter().map(|c| c.hit).collect();
+                if proven != results {
+                    println!("Warning: proven salvo results differ from local application.");
+                }
+                println!("{}", salvo_summary(&results));
+                break;
+            }
+            if opponent.all_ships_sunk() {
+                println!("All opponent ships sunk! {} wins!", active_name);
+                println!("{}", clock);
+                return;
+            }
+            turn = turn.other();
+            continue;
+        }
+
         // Player may take one or more shots depending on Hit vs Miss vs Sunk rules.
             loop {
+            clock.start(shooter);
             print!("{active_name}, enter shot as 'x y' (or 'show' to display both boards): ");
             io::stdout().flush().ok();
             let mut input = String::new();
@@ -79,11 +260,12 @@ pub fn run_game_master_interactive() {
             // Instead of applying the shot directly, produce a per-round proof
             // using the guest and verify the produced RoundCommit matches the
             // server's authoritative application of the shot.
+                clock.stop();
 
-                match produce_and_verify_proof(&GuestInput { initial: opponent.clone(), shots: vec![pos], match_id: uuid::Uuid::nil(), seq: 0 }) {
+                match produce_and_verify_proof(&GuestInput { initial: opponent.clone(), shots: vec![pos], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![shooter], turn: TurnState { to_move: shooter, streak: 0 }, fleet: FleetSpec { turn_rules: rules, ..FleetSpec::classic() }, disclosure: DisclosureMode::Open }) {
                 Ok(receipt) => {
                     // Verify and validate the round's commit against authoritative state
-                        match verify_remote_round_proof(&receipt, opponent, pos, None, None) {
+                        match verify_remote_round_proof(&receipt, opponent.commit(), pos, None, None) {
                         Ok(commits) => {
                             // commits last element corresponds to the shot result we just proved
                             let rc = commits.last().unwrap();
@@ -92,28 +274,30 @@ pub fn run_game_master_interactive() {
                                     println!("Miss (verified).");
                                     // update opponent state using the commit we verified
                                     let _ = opponent.apply_shot(pos);
-                                    turn = 1 - turn;
+                                    turn = rules.next_state(shooter, &rc.hit).next_player;
                                     break;
                                 }
-                                HitType::Hit => {
-                                    println!("Hit (verified)! You get another shot.");
-                                    let _ = opponent.apply_shot(pos);
-                                    if opponent.ships.iter().all(|s| s.is_sunk()) {
-                                        println!("All opponent ships sunk! {} wins!", active_name);
-                                        return;
+                                HitType::Hit | HitType::Sunk(_) | HitType::SunkHidden(_) => {
+                                    let transition = rules.next_state(shooter, &rc.hit);
+                                    let keeps = transition.extra_shot;
+                                    let next = if keeps { "You get another shot." } else { "Turn passes." };
+                                    match &rc.hit {
+                                        HitType::Sunk(st) => println!("Sunk {:?} (verified). {}", st, next),
+                                        HitType::SunkHidden(_) => println!("Sunk a ship, type hidden until the end (verified). {}", next),
+                                        _ => println!("Hit (verified)! {}", next),
                                     }
-                                    display_board(active, true);
-                                    display_board(opponent, false);
-                                    continue;
-                                }
-                                HitType::Sunk(st) => {
-                                    println!("Sunk {:?} (verified). Turn passes.", st);
                                     let _ = opponent.apply_shot(pos);
                                     if opponent.ships.iter().all(|s| s.is_sunk()) {
                                         println!("All opponent ships sunk! {} wins!", active_name);
+                                        println!("{}", clock);
                                         return;
                                     }
-                                    turn = 1 - turn;
+                                    if keeps {
+                                        display_board(active, true);
+                                        display_board(opponent, false);
+                                        continue;
+                                    }
+                                    turn = transition.next_player;
                                     break;
                                 }
                             }
@@ -137,11 +321,20 @@ pub fn run_game_master_interactive() {
 
 /// Non-interactive quick demo that follows the same turn rules but uses
 /// deterministic placements and a scripted series of shots (for testing).
-pub fn run_game_master_demo() {
+/// Running out of scripted shots counts as reaching the move cap, so the
+/// returned outcome is `Draw` unless a fleet was sunk first.
+pub fn run_game_master_demo(rules: TurnRules) -> GameOutcome {
+    run_game_master_report(rules).outcome
+}
+
+/// `run_game_master_demo`, also returning each player's shot statistics.
+pub fn run_game_master_report(rules: TurnRules) -> GameReport {
     use core::{ShipType, Direction};
     // Setup demo players
     let mut p1 = GameState::new([0; 16]);
     let mut p2 = GameState::new([0; 16]);
+    p1.enable_history();
+    p2.enable_history();
 
     // deterministic placements
     p1.place_ship(ShipType::Carrier, Position::new(0,0), Direction::Horizontal);
@@ -157,36 +350,52 @@ pub fn run_game_master_demo() {
     p2.place_ship(ShipType::Destroyer, Position::new(8,0), Direction::Vertical);
 
     // Scripted play: P1 shoots and misses, P2 hits, etc. We exercise rules.
-    let mut turn = 0usize;
+    let mut turn = Player::One;
     let shots = vec![
         // (player, x,y)
-        (0, 9, 9), // P1 miss
-        (1, 0, 0), // P2 hit (continues)
-        (1, 0, 1), // P2 hit (continues)
-        (1, 0, 2), // P2 hit (continues)
+        (Player::One, 9, 9), // P1 miss
+        (Player::Two, 0, 0), // P2 hits P1's carrier and, under the default rules, shoots again
+        (Player::Two, 0, 1), // P2 misses: the turn passes
+        (Player::One, 0, 2), // P1 hits P2's vertical carrier
         // ... continue until demo ends
     ];
 
     let mut idx = 0usize;
+    let mut outcome = GameOutcome::InProgress;
     while idx < shots.len() {
         let (_p, x, y) = shots[idx];
-        let (_active, opponent, active_name) = if turn == 0 { (&mut p1, &mut p2, "P1") } else { (&mut p2, &mut p1, "P2") };
+        let (_active, opponent, active_name) = match turn { Player::One => (&mut p1, &mut p2, "P1"), Player::Two => (&mut p2, &mut p1, "P2") };
         println!("{} shoots at {},{}", active_name, x, y);
         let pos = Position::new(x, y);
         if let Some(hit_type) = opponent.apply_shot(pos) {
             match hit_type {
-                HitType::Miss => { println!("Miss."); turn = 1 - turn; idx += 1; }
-                HitType::Hit => { println!("Hit! {} shoots again.", active_name); idx += 1; }
-                HitType::Sunk(st) => { println!("Sunk {:?}. Turn passes.", st); turn = 1 - turn; idx += 1; }
+                HitType::Miss => println!("Miss."),
+                HitType::Hit => println!("Hit!"),
+                HitType::Sunk(st) => println!("Sunk {:?}.", st),
+                HitType::SunkHidden(_) => println!("Sunk a ship."),
             }
+            let transition = rules.next_state(turn, &hit_type);
+            if transition.extra_shot {
+                println!("{} shoots again.", active_name);
+            }
+            turn = transition.next_player;
+            idx += 1;
         } else {
             println!("Invalid shot (OOB or already shot). Skipping."); idx += 1; }
 
-        if opponent.ships.iter().all(|s| s.is_sunk()) {
-            println!("{} wins!", active_name);
+        // The script length acts as the move cap.
+        outcome = evaluate_outcome(&p1, &p2, idx >= shots.len());
+        if outcome != GameOutcome::InProgress {
             break;
         }
     }
+    match outcome {
+        GameOutcome::Winner(Player::One) => println!("P1 wins!"),
+        GameOutcome::Winner(Player::Two) => println!("P2 wins!"),
+        GameOutcome::Draw => println!("Script exhausted with both fleets afloat: draw."),
+        GameOutcome::InProgress => {}
+    }
+    GameReport::from_boards(&p1, &p2, &rules, idx >= shots.len())
 }
 
 /// Networked game coordinator (previously GameCoordinator). Manages a
@@ -196,64 +405,417 @@ pub struct GameCoordinator {
     pub local_commit: Digest,
     pub network: NetworkConnection,
     pub player_name: String,
+    /// Whether we run the host side of the `BoardReady` handshake.
+    pub is_host: bool,
+    /// Whether we shoot first. `handshake` derives it from the coin flip
+    /// (`NetworkConnection::flip_first_player`); until then it equals
+    /// `is_host`.
     pub starts_first: bool,
     pub opponent_name: Option<String>,
+    /// Opponent's current full-state commitment; starts at the `state_commitment`
+    /// from their `BoardReady` and advances with each verified proof.
     pub opponent_commit: Option<Digest>,
+    /// Opponent's placement commitment (`GameState::hash_ships_only`), fixed for the match.
+    pub opponent_placement: Option<Digest>,
     /// Local tracking view of the opponent's board (only grid updated with hits/misses)
     pub opponent_view: GameState,
+    /// Shots-per-turn rule; both peers must be configured with the same mode.
+    pub turn_mode: TurnMode,
+    /// How our proofs disclose the type of a ship the opponent sinks.
+    pub disclosure: DisclosureMode,
+    /// Thinking time per seat: ours while waiting for local input, the
+    /// opponent's while waiting for their next request.
+    pub clock: Clock,
+    /// `(match_id, seq, shot)` of every verified result adopted by
+    /// `apply_verified_result`, so a replayed result is never applied twice.
+    pub applied_shots: HashSet<(uuid::Uuid, u64, Position)>,
+    /// Whose shot is next and their current hit streak. `play_game`
+    /// continues from here, so restoring a saved value (see
+    /// `resume_turn_from`) after reconnecting resumes mid-streak.
+    pub turn: TurnState,
+    /// If set, our seat and `turn` are saved here with `save_turn_in`
+    /// every time `turn` changes during `play_game`.
+    pub session_dir: Option<PathBuf>,
+    /// How many invalid shot requests (out of bounds, repeated, or an
+    /// illegal salvo) the opponent may send before we declare a
+    /// `Forfeit` and win. `None`, the default, refuses them with an
+    /// `Error` forever.
+    pub invalid_shot_limit: Option<u32>,
+    /// Invalid shot requests received from the opponent this match.
+    pub invalid_shots: u32,
+    /// Prove our board legal during `handshake` and send the proof in
+    /// `BoardReady` (see `crate::proofs::prove_board_setup`). Off by
+    /// default, as it costs a prover run before play.
+    pub send_setup_proof: bool,
+    /// Fail `handshake` unless the opponent's `BoardReady` carries a setup
+    /// proof. One that is sent is always checked.
+    pub require_setup_proof: bool,
+}
+
+/// How a networked game ended: the outcome from the local player's seat and
+/// each player's thinking time on `GameCoordinator::clock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchResult {
+    pub outcome: GameOutcome,
+    pub p1_time: Duration,
+    pub p2_time: Duration,
+}
+
+/// The part of a match's session that `GameCoordinator::turn` cannot be
+/// rebuilt from: our seat (a reconnect flips a new coin) and the turn
+/// state, including any extra shots owed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedTurn {
+    pub local_player: Player,
+    pub turn: TurnState,
+}
+
+/// Save `saved` for `match_id` as `<match_id>.turn` (JSON) in `dir`, which
+/// may be `crate::proofs::RECEIPTS_DIR` so it sits next to the match's
+/// receipt log. Replaces any earlier save.
+pub fn save_turn_in(dir: &Path, match_id: uuid::Uuid, saved: &SavedTurn) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.turn", match_id));
+    std::fs::write(&path, serde_json::to_string(saved)?).map_err(|e| anyhow::anyhow!("writing {}: {}", path.display(), e))
+}
+
+/// What `save_turn_in` saved for `match_id` in `dir`, or `None` if nothing was.
+pub fn load_turn_in(dir: &Path, match_id: uuid::Uuid) -> Result<Option<SavedTurn>> {
+    let path = dir.join(format!("{}.turn", match_id));
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow::anyhow!("reading {}: {}", path.display(), e)),
+    }
 }
 
 impl GameCoordinator {
-    pub fn new(local_state: GameState, local_commit: Digest, network: NetworkConnection, player_name: String, starts_first: bool) -> Self {
-        Self { local_state, local_commit, network, player_name, starts_first, opponent_name: None, opponent_commit: None, opponent_view: GameState::new([0;16]) }
+    pub fn new(local_state: GameState, local_commit: Digest, network: NetworkConnection, player_name: String, is_host: bool) -> Self {
+        Self { local_state, local_commit, network, player_name, is_host, starts_first: is_host, opponent_name: None, opponent_commit: None, opponent_placement: None, opponent_view: GameState::new([0;16]), turn_mode: TurnMode::Classic, disclosure: DisclosureMode::Open, clock: Clock::new(), applied_shots: HashSet::new(), turn: TurnState::default(), session_dir: None, invalid_shot_limit: None, invalid_shots: 0, send_setup_proof: false, require_setup_proof: false }
+    }
+
+    /// Send `text` to the opponent as a `Chat` from `player_name`.
+    pub fn send_chat(&mut self, text: &str) -> Result<()> {
+        Ok(self.network.send_chat(&self.player_name, text)?)
     }
 
-    /// Perform handshake: exchange BoardReady messages and record opponent info.
+    /// Perform handshake: exchange BoardReady messages and record opponent info,
+    /// then flip the coin that decides `starts_first`.
+    /// Fails without sending anything if the local board is not fully placed,
+    /// so an incomplete board is never committed to.
     pub fn handshake(&mut self) -> Result<()> {
-        if self.starts_first {
+        if !self.local_state.is_placement_complete(self.network.fleet_spec()) {
+            anyhow::bail!("local board placement is incomplete; place all ships before starting the match");
+        }
+        match self.network.peer_fingerprint() {
+            Some(fp) => println!("Opponent TLS certificate SHA-256: {}", fp),
+            None => println!("Opponent presented no TLS certificate"),
+        }
+        let proof = if self.send_setup_proof {
+            println!("Proving that our board is legal...");
+            Some(crate::proofs::prove_board_setup(&self.local_state, self.network.fleet_spec())?)
+        } else {
+            None
+        };
+        if self.is_host {
             // As host: send our BoardReady then receive opponent's
-            let (opp_name, opp_commit, _opp_proof) = self.network.handshake_as_host(&self.player_name, self.local_commit, None)?;
+            let (opp_name, opp_placement, opp_commit, opp_proof) = self.network.handshake_as_host(&self.player_name, self.local_state.hash_ships_only(), self.local_commit, proof)?;
+            self.check_setup_proof(opp_proof.as_ref(), opp_placement, opp_commit)?;
             self.opponent_name = Some(opp_name);
+            self.opponent_placement = Some(opp_placement);
             self.opponent_commit = Some(opp_commit);
         } else {
             // As client: receive host BoardReady then send ours
-            let (host_name, host_commit, _host_proof) = self.network.handshake_as_client(&self.player_name, self.local_commit, None)?;
+            let (host_name, host_placement, host_commit, host_proof) = self.network.handshake_as_client(&self.player_name, self.local_state.hash_ships_only(), self.local_commit, proof)?;
+            self.check_setup_proof(host_proof.as_ref(), host_placement, host_commit)?;
             self.opponent_name = Some(host_name);
+            self.opponent_placement = Some(host_placement);
             self.opponent_commit = Some(host_commit);
         }
+        self.starts_first = self.network.flip_first_player()? == self.is_host;
+        println!("{}", if self.starts_first { "Coin flip: you shoot first." } else { "Coin flip: opponent shoots first." });
     println!("Handshake complete with opponent: {}", self.opponent_name.as_deref().unwrap_or("Unknown"));
         Ok(())
     }
 
-    /// Play the networked game loop. This function blocks until the game ends.
-    pub fn play_game(&mut self) -> Result<()> {
-        // Turn: true means local player's turn, false means opponent's turn
-        let mut local_turn = self.starts_first;
+    /// Check the setup proof from the opponent's `BoardReady` against the
+    /// `placement` and `state_commitment` it came with, or its absence
+    /// against `require_setup_proof`.
+    fn check_setup_proof(&self, proof: Option<&SetupProof>, placement: Digest, state_commitment: Digest) -> Result<()> {
+        match proof {
+            Some(sp) => {
+                crate::verify::verify_setup_proof(sp, placement, state_commitment, self.network.fleet_spec()).map_err(|e| anyhow::anyhow!("opponent's setup proof rejected: {}", e))?;
+                println!("Opponent proved their board legal.");
+            }
+            None if self.require_setup_proof => anyhow::bail!("opponent sent no setup proof, but one is required"),
+            None => {}
+        }
+        Ok(())
+    }
 
+    /// Begin the next match on the same connection, after
+    /// `NetworkConnection::request_rematch` (or `accept_rematch`) succeeded:
+    /// adopt the freshly placed `local_state`, forget everything learned
+    /// about the opponent's previous board, and run `handshake` again.
+    /// Turn mode and rules carry over.
+    pub fn start_rematch(&mut self, local_state: GameState) -> Result<()> {
+        self.local_commit = local_state.commit();
+        self.local_state = local_state;
+        self.starts_first = self.is_host;
+        self.opponent_name = None;
+        self.opponent_commit = None;
+        self.opponent_placement = None;
+        self.opponent_view = GameState::new([0;16]);
+        self.clock = Clock::new();
+        self.applied_shots.clear();
+        self.turn = TurnState::default();
+        self.invalid_shots = 0;
+        self.handshake()
+    }
+
+    /// After reconnecting and running `handshake`, continue the match
+    /// `match_id` from what `save_turn_in` left in `dir`: our old seat,
+    /// whose shot is next and any streak in progress. Both peers must do
+    /// this. Returns false, changing nothing, if nothing was saved.
+    pub fn resume_turn_from(&mut self, dir: &Path, match_id: uuid::Uuid) -> Result<bool> {
+        let saved = match load_turn_in(dir, match_id)? {
+            Some(s) => s,
+            None => return Ok(false),
+        };
+        self.starts_first = saved.local_player == Player::One;
+        self.turn = saved.turn;
+        Ok(true)
+    }
+
+    /// Seat of the local player: whoever starts first is Player One.
+    pub fn local_player(&self) -> Player {
+        if self.starts_first { Player::One } else { Player::Two }
+    }
+
+    fn opponent_player(&self) -> Player {
+        self.local_player().other()
+    }
+
+    /// Prove `input` and package the last commit for the peer. An input
+    /// `validate_guest_input` rejects, or any prover or packaging failure,
+    /// is reported to the peer as `GameMessage::Error` and yields `None`, so
+    /// the match continues and nothing is applied to `local_state`.
+    fn prove_for_peer(&mut self, input: &GuestInput, io: &mut dyn PlayerIo) -> Option<(Vec<RoundCommit>, ProofData)> {
+        // Catch inputs the guest would reject before spending prover time
+        let proved = validate_guest_input(input).and_then(|_| produce_and_verify_proof(input)).and_then(|receipt| {
+            let commits = extract_round_commits(&receipt)?;
+            let last = commits.last().cloned().ok_or_else(|| ProofError::Rejected("receipt contains no round commits".into()))?;
+            let pd = proofdata_from_receipt(&receipt, last)?;
+            Ok((commits, pd))
+        });
+        match proved {
+            Ok(p) => Some(p),
+            Err(e) => {
+                // inform requester but do not abort the game; allow retry
+                let message = match e {
+                    ProofError::InvalidInput(_) => e.to_string(),
+                    _ => format!("prover failed: {}", e),
+                };
+                let _ = self.network.send_enveloped(&GameMessage::Error { message });
+                io.log(&format!("Proving failed: {}. Sent Error to requester.", e));
+                None
+            }
+        }
+    }
+
+    /// `check_fleet` and `check_placement` for a proof from the opponent,
+    /// against the parameters and placement from their `BoardReady`.
+    fn check_opponent_proof(&self, receipt: &Receipt) -> Result<(), ProofError> {
+        let placement = self.opponent_placement.ok_or_else(|| ProofError::Rejected("no stored opponent placement commitment".into()))?;
+        check_fleet(receipt, self.network.fleet_spec())?;
+        check_placement(receipt, placement)
+    }
+
+    /// Adopt a verified result for one of our shots: advance
+    /// `opponent_commit` to its `new_state` and record it in `opponent_view`,
+    /// marking every cell of a sunk ship. Each `(match_id, seq, shot)` is
+    /// applied at most once; a replay returns `Ok(false)` and changes
+    /// nothing. A result that contradicts an earlier one for the same cell
+    /// is an error: the opponent's proofs can no longer be trusted, and so
+    /// is a result the guest flagged as `invalid`, which is no real `Miss`.
+    pub fn apply_verified_result(&mut self, rc: &RoundCommit) -> Result<bool> {
+        if let Some(e) = rc.invalid {
+            return Err(ProofError::InvalidShot(e).into());
+        }
+        if !self.applied_shots.insert((rc.match_id, rc.seq, rc.shot)) {
+            return Ok(false);
+        }
+        let cell = if rc.hit == HitType::Miss { CellState::Miss } else { CellState::Hit };
+        let cells = std::iter::once(&rc.shot).chain(&rc.sunk_cells).map(|&pos| (pos, cell));
+        for (pos, cell) in cells.chain(rc.revealed_water.iter().map(|&pos| (pos, CellState::Miss))) {
+            self.opponent_view.merge_observation(pos, cell).map_err(|c| anyhow::anyhow!("opponent contradicted an earlier result: {}", c))?;
+        }
+        self.opponent_commit = Some(rc.new_state);
+        Ok(true)
+    }
+
+    /// `apply_verified_result`, reporting the outcome to `io`. Returns false
+    /// for a replayed result, which the caller must not act on.
+    fn record_observation(&mut self, rc: &RoundCommit, io: &mut dyn PlayerIo) -> Result<bool> {
+        if !self.apply_verified_result(rc)? {
+            io.log(&format!("Ignoring replayed result for {:?} (seq {}).", rc.shot, rc.seq));
+            return Ok(false);
+        }
+        io.shot_resolved(rc.shot, &rc.hit);
+        Ok(true)
+    }
+
+    /// Whether every cell of the agreed fleet has been verified as a hit in
+    /// `opponent_view`, i.e. the opponent has no ship left afloat.
+    fn opponent_fleet_sunk(&self) -> bool {
+        let fleet_cells: usize = self.network.fleet_spec().ships.iter().map(|st| st.size() as usize).sum();
+        let hits = self.opponent_view.grid.iter().flatten().filter(|c| **c == CellState::Hit).count();
+        hits >= fleet_cells
+    }
+
+    /// Wait for the defeated opponent's `GameOver` after our shot sank their
+    /// last ship, logging anything else that arrives first.
+    fn await_game_over(&mut self, io: &mut dyn PlayerIo) -> Result<GameOutcome> {
+        loop {
+            match self.network.receive_enveloped()?.payload {
+                GameMessage::GameOver { winner } => {
+                    io.log(&format!("Game over: winner = {}", winner));
+                    return Ok(GameOutcome::Winner(self.local_player()));
+                }
+                other => io.log(&format!("Unexpected message while waiting for GameOver: {:?}", other)),
+            }
+        }
+    }
+
+    /// Ask `io` for, send, and verify one salvo against the opponent. Input is
+    /// re-requested until a verified `SalvoResult` arrives. Returns the
+    /// outcome instead if the opponent ends the match with a `Forfeit`.
+    fn take_salvo_turn(&mut self, io: &mut dyn PlayerIo) -> Result<Option<GameOutcome>> {
         loop {
+            io.show_boards(&self.local_state, &self.opponent_view);
+            let count = self.turn_mode.shots_per_turn(&self.local_state);
+            self.clock.start(self.local_player());
+            let positions = io.next_salvo(count);
+            self.clock.stop();
+            let positions = positions?;
+            // opponent_view only tracks our shots, so it catches OOB, repeats, and duplicates
+            if self.opponent_view.clone().apply_salvo(&positions).is_none() {
+                io.log("Salvo contains an out-of-bounds, repeated, or duplicate shot.");
+                continue;
+            }
+            let expected_old = match self.opponent_commit {
+                Some(d) => d,
+                None => { io.log("No stored opponent commit - cannot verify salvo results."); continue; }
+            };
+
+            self.network.send_enveloped(&GameMessage::TakeSalvo { positions: positions.clone() })?;
+            let env = self.network.receive_enveloped()?;
+            let proof = match env.payload {
+                GameMessage::SalvoResult { positions: got, proof, .. } if got == positions => proof,
+                GameMessage::Error { message } => { io.log(&format!("Opponent could not prove the salvo: {}", message)); continue; }
+                GameMessage::Forfeit { reason } => {
+                    io.log(&format!("Opponent declared a forfeit against us: {}", reason));
+                    return Ok(Some(GameOutcome::Winner(self.opponent_player())));
+                }
+                other => { io.log(&format!("Unexpected message while waiting for SalvoResult: {:?}", other)); continue; }
+            };
+            let commits = match receipt_from_proofdata(&proof).and_then(|receipt| {
+                self.check_opponent_proof(&receipt)?;
+                crate::proofs::verify_salvo_result_for_shooter(&receipt, expected_old, &positions, Some(env.match_id), Some(env.seq), Some(self.turn))
+            }) {
+                Ok(c) => c,
+                Err(e) => { io.log(&format!("Failed to verify incoming SalvoResult: {}. Rejecting.", e)); continue; }
+            };
+
+            for c in &commits {
+                self.record_observation(c, io)?;
+            }
+            let results: Vec<HitType> = commits.into_iter().map(|c| c.hit).collect();
+            io.log(&salvo_summary(&results));
+            return Ok(None);
+        }
+    }
+
+    /// Play the networked game loop. This function blocks until the game ends
+    /// and returns the outcome from the local player's seat together with
+    /// both players' thinking time.
+    pub fn play_game(&mut self) -> Result<MatchResult> {
+        self.play_game_with(&mut StdioIo)
+    }
+
+    /// Headless variant of `play_game` for GUI front ends: shots come from
+    /// `rx` and everything `play_game` would print is sent on `tx` (see
+    /// `crate::ui`). Ends with `UiEvent::GameOver`, or an error if the
+    /// command channel closes mid-game.
+    pub fn play_game_channels(&mut self, rx: Receiver<UiCommand>, tx: Sender<UiEvent>) -> Result<MatchResult> {
+        let mut io = ChannelIo { rx, tx };
+        let result = self.play_game_with(&mut io)?;
+        let _ = io.tx.send(UiEvent::GameOver(result.outcome));
+        Ok(result)
+    }
+
+    fn play_game_with(&mut self, io: &mut dyn PlayerIo) -> Result<MatchResult> {
+        let outcome = self.play_turns(io)?;
+        io.log(&self.clock.to_string());
+        Ok(MatchResult { outcome, p1_time: self.clock.total(Player::One), p2_time: self.clock.total(Player::Two) })
+    }
+
+    /// Save `turn` to `session_dir`, if set. A failed save is reported but
+    /// does not stop the game.
+    fn turn_changed(&self, io: &mut dyn PlayerIo) {
+        if let (Some(dir), Some(match_id)) = (&self.session_dir, self.network.match_id()) {
+            if let Err(e) = save_turn_in(dir, match_id, &SavedTurn { local_player: self.local_player(), turn: self.turn }) {
+                io.log(&format!("Could not save the turn state: {}", e));
+            }
+        }
+    }
+
+    /// Count an invalid shot request from the opponent and refuse it with
+    /// `message`. Once `invalid_shot_limit` is reached, sends `Forfeit`
+    /// instead and returns true: the match is over and we won.
+    fn refuse_invalid_shot(&mut self, message: String, io: &mut dyn PlayerIo) -> Result<bool> {
+        self.invalid_shots += 1;
+        if self.invalid_shot_limit.is_some_and(|limit| self.invalid_shots >= limit) {
+            let reason = format!("{} invalid shots ({})", self.invalid_shots, message);
+            io.log(&format!("Opponent forfeits after {}.", reason));
+            self.network.send_enveloped(&GameMessage::Forfeit { reason })?;
+            return Ok(true);
+        }
+        self.network.send_enveloped(&GameMessage::Error { message })?;
+        Ok(false)
+    }
+
+    fn play_turns(&mut self, io: &mut dyn PlayerIo) -> Result<GameOutcome> {
+        // `turn` starts with Player One, whom `local_player()` maps onto our
+        // seat, unless a saved turn state was restored
+        loop {
+            let local_turn = self.turn.to_move == self.local_player();
+            if local_turn && self.turn_mode == TurnMode::Salvo {
+                if let Some(outcome) = self.take_salvo_turn(io)? {
+                    return Ok(outcome);
+                }
+                self.turn.pass_to(self.opponent_player());
+                self.turn_changed(io);
+                continue;
+            }
             if local_turn {
                 // Show boards: local (revealed) and opponent view (hits/misses)
-                display_dual(&self.local_state, &self.opponent_view, true);
+                io.show_boards(&self.local_state, &self.opponent_view);
                 // Local player's move
-                println!("Your turn. Enter shot as 'x y':");
-                print!("> "); io::stdout().flush().ok();
-                let mut line = String::new();
-                io::stdin().read_line(&mut line)?;
-                let parts: Vec<_> = line.trim().split_whitespace().collect();
-                if parts.len() != 2 { println!("Invalid input"); continue; }
-                let x: u32 = parts[0].parse().unwrap_or(999);
-                let y: u32 = parts[1].parse().unwrap_or(999);
-                let pos = Position::new(x,y);
+                self.clock.start(self.local_player());
+                let pos = io.next_shot();
+                self.clock.stop();
+                let pos = pos?;
 
                 // Prevent shooting same place twice (use opponent_view which tracks our shots on opponent)
                 if !pos.in_bounds() {
-                    println!("Position out of bounds");
+                    io.log("Position out of bounds");
                     continue;
                 }
                 let ox = pos.x as usize;
                 let oy = pos.y as usize;
                 if self.opponent_view.grid[oy][ox] != CellState::Empty {
-                    println!("You already fired at {},{}; pick another target.", ox, oy);
+                    io.log(&format!("You already fired at {},{}; pick another target.", ox, oy));
                     continue;
                 }
 
@@ -265,44 +827,79 @@ impl GameCoordinator {
                         // Simpler approach: send a TakeShot request and wait for opponent to respond with ShotResult containing proof.
                         let msg = GameMessage::TakeShot { position: pos };
                         self.network.send_enveloped(&msg)?;
-                        // Wait for opponent ShotResult
-                        let env = self.network.receive_enveloped()?;
+                        // Wait for opponent ShotResult. A shot request from the peer now
+                        // means both sides think they are shooting; refuse it without
+                        // touching our board and keep waiting.
+                        let env = loop {
+                            let env = self.network.receive_enveloped()?;
+                            match &env.payload {
+                                GameMessage::TakeShot { .. } | GameMessage::TakeSalvo { .. } => {
+                                    io.log("Opponent tried to shoot out of turn; refused.");
+                                    self.network.send_enveloped(&GameMessage::Error { message: "out of turn: waiting for your ShotResult".into() })?;
+                                }
+                                _ => break env,
+                            }
+                        };
                         match env.payload {
                             GameMessage::ShotResult { position, hit_type: _, proof } => {
                                 // Reconstruct receipt
-                                let receipt = receipt_from_proofdata(&proof)?;
+                                let receipt = match receipt_from_proofdata(&proof) {
+                                    Ok(r) => r,
+                                    Err(e) => {
+                                        io.log(&format!("Malformed proof in ShotResult: {}. Rejecting.", e));
+                                        continue;
+                                    }
+                                };
                                 // We must have a stored opponent_commit (old digest) to validate against
                                 let expected_old = match self.opponent_commit {
                                     Some(d) => d,
                                     None => {
-                                        println!("No stored opponent commit - cannot verify incoming ShotResult. Rejecting.");
+                                        io.log("No stored opponent commit - cannot verify incoming ShotResult. Rejecting.");
                                         continue;
                                     }
                                 };
 
                                 // Verify receipt and binding: ensure the proof contains a commit bound to the
                                 // current match_id/seq and that commit.old_state == our stored opponent_commit.
-                                let rc = match crate::proofs::verify_shot_result_for_shooter(&receipt, expected_old, position, Some(env.match_id), Some(env.seq)) {
+                                let rc = match self.check_opponent_proof(&receipt).and_then(|_| crate::proofs::verify_shot_result_for_shooter(&receipt, expected_old, position, Some(env.match_id), Some(env.seq), Some(self.turn))) {
                                     Ok(c) => c,
                                     Err(e) => {
-                                        println!("Failed to verify incoming ShotResult: {}. Rejecting.", e);
+                                        io.log(&format!("Failed to verify incoming ShotResult: {}. Rejecting.", e));
                                         continue;
                                     }
                                 };
 
                                 // Adopt the new opponent commitment and record hit/miss for UI
-                                self.opponent_commit = Some(rc.new_state);
-                                use core::CellState;
-                                let x = position.x as usize;
-                                let y = position.y as usize;
+                                if !self.record_observation(&rc, io)? {
+                                    continue;
+                                }
                                 match rc.hit {
-                                    HitType::Miss => { self.opponent_view.grid[y][x] = CellState::Miss; println!("Miss (verified). Turn passes to opponent."); local_turn = false; }
-                                    HitType::Hit => { self.opponent_view.grid[y][x] = CellState::Hit; println!("Hit (verified)! You get another shot."); local_turn = true; }
-                                    HitType::Sunk(st) => { self.opponent_view.grid[y][x] = CellState::Hit; println!("Sunk {:?} (verified). Turn passes.", st); local_turn = false; }
+                                    HitType::Miss => io.log("Miss (verified). Turn passes to opponent."),
+                                    HitType::Hit => io.log("Hit (verified)!"),
+                                    HitType::Sunk(st) => io.log(&format!("Sunk {:?} (verified).", st)),
+                                    HitType::SunkHidden(_) => io.log("Sunk a ship, type hidden until the end (verified)."),
                                 }
-                                display_dual(&self.local_state, &self.opponent_view, true);
+                                self.turn.advance(&self.network.fleet_spec().turn_rules, self.local_player(), &rc.hit);
+                                self.turn_changed(io);
+                                // A final sinking may keep the turn under `extra_turn_on_sunk`;
+                                // the defender's GameOver follows, so don't prompt for another shot
+                                if self.opponent_fleet_sunk() {
+                                    io.show_boards(&self.local_state, &self.opponent_view);
+                                    return self.await_game_over(io);
+                                }
+                                if self.turn.to_move == self.local_player() && rc.hit != HitType::Miss { io.log("You get another shot."); }
+                                io.show_boards(&self.local_state, &self.opponent_view);
+                            }
+                            GameMessage::Error { message } => { io.log(&format!("Opponent could not answer the shot: {}. Try again.", message)); }
+                            GameMessage::Forfeit { reason } => {
+                                io.log(&format!("Opponent declared a forfeit against us: {}", reason));
+                                return Ok(GameOutcome::Winner(self.opponent_player()));
+                            }
+                            GameMessage::GameOver { winner } => {
+                                io.log(&format!("Game over: winner = {}", winner));
+                                return Ok(GameOutcome::Winner(self.local_player()));
                             }
-                            other => { println!("Unexpected message while waiting for ShotResult: {:?}", other); }
+                            other => { io.log(&format!("Unexpected message while waiting for ShotResult: {:?}", other)); }
                         }
                         // Continue to next loop iteration
                         continue;
@@ -310,118 +907,158 @@ impl GameCoordinator {
                 }
             } else {
                 // Opponent's turn: wait for messages
-                let env = self.network.receive_enveloped()?;
+                self.clock.start(self.opponent_player());
+                let env = self.network.receive_enveloped();
+                self.clock.stop();
+                let env = env?;
                 match env.payload {
                     GameMessage::TakeShot { position } => {
                         // Opponent is requesting to take a shot; as the defender we must produce a proof and respond with ShotResult
                         // Build GuestInput using our local_state and the requested shot
-                        // Reject duplicate shots: if this position was already shot on our board, return an Error
-                        if !position.in_bounds() {
-                            let err = GameMessage::Error { message: format!("requested position out of bounds: {:?}", position) };
-                            self.network.send_enveloped(&err)?;
-                            continue;
-                        }
-                        let px = position.x as usize;
-                        let py = position.y as usize;
-                        if self.local_state.grid[py][px] != CellState::Empty {
-                            let err = GameMessage::Error { message: format!("position already shot: {:?}", position) };
-                            self.network.send_enveloped(&err)?;
+                        // Reject out-of-bounds and duplicate shots with an Error, counting them
+                        // against `invalid_shot_limit`
+                        if let Err(e) = self.local_state.check_shot(position) {
+                            let message = match e {
+                                ShotError::OutOfBounds => format!("requested position out of bounds: {:?}", position),
+                                ShotError::AlreadyShot => format!("position already shot: {:?}", position),
+                            };
+                            if self.refuse_invalid_shot(message, io)? {
+                                return Ok(GameOutcome::Winner(self.local_player()));
+                            }
                             continue;
                         }
 
-                        let input = crate::proofs::GuestInput { initial: self.local_state.clone(), shots: vec![position], match_id: env.match_id, seq: env.seq };
-                        // Try to produce the per-shot proof locally. If the prover is
-                        // not available the function will return an error; in that
-                        // case send an Error message back to the requester so the
-                        // remote peer can decide how to continue (or re-run with
-                        // a proper toolchain).
-                        let receipt = match produce_and_verify_proof(&input) {
-                            Ok(r) => r,
-                            Err(e) => {
-                                let err_msg = format!("prover unavailable: {}", e);
-                                let err = GameMessage::Error { message: err_msg.clone() };
-                                // inform requester but do not abort the game; allow retry
-                                let _ = self.network.send_enveloped(&err);
-                                println!("Prover unavailable: {}. Sent Error to requester.", e);
-                                continue;
-                            }
+                        let input = crate::proofs::GuestInput { initial: self.local_state.clone(), shots: vec![position], match_id: env.match_id, seq: env.seq, turn_owner: vec![self.opponent_player()], turn: self.turn, fleet: self.network.fleet_spec().clone(), disclosure: self.disclosure };
+                        // Try to produce the per-shot proof locally. If proving fails
+                        // an Error is sent back to the requester so the remote peer
+                        // can decide how to continue (or re-run with a proper toolchain).
+                        let (commits, pd) = match self.prove_for_peer(&input, io) {
+                            Some(p) => p,
+                            None => continue,
                         };
-                        // Extract round commit
-                        let commits = extract_round_commits(&receipt)?;
-                        let rc = commits.last().unwrap().clone();
+                        let rc = commits.last().expect("prove_for_peer returns at least one commit").clone();
                         // Apply shot locally
                         let _apply_res = self.local_state.apply_shot(position);
                         // If all our ships are sunk after this shot, notify opponent and end game
                         if self.local_state.ships.iter().all(|s| s.is_sunk()) {
                             let winner = self.opponent_name.clone().unwrap_or_else(|| "Opponent".to_string());
-                            // Build ProofData and send ShotResult
-                            let pd = proofdata_from_receipt(&receipt, rc.clone())?;
+                            // Send ShotResult
                             let msg = GameMessage::ShotResult { position, hit_type: rc.hit.clone(), proof: pd };
                             self.network.send_enveloped(&msg)?;
                             // Send GameOver announcing opponent as winner
                             let over = GameMessage::GameOver { winner: winner.clone() };
                             self.network.send_enveloped(&over)?;
-                            println!("All our ships sunk. {} wins!", winner);
-                            return Ok(());
+                            io.log(&format!("All our ships sunk. {} wins!", winner));
+                            return Ok(GameOutcome::Winner(self.opponent_player()));
                         }
-                        // Build ProofData and send ShotResult
-                        let pd = proofdata_from_receipt(&receipt, rc.clone())?;
+                        // Send ShotResult
                         let msg = GameMessage::ShotResult { position, hit_type: rc.hit.clone(), proof: pd };
                         self.network.send_enveloped(&msg)?;
 
                         // Update turn according to hit type
                         match rc.hit {
-                            HitType::Miss => { println!("Opponent missed at {:?}", position); local_turn = true; }
-                            HitType::Hit => { println!("Opponent hit at {:?}", position); local_turn = false; }
-                            HitType::Sunk(_) => { println!("Opponent sunk a ship at {:?}", position); local_turn = true; }
+                            HitType::Miss => io.log(&format!("Opponent missed at {:?}", position)),
+                            HitType::Hit => io.log(&format!("Opponent hit at {:?}", position)),
+                            HitType::Sunk(_) | HitType::SunkHidden(_) => io.log(&format!("Opponent sunk a ship at {:?}", position)),
                         }
+                        self.turn.advance(&self.network.fleet_spec().turn_rules, self.opponent_player(), &rc.hit);
+                        self.turn_changed(io);
                     },
                     GameMessage::ShotResult { position, hit_type: _, proof } => {
                         // Received a ShotResult for a shot we previously made
-                        let receipt = receipt_from_proofdata(&proof)?;
+                        let receipt = match receipt_from_proofdata(&proof) {
+                            Ok(r) => r,
+                            Err(e) => {
+                                io.log(&format!("Malformed proof in ShotResult: {}. Rejecting.", e));
+                                continue;
+                            }
+                        };
                         // We must have a stored opponent_commit (old digest) to validate against
                         let expected_old = match self.opponent_commit {
                             Some(d) => d,
                             None => {
-                                println!("No stored opponent commit - cannot verify incoming ShotResult. Rejecting.");
+                                io.log("No stored opponent commit - cannot verify incoming ShotResult. Rejecting.");
                                 continue;
                             }
                         };
 
                         // Verify receipt, binding, and that commit.old_state == our stored opponent_commit
-                        let rc = match verify_shot_result_for_shooter(&receipt, expected_old, position, Some(env.match_id), Some(env.seq)) {
+                        let rc = match self.check_opponent_proof(&receipt).and_then(|_| verify_shot_result_for_shooter(&receipt, expected_old, position, Some(env.match_id), Some(env.seq), None)) {
                             Ok(c) => c,
                             Err(e) => {
-                                println!("Failed to verify incoming ShotResult: {}. Rejecting.", e);
+                                io.log(&format!("Failed to verify incoming ShotResult: {}. Rejecting.", e));
                                 continue;
                             }
                         };
 
                         // Adopt the new opponent commitment and record hit/miss for UI
-                        self.opponent_commit = Some(rc.new_state);
-                        use core::CellState;
-                        let x = position.x as usize;
-                        let y = position.y as usize;
+                        if !self.record_observation(&rc, io)? {
+                            continue;
+                        }
                         match rc.hit {
-                            HitType::Miss => { self.opponent_view.grid[y][x] = CellState::Miss; println!("Miss (verified). Turn passes to opponent."); local_turn = true; }
-                            HitType::Hit => { self.opponent_view.grid[y][x] = CellState::Hit; println!("Hit (verified)! You get another shot."); local_turn = false; }
-                            HitType::Sunk(st) => { self.opponent_view.grid[y][x] = CellState::Hit; println!("Sunk {:?} (verified). Turn passes.", st); local_turn = true; }
+                            HitType::Miss => io.log("Miss (verified). Turn passes to opponent."),
+                            HitType::Hit => io.log("Hit (verified)!"),
+                            HitType::Sunk(st) => io.log(&format!("Sunk {:?} (verified).", st)),
+                            HitType::SunkHidden(_) => io.log("Sunk a ship, type hidden until the end (verified)."),
                         }
-                        display_dual(&self.local_state, &self.opponent_view, true);
+                        self.turn.advance(&self.network.fleet_spec().turn_rules, self.opponent_player(), &rc.hit);
+                        self.turn_changed(io);
+                        io.show_boards(&self.local_state, &self.opponent_view);
                     }
-                    GameMessage::BoardReady { .. } => {
+                    GameMessage::BoardReady { .. } | GameMessage::CoinReveal { .. } | GameMessage::RematchRequest | GameMessage::RematchAccept => {
                         // ignore here
                     }
+                    GameMessage::TakeSalvo { positions } => {
+                        // Validate on a scratch copy so nothing is applied unless the whole salvo is legal
+                        let mut scratch = self.local_state.clone();
+                        if positions.is_empty() || positions.len() > core::NUM_SHIPS || scratch.apply_salvo(&positions).is_none() {
+                            if self.refuse_invalid_shot(format!("invalid salvo: {:?}", positions), io)? {
+                                return Ok(GameOutcome::Winner(self.local_player()));
+                            }
+                            continue;
+                        }
+                        let input = GuestInput { initial: self.local_state.clone(), shots: positions.clone(), match_id: env.match_id, seq: env.seq, turn_owner: vec![], turn: self.turn, fleet: self.network.fleet_spec().clone(), disclosure: self.disclosure };
+                        let (commits, pd) = match self.prove_for_peer(&input, io) {
+                            Some(p) => p,
+                            None => continue,
+                        };
+                        let hit_types: Vec<HitType> = commits.iter().map(|c| c.hit.clone()).collect();
+                        let _ = self.local_state.apply_salvo(&positions);
+                        let msg = GameMessage::SalvoResult { positions: positions.clone(), hit_types, proof: pd };
+                        self.network.send_enveloped(&msg)?;
+                        io.log(&format!("Opponent fired a salvo at {:?}", positions));
+                        if self.local_state.all_ships_sunk() {
+                            let winner = self.opponent_name.clone().unwrap_or_else(|| "Opponent".to_string());
+                            self.network.send_enveloped(&GameMessage::GameOver { winner: winner.clone() })?;
+                            io.log(&format!("All our ships sunk. {} wins!", winner));
+                            return Ok(GameOutcome::Winner(self.opponent_player()));
+                        }
+                        self.turn.pass_to(self.local_player());
+                        self.turn_changed(io);
+                    }
+                    GameMessage::SalvoResult { .. } => {
+                        // salvo results are consumed by take_salvo_turn; ignore strays
+                    }
+                    GameMessage::ShotResultChunk { .. } => {
+                        // chunked proofs are consumed via receive_large_proof; ignore strays
+                    }
                     GameMessage::GameOver { winner } => {
-                        println!("Game over: winner = {}", winner);
-                        break;
+                        // Only the defeated side sends GameOver, so receiving it means we won.
+                        io.log(&format!("Game over: winner = {}", winner));
+                        return Ok(GameOutcome::Winner(self.local_player()));
                     }
                     GameMessage::Error { message } => {
-                        println!("Network error: {}", message);
+                        io.log(&format!("Network error: {}", message));
+                    }
+                    GameMessage::Forfeit { reason } => {
+                        io.log(&format!("Opponent declared a forfeit against us: {}", reason));
+                        return Ok(GameOutcome::Winner(self.opponent_player()));
+                    }
+                    GameMessage::Chat { .. } => {
+                        // consumed by receive_enveloped
                     }
                 }
             }
         }
-        Ok(())
     }
-}
\ No newline at end of file
+}
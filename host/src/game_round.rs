@@ -6,59 +6,124 @@
 // announcing the winner when all ships for a player are sunk.
 //
 // The module keeps the interaction simple and synchronous using stdin/stdout.
+// `resolve_shot_proven` is the proof-backed variant of `resolve_shot` for
+// front ends that click to shoot at a local opponent.
 
 use std::io::{self, Write};
+use anyhow::{bail, Result};
+use rand::Rng;
 use crate::board_init::prompt_place_ships;
+use crate::proofs::{execute_only, produce_and_verify_proof, verify_remote_round_proof, GuestInput};
 use crate::visualize::{display_board, display_dual};
-use core::{GameState, Position};
+use core::{CellState, GameState, HitType, Player, Position, RoundCommit, TurnRules};
+
+/// Result of one shot, so callers (a GUI, tests) can react without
+/// scraping stdout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnOutcome {
+    pub result: HitType,
+    /// The opponent's whole fleet is now sunk.
+    pub game_over: bool,
+    /// The shooter fires again under the `TurnRules` in force.
+    pub extra_shot: bool,
+}
+
+/// Apply `pos` to `opponent` and describe the outcome. Returns `None` if
+/// the shot is out of bounds or the cell was already shot.
+pub fn resolve_shot(opponent: &mut GameState, pos: Position, rules: &TurnRules) -> Option<TurnOutcome> {
+    let result = opponent.apply_shot(pos)?;
+    let game_over = opponent.all_ships_sunk();
+    let extra_shot = !game_over && rules.keeps_turn(&result);
+    Some(TurnOutcome { result, game_over, extra_shot })
+}
+
+/// How `resolve_shot_proven` runs the guest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofMode {
+    /// Prove the round and verify the receipt, as a networked defender would.
+    Prove,
+    /// Run the guest without proving (`execute_only`): same commits, no
+    /// cryptographic guarantee, much faster.
+    ExecuteOnly,
+}
+
+/// `resolve_shot` through the guest, for a front end playing against a
+/// local opponent (e.g. an AI) whose board it holds. The shot is run as a
+/// one-round guest input against `opponent` in `mode`, and `view` (the
+/// shooter's picture of the opponent's board) is updated only from the
+/// resulting `RoundCommit`, as in the networked protocol. `status` is
+/// called with "proving..." before the guest starts so a GUI can show it
+/// is busy. Returns `Ok(None)` for an out-of-bounds or repeated shot;
+/// nothing changes unless the guest succeeds.
+pub fn resolve_shot_proven(opponent: &mut GameState, view: &mut GameState, pos: Position, rules: &TurnRules, mode: ProofMode, status: &mut dyn FnMut(&str)) -> Result<Option<(TurnOutcome, RoundCommit)>> {
+    let mut next = opponent.clone();
+    let outcome = match resolve_shot(&mut next, pos, rules) {
+        Some(o) => o,
+        None => return Ok(None),
+    };
+    let input = GuestInput { initial: opponent.clone(), shots: vec![pos], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec { turn_rules: *rules, ..core::FleetSpec::classic() }, disclosure: core::DisclosureMode::Open };
+    status("proving...");
+    let commits = match mode {
+        ProofMode::Prove => {
+            let receipt = produce_and_verify_proof(&input)?;
+            verify_remote_round_proof(&receipt, opponent.commit(), pos, None, None)?
+        }
+        ProofMode::ExecuteOnly => execute_only(&input)?,
+    };
+    let rc = match commits.last() {
+        Some(rc) if rc.shot == pos && rc.hit == outcome.result && rc.old_state == opponent.commit() => rc.clone(),
+        _ => bail!("guest result for {:?} does not match the shot", pos),
+    };
+    let cell = if rc.hit == HitType::Miss { CellState::Miss } else { CellState::Hit };
+    let cells = std::iter::once(&rc.shot).chain(&rc.sunk_cells).map(|&p| (p, cell));
+    for (p, cell) in cells.chain(rc.revealed_water.iter().map(|&p| (p, CellState::Miss))) {
+        view.merge_observation(p, cell).map_err(|c| anyhow::anyhow!("guest contradicted an earlier result: {}", c))?;
+    }
+    *opponent = next;
+    Ok(Some((outcome, rc)))
+}
 
 /// Run a fully interactive two-player session. This function blocks on
-/// stdin and prints progress to stdout.
-pub fn run_interactive() {
+/// stdin and prints progress to stdout. `rules` decides when a player
+/// fires again. Random placement draws from `rng`.
+pub fn run_interactive<R: Rng + ?Sized>(rules: TurnRules, rng: &mut R) {
     println!("Welcome to Battleship (interactive host-mode)");
 
     println!("Player 1, place your ships:");
-    let mut p1: GameState = prompt_place_ships("Player 1");
+    let mut p1: GameState = prompt_place_ships("Player 1", rng);
 
     println!("Player 2, place your ships:");
-    let mut p2: GameState = prompt_place_ships("Player 2");
+    let mut p2: GameState = prompt_place_ships("Player 2", rng);
 
     // Optionally show both boards to each player here. For simple
     // play-through, we show the current player's own board and the
     // opponent's hidden board.
-    let mut turn = 0usize; // 0 => player1, 1 => player2
+    let mut turn = Player::One;
 
     loop {
-        println!("\n---- Round: player {} ----", if turn == 0 { 1 } else { 2 });
-        if turn == 0 {
-            println!("Your board (revealed):");
-            display_board(&p1, true);
-            println!("Opponent board (hidden):");
-            display_board(&p2, false);
-
-            if handle_player_turn(&mut p1, &mut p2, "Player 1") {
-                println!("Player 1 wins!");
-                break;
-            }
-        } else {
-            println!("Your board (revealed):");
-            display_board(&p2, true);
-            println!("Opponent board (hidden):");
-            display_board(&p1, false);
+        let name = format!("Player {}", turn.index() + 1);
+        println!("\n---- Round: {} ----", name.to_lowercase());
+        let (active, opponent) = match turn {
+            Player::One => (&mut p1, &mut p2),
+            Player::Two => (&mut p2, &mut p1),
+        };
+        println!("Your board (revealed):");
+        display_board(active, true);
+        println!("Opponent board (hidden):");
+        display_board(opponent, false);
 
-            if handle_player_turn(&mut p2, &mut p1, "Player 2") {
-                println!("Player 2 wins!");
-                break;
-            }
+        let outcome = handle_player_turn(active, opponent, &name, &rules);
+        if outcome.game_over {
+            println!("{} wins!", name);
+            break;
         }
-        turn = 1 - turn;
+        turn = rules.next_state(turn, &outcome.result).next_player;
     }
 }
 
 /// Handle a single player's turn: prompt for shot coordinates, apply shot
-/// to opponent board, print the outcome, and return `true` if opponent is
-/// fully sunk (game over).
-fn handle_player_turn(active: &mut GameState, opponent: &mut GameState, player_name: &str) -> bool {
+/// to opponent board, print the outcome, and return it.
+fn handle_player_turn(active: &mut GameState, opponent: &mut GameState, player_name: &str, rules: &TurnRules) -> TurnOutcome {
     loop {
         print!("{player_name}, enter shot as: x y (or 'show' to display boards): ");
         io::stdout().flush().ok();
@@ -86,31 +151,28 @@ fn handle_player_turn(active: &mut GameState, opponent: &mut GameState, player_n
             Err(_) => { println!("Invalid y"); continue; }
         };
         let pos = Position::new(x as u32, y as u32);
-        match opponent.apply_shot(pos) {
+        match resolve_shot(opponent, pos, rules) {
             None => { println!("Shot out of bounds or already taken; try again."); continue; }
-            Some(hit_type) => {
-                match hit_type {
-                    core::HitType::Miss => println!("Miss."),
-                    core::HitType::Hit => println!("Hit!"),
-                    core::HitType::Sunk(st) => println!("Sunk a {:?}!", st),
+            Some(outcome) => {
+                match &outcome.result {
+                    HitType::Miss => println!("Miss."),
+                    HitType::Hit => println!("Hit!"),
+                    HitType::Sunk(st) => println!("Sunk a {:?}!", st),
+                    HitType::SunkHidden(_) => println!("Sunk a ship!"),
                 }
-                break;
+                if outcome.extra_shot {
+                    println!("{player_name} fires again.");
+                }
+                return outcome;
             }
         }
     }
-
-    // After applying shot, check for game over
-    if opponent.check() && opponent.ships.iter().all(|s| s.is_sunk()) {
-        return true;
-    }
-    false
 }
 
-/// Small helper to run a quick demo game without interactive placement.
-/// Places each player's ships in predefined locations (useful for
-/// automated testing or demoing visualization).
-pub fn run_demo() {
-    use core::{GameState, ShipType, Direction, Position};
+/// Fixed, fully placed boards for both players: Player 1's ships lie
+/// horizontally on even rows, Player 2's vertically on even columns.
+pub fn demo_boards() -> (GameState, GameState) {
+    use core::{ShipType, Direction};
     let mut p1 = GameState::new([0; 16]);
     p1.place_ship(ShipType::Carrier, Position::new(0,0), Direction::Horizontal);
     p1.place_ship(ShipType::Battleship, Position::new(0,2), Direction::Horizontal);
@@ -124,7 +186,14 @@ pub fn run_demo() {
     p2.place_ship(ShipType::Cruiser, Position::new(4,0), Direction::Vertical);
     p2.place_ship(ShipType::Submarine, Position::new(6,0), Direction::Vertical);
     p2.place_ship(ShipType::Destroyer, Position::new(8,0), Direction::Vertical);
+    (p1, p2)
+}
 
+/// Small helper to run a quick demo game without interactive placement.
+/// Places each player's ships in predefined locations (`demo_boards`,
+/// useful for automated testing or demoing visualization).
+pub fn run_demo() {
+    let (p1, p2) = demo_boards();
     println!("Demo: Player boards (left: P1 revealed, right: P2 hidden)");
     display_dual(&p1, &p2, true);
 }
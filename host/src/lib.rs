@@ -2,12 +2,24 @@
 // integration tests and other crates can depend on `host` as a library.
 
 pub mod board_init;
+pub mod clock;
+pub mod error;
 pub mod visualize;
+#[cfg(feature = "prove")]
 pub mod game_round;
+#[cfg(feature = "prove")]
 pub mod proofs;
+pub mod verify;
 pub mod network;
 pub mod network_protocol;
+pub mod memory_transport;
+pub mod referee;
+pub mod ui;
+#[cfg(feature = "prove")]
 pub mod game;
+#[cfg(feature = "prove")]
+pub mod doctor;
+pub mod tournament;
 
 // Optionally, you can expose helper functions here that combine the above
 // modules into common flows.
@@ -5,7 +5,57 @@ use tracing_subscriber::{self, filter::{LevelFilter, filter_fn}, layer::Subscrib
 
 use host::game::{run_game_master_interactive, GameCoordinator};
 use host::board_init::prompt_place_ships;
-use host::network::NetworkConnection;
+use host::network::{HeartbeatConfig, NetworkConnection};
+use host::proofs::ProverBackend;
+use core::{TurnMode, TurnRules};
+use rand::rngs::ThreadRng;
+
+/// Play matches over `coord`'s connection until either player declines a
+/// rematch or a game ends with an error.
+fn play_matches(coord: &mut GameCoordinator, rng: &mut ThreadRng) {
+    loop {
+        match coord.play_game() {
+            Ok(result) => println!("Match finished: {:?} (P1 {:.1}s, P2 {:.1}s thinking)", result.outcome, result.p1_time.as_secs_f64(), result.p2_time.as_secs_f64()),
+            Err(e) => {
+                eprintln!("Game ended with error: {}", e);
+                return;
+            }
+        }
+        print!("Rematch over the same connection? [y/N]: "); io::stdout().flush().ok();
+        let mut answer = String::new(); io::stdin().read_line(&mut answer).ok();
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return;
+        }
+        println!("Waiting for the opponent to agree...");
+        match coord.network.request_rematch() {
+            Ok(true) => {}
+            Ok(false) => { println!("Opponent declined the rematch."); return; }
+            Err(e) => { eprintln!("Rematch failed: {}", e); return; }
+        }
+        println!("{}: place your ships", coord.player_name);
+        let state = prompt_place_ships(&coord.player_name, rng);
+        if let Err(e) = coord.start_rematch(state) {
+            eprintln!("Rematch handshake failed: {}", e);
+            return;
+        }
+    }
+}
+
+/// `host doctor`: check the proving setup and exit 0 only if a real
+/// receipt verifies.
+fn doctor() -> ! {
+    let backend = if cfg!(feature = "cuda") { ProverBackend::Cuda } else { ProverBackend::Local };
+    println!("Checking the prover setup (this proves one shot and may take a while)...");
+    let result = host::doctor::run(&backend, &mut |s| println!("  ok: {}", s));
+    match &result {
+        Ok(()) => println!("Prover setup is healthy."),
+        Err(failure) => {
+            eprintln!("  FAILED: {}", failure);
+            eprintln!("{}", failure.guidance());
+        }
+    }
+    std::process::exit(host::doctor::exit_code(&result))
+}
 
 fn main() {
     // Hard-disable all tracing output from RISC0 and related proving crates by target prefix,
@@ -27,7 +77,13 @@ fn main() {
         )
         .init();
 
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        doctor();
+    }
+
     println!("=== ZK Battleship Host ===");
+    // The only RNG the binary creates; everything below takes it as a parameter
+    let mut rng = rand::thread_rng();
 
     loop {
         println!("Select an option:\n 1) Local 2-player (no network)\n 2) Host a networked game\n 3) Join a networked game\n 4) Exit");
@@ -39,7 +95,10 @@ fn main() {
         }
         match choice.trim() {
             "1" => {
-                run_game_master_interactive();
+                print!("Turn mode: (C)lassic or (S)alvo? [C]: "); io::stdout().flush().ok();
+                let mut mode_s = String::new(); io::stdin().read_line(&mut mode_s).ok();
+                let mode = if mode_s.trim().eq_ignore_ascii_case("s") { TurnMode::Salvo } else { TurnMode::Classic };
+                run_game_master_interactive(mode, TurnRules::default(), &mut rng);
             }
             "2" => {
                 // Host a networked game
@@ -47,31 +106,42 @@ fn main() {
                 let mut port_s = String::new(); io::stdin().read_line(&mut port_s).ok();
                 let port: u16 = port_s.trim().parse().unwrap_or(7878);
 
+                print!("Bind address (default 0.0.0.0, use :: for IPv6): "); io::stdout().flush().ok();
+                let mut bind_s = String::new(); io::stdin().read_line(&mut bind_s).ok();
+                let bind_ip: std::net::IpAddr = match bind_s.trim().trim_start_matches('[').trim_end_matches(']') {
+                    "" => std::net::Ipv4Addr::UNSPECIFIED.into(),
+                    ip => match ip.parse() {
+                        Ok(ip) => ip,
+                        Err(_) => { println!("Invalid bind address: {}", ip); continue; }
+                    },
+                };
+
                 print!("Player name: "); io::stdout().flush().ok();
                 let mut name = String::new(); io::stdin().read_line(&mut name).ok();
                 let name = name.trim().to_string();
 
                 println!("{}: place your ships", name);
-                let state = prompt_place_ships(&name);
+                let state = prompt_place_ships(&name, &mut rng);
                 let commit = state.commit();
 
-                match NetworkConnection::host(port) {
+                match NetworkConnection::host(std::net::SocketAddr::new(bind_ip, port)) {
                     Ok(net) => {
                         let mut coord = GameCoordinator::new(state, commit, net, name.clone(), true);
                         if let Err(e) = coord.handshake() {
                             eprintln!("Handshake failed: {}", e);
                             continue;
                         }
-                        if let Err(e) = coord.play_game() {
-                            eprintln!("Game ended with error: {}", e);
+                        if let Err(e) = coord.network.start_heartbeat(HeartbeatConfig::default()) {
+                            eprintln!("Heartbeat unavailable, continuing without: {}", e);
                         }
+                        play_matches(&mut coord, &mut rng);
                     }
                     Err(e) => eprintln!("Failed to start host: {}", e),
                 }
             }
             "3" => {
                 // Join a networked game
-                print!("Host IP (default 127.0.0.1): "); io::stdout().flush().ok();
+                print!("Host name or IP, IPv6 allowed (default 127.0.0.1): "); io::stdout().flush().ok();
                 let mut host = String::new(); io::stdin().read_line(&mut host).ok();
                 let host = if host.trim().is_empty() { "127.0.0.1".to_string() } else { host.trim().to_string() };
 
@@ -84,19 +154,20 @@ fn main() {
                 let name = name.trim().to_string();
 
                 println!("{}: place your ships", name);
-                let state = prompt_place_ships(&name);
+                let state = prompt_place_ships(&name, &mut rng);
                 let commit = state.commit();
 
-                match NetworkConnection::connect(&host, port) {
+                match NetworkConnection::connect_with_retry(&host, port, 5, std::time::Duration::from_millis(500)) {
                     Ok(net) => {
                         let mut coord = GameCoordinator::new(state, commit, net, name.clone(), false);
                         if let Err(e) = coord.handshake() {
                             eprintln!("Handshake failed: {}", e);
                             continue;
                         }
-                        if let Err(e) = coord.play_game() {
-                            eprintln!("Game ended with error: {}", e);
+                        if let Err(e) = coord.network.start_heartbeat(HeartbeatConfig::default()) {
+                            eprintln!("Heartbeat unavailable, continuing without: {}", e);
                         }
+                        play_matches(&mut coord, &mut rng);
                     }
                     Err(e) => eprintln!("Failed to connect: {}", e),
                 }
@@ -0,0 +1,101 @@
+// In-memory transport for driving `NetworkConnection` without sockets.
+//
+// `NetworkConnection` frames, seals and checks envelopes on top of any
+// `Read + Write` stream (see `NetworkConnection::from_stream`), so the
+// stream is already the transport seam. `MemoryStream::pair` provides two
+// connected ends backed by byte queues: bytes written to one end are read
+// from the other, reads block until data arrives, and dropping an end looks
+// like a closed socket to its peer (EOF on read, `BrokenPipe` on write).
+//
+// There is no TLS, DH exchange or read timeout, so coordinator tests run
+// fast and deterministically without certificates. For the same reason a
+// heartbeat over this transport cannot notice a silent peer.
+
+use crate::network::NetworkConnection;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// One direction of a `MemoryStream` pair.
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    ready: Condvar,
+}
+
+#[derive(Default)]
+struct PipeState {
+    bytes: VecDeque<u8>,
+    /// Set when either end is dropped.
+    closed: bool,
+}
+
+impl Pipe {
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// One end of an in-memory duplex byte stream; see `MemoryStream::pair`.
+pub struct MemoryStream {
+    incoming: Arc<Pipe>,
+    outgoing: Arc<Pipe>,
+}
+
+impl MemoryStream {
+    /// Two connected ends: what one writes, the other reads.
+    pub fn pair() -> (MemoryStream, MemoryStream) {
+        let (a_to_b, b_to_a) = (Arc::new(Pipe::default()), Arc::new(Pipe::default()));
+        (MemoryStream { incoming: b_to_a.clone(), outgoing: a_to_b.clone() }, MemoryStream { incoming: a_to_b, outgoing: b_to_a })
+    }
+}
+
+impl Read for MemoryStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.incoming.state.lock().unwrap();
+        while state.bytes.is_empty() && !state.closed {
+            state = self.incoming.ready.wait(state).unwrap();
+        }
+        // Bytes written before the peer went away are still delivered
+        let n = buf.len().min(state.bytes.len());
+        for (dst, src) in buf.iter_mut().zip(state.bytes.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for MemoryStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.outgoing.state.lock().unwrap();
+        if state.closed {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "in-memory peer dropped"));
+        }
+        state.bytes.extend(buf);
+        self.outgoing.ready.notify_all();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for MemoryStream {
+    fn drop(&mut self) {
+        self.incoming.close();
+        self.outgoing.close();
+    }
+}
+
+/// Two `NetworkConnection`s joined by a `MemoryStream` pair, both using
+/// `match_secret` as if the DH exchange had agreed on it. Returned as
+/// `(host, client)`; neither has run the `BoardReady` handshake yet.
+pub fn connection_pair(match_secret: Option<Vec<u8>>) -> (NetworkConnection, NetworkConnection) {
+    let (host, client) = MemoryStream::pair();
+    (NetworkConnection::from_stream(host, match_secret.clone()), NetworkConnection::from_stream(client, match_secret))
+}
@@ -1,18 +1,392 @@
 use anyhow::Context;
 use serde_json;
-use std::io::{BufRead, BufReader, Read, Write};
-use std::net::{TcpListener, TcpStream};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use base64::{engine::general_purpose, Engine as _};
-use std::sync::{Arc, Mutex};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::error::NetworkError;
 
 // Helper trait object for boxed TLS streams that implement Read+Write
 trait ReadWrite: Read + Write {}
 impl<T: Read + Write> ReadWrite for T {}
 
+/// Write one frame: a 4-byte big-endian length followed by `payload`.
+fn write_frame<W: Write + ?Sized>(w: &mut W, payload: &[u8]) -> Result<(), NetworkError> {
+    let len = u32::try_from(payload.len()).map_err(|_| NetworkError::FrameTooLarge { len: payload.len(), max: u32::MAX as usize })?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(payload)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Length-prefix bit marking a heartbeat control frame: the low bits hold
+/// the kind and no payload follows. Data frames never set it, since their
+/// length is capped far below 2^31. Control frames are not envelopes, so
+/// they carry no sequence number or HMAC and never disturb game message
+/// ordering; TLS already keeps a third party from injecting them.
+const CONTROL_FRAME_BIT: u32 = 1 << 31;
+const CONTROL_PING: u32 = CONTROL_FRAME_BIT | 1;
+const CONTROL_PONG: u32 = CONTROL_FRAME_BIT | 2;
+
+/// A frame as seen by readers that understand control frames.
+enum Frame {
+    Data(Vec<u8>),
+    Control(u32),
+}
+
+fn write_control<W: Write + ?Sized>(w: &mut W, kind: u32) -> Result<(), NetworkError> {
+    w.write_all(&kind.to_be_bytes())?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Split the first whole frame off the front of `buf`, if there is one.
+fn take_frame(buf: &mut Vec<u8>, max_len: usize) -> Result<Option<Frame>, NetworkError> {
+    if buf.len() < 4 {
+        return Ok(None);
+    }
+    let word = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
+    if word & CONTROL_FRAME_BIT != 0 {
+        buf.drain(..4);
+        return Ok(Some(Frame::Control(word)));
+    }
+    let len = word as usize;
+    if len > max_len {
+        return Err(NetworkError::FrameTooLarge { len, max: max_len });
+    }
+    if buf.len() < 4 + len {
+        return Ok(None);
+    }
+    let payload = buf[4..4 + len].to_vec();
+    buf.drain(..4 + len);
+    Ok(Some(Frame::Data(payload)))
+}
+
+/// Default upper bound on a single incoming frame. Large enough for an
+/// enveloped receipt, small enough that a hostile peer cannot force the
+/// host to allocate gigabytes.
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Cap for the DH exchange frames, which only carry a base64 public key.
+const MAX_DH_FRAME_BYTES: usize = 4096;
+
+/// Read one frame written by `write_frame`. The announced length is checked
+/// against `max_len` before any payload buffer is allocated.
+pub(crate) fn read_frame<R: Read + ?Sized>(r: &mut R, max_len: usize) -> Result<Vec<u8>, NetworkError> {
+    let mut len_buf = [0u8; 4];
+    if let Err(e) = r.read_exact(&mut len_buf) {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            return Err(NetworkError::Closed);
+        }
+        return Err(e.into());
+    }
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len > max_len {
+        return Err(NetworkError::FrameTooLarge { len, max: max_len });
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// HMAC-SHA256 token for `env`, computed over its JSON encoding with
+/// `auth_token` cleared and returned as base64. Shared by both peers and
+/// the referee so all three agree on what is authenticated.
+pub fn envelope_auth_token(secret: &[u8], env: &crate::network_protocol::Envelope) -> Result<String, NetworkError> {
+    let mut tmp = env.clone();
+    tmp.auth_token = None;
+    let json_no_auth = serde_json::to_string(&tmp).map_err(|e| NetworkError::Encode(e.to_string()))?;
+    type HmacSha256 = Hmac<Sha256>;
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
+    mac.update(json_no_auth.as_bytes());
+    Ok(general_purpose::STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+/// Bind the DH-derived secret to both boards:
+/// `SHA-256(dh_shared || host_commit || client_commit)`. Applied by both
+/// handshakes once the `BoardReady` exchange completes, so envelopes from
+/// one match cannot authenticate in another even if the DH secret or the
+/// boards are later reused.
+pub fn bind_match_secret(dh_shared: &[u8], host_commit: &risc0_zkvm::sha::Digest, client_commit: &risc0_zkvm::sha::Digest) -> Vec<u8> {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(dh_shared);
+    ctx.update(host_commit.as_bytes());
+    ctx.update(client_commit.as_bytes());
+    ctx.finish().as_ref().to_vec()
+}
+
+/// Secret for the match after a rematch:
+/// `SHA-256("zk-battleship rematch v1" || secret)`. Rotating it means an
+/// envelope recorded in the previous match, whose `BoardReady` the client
+/// would otherwise adopt a match id from, no longer authenticates.
+pub fn rematch_secret(secret: &[u8]) -> Vec<u8> {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"zk-battleship rematch v1");
+    ctx.update(secret);
+    ctx.finish().as_ref().to_vec()
+}
+
+/// Commitment to a first-player coin nonce, sent in `BoardReady`:
+/// `SHA-256("zk-battleship coin v1" || nonce)`.
+pub fn coin_commitment(nonce: &[u8; 32]) -> risc0_zkvm::sha::Digest {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"zk-battleship coin v1");
+    ctx.update(nonce);
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(ctx.finish().as_ref());
+    risc0_zkvm::sha::Digest::from(bytes)
+}
+
+/// Outcome of the first-player coin flip: the host starts when the low bit
+/// of the XOR of both nonces is 0. XOR is symmetric, so both peers get the
+/// same answer whichever nonce they pass first, and since each nonce was
+/// committed before either was revealed, one uniform nonce is enough for a
+/// fair flip.
+pub fn coin_host_starts(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    (a[0] ^ b[0]) & 1 == 0
+}
+
+/// Keep-alive settings for `NetworkConnection::start_heartbeat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    /// How often to ping the peer.
+    pub interval: Duration,
+    /// How long the peer may stay silent before it is declared dropped.
+    /// Should span several `interval`s.
+    pub timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self { interval: Duration::from_secs(5), timeout: Duration::from_secs(20) }
+    }
+}
+
+/// Longest the heartbeat thread (or a split `Receiver`) holds the stream
+/// lock waiting for input, and so the longest a send waits behind it.
+const HEARTBEAT_POLL: Duration = Duration::from_millis(20);
+
+/// Running heartbeat: data frames read by the background thread, and the
+/// flag that stops it when the connection is dropped.
+struct Heartbeat {
+    frames: mpsc::Receiver<Result<Vec<u8>, NetworkError>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Body of the heartbeat thread. It owns all reading from `stream`: every
+/// `HEARTBEAT_POLL` it takes the lock, sends a ping when one is due,
+/// answers the peer's pings, and reads whatever has arrived. Data frames
+/// go to `frames` for `recv_framed`; anything at all from the peer counts
+/// as a sign of life. Ends by sending the error that stopped it.
+fn run_heartbeat(stream: Arc<Mutex<Box<dyn ReadWrite + Send>>>, config: HeartbeatConfig, max_len: usize, frames: mpsc::Sender<Result<Vec<u8>, NetworkError>>, stop: Arc<AtomicBool>) {
+    let mut buf = Vec::new();
+    let mut chunk = vec![0u8; 16 * 1024];
+    let mut last_heard = Instant::now();
+    let mut last_ping: Option<Instant> = None;
+    let mut pongs_owed = 0usize;
+    let err = 'run: loop {
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let read = {
+            let mut guard = stream.lock().unwrap();
+            if last_ping.is_none_or(|t| t.elapsed() >= config.interval) {
+                if let Err(e) = write_control(&mut **guard, CONTROL_PING) {
+                    break 'run e;
+                }
+                last_ping = Some(Instant::now());
+            }
+            for _ in 0..std::mem::take(&mut pongs_owed) {
+                if let Err(e) = write_control(&mut **guard, CONTROL_PONG) {
+                    break 'run e;
+                }
+            }
+            guard.read(&mut chunk)
+        };
+        match read {
+            Ok(0) => break NetworkError::Closed,
+            Ok(n) => {
+                buf.extend_from_slice(&chunk[..n]);
+                last_heard = Instant::now();
+            }
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut | std::io::ErrorKind::Interrupted) => {}
+            Err(e) => break e.into(),
+        }
+        loop {
+            match take_frame(&mut buf, max_len) {
+                Ok(Some(Frame::Data(payload))) => {
+                    if frames.send(Ok(payload)).is_err() {
+                        return;
+                    }
+                }
+                Ok(Some(Frame::Control(CONTROL_PING))) => pongs_owed += 1,
+                Ok(Some(Frame::Control(_))) => {}
+                Ok(None) => break,
+                Err(e) => break 'run e,
+            }
+        }
+        if last_heard.elapsed() > config.timeout {
+            break NetworkError::PeerTimedOut { silent_for: last_heard.elapsed() };
+        }
+    };
+    let _ = frames.send(Err(err));
+}
+
+/// Encode `payload` as envelope `seq` of `match_id`, adding its HMAC when
+/// there is a match secret.
+fn seal_envelope(match_id: uuid::Uuid, seq: u64, payload: &crate::network_protocol::GameMessage, secret: Option<&[u8]>) -> Result<Vec<u8>, NetworkError> {
+    let mut env = crate::network_protocol::Envelope::new(match_id, seq, payload.clone());
+    // If we have a match_secret, compute HMAC over the envelope (without auth_token)
+    if let Some(secret) = secret {
+        env.auth_token = Some(envelope_auth_token(secret, &env)?);
+    }
+    serde_json::to_vec(&env).map_err(|e| NetworkError::Encode(e.to_string()))
+}
+
+/// Parse an incoming frame and check its HMAC, match id and sequence
+/// number. The first match id seen is adopted if none is set yet;
+/// `expected_seq` advances only for an accepted envelope.
+fn open_envelope(frame: &[u8], secret: Option<&[u8]>, match_id: &mut Option<uuid::Uuid>, expected_seq: &mut u64) -> Result<crate::network_protocol::Envelope, NetworkError> {
+    let env: crate::network_protocol::Envelope = serde_json::from_slice(frame)
+        .map_err(|e| NetworkError::Malformed(format!("{} (raw={:?})", e, String::from_utf8_lossy(frame))))?;
+
+    // If we have a match_secret, validate the HMAC auth_token
+    if let Some(secret) = secret {
+        let expected_b64 = envelope_auth_token(secret, &env)?;
+        if env.auth_token.as_deref() != Some(expected_b64.as_str()) {
+            return Err(NetworkError::AuthTokenInvalid);
+        }
+    }
+
+    // If we don't yet have a match_id, accept the first one seen
+    let id = *match_id.get_or_insert(env.match_id);
+    if env.match_id != id {
+        return Err(NetworkError::MatchIdMismatch { expected: id, got: env.match_id });
+    }
+
+    // Validate sequence
+    if env.seq != *expected_seq {
+        return Err(NetworkError::UnexpectedSeq { expected: *expected_seq, got: env.seq });
+    }
+    *expected_seq = expected_seq.wrapping_add(1);
+
+    Ok(env)
+}
+
+/// Cap on incoming envelopes: at most `max_messages` in any `window`.
+/// Arrivals are counted before the HMAC is checked, so a flooding peer
+/// costs one frame read per message rather than an HMAC each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_messages: u32,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    pub fn per_second(max_messages: u32) -> Self {
+        Self { max_messages, window: Duration::from_secs(1) }
+    }
+
+    /// Record an arrival now in `arrivals` (the arrival times inside the
+    /// current window), failing once the limit is exceeded.
+    fn admit(&self, arrivals: &mut VecDeque<Instant>) -> Result<(), NetworkError> {
+        let now = Instant::now();
+        while arrivals.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            arrivals.pop_front();
+        }
+        if arrivals.len() >= self.max_messages as usize {
+            return Err(NetworkError::RateLimited { max_messages: self.max_messages, window: self.window });
+        }
+        arrivals.push_back(now);
+        Ok(())
+    }
+}
+
+/// How `handshake_as_host` picks the match id. Clients always adopt the
+/// id from the host's `BoardReady`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchIdStrategy {
+    /// A fresh random (v4) id for every match.
+    #[default]
+    Random,
+    /// A reproducible id from `derive_match_id`, for test transcripts or
+    /// to tie a match to an external identifier.
+    Derived { namespace: uuid::Uuid, nonce: u64 },
+}
+
+/// Name-based match id: the first 16 bytes of
+/// `SHA-256(namespace || host_commit || nonce as u64 LE)` as a version-8
+/// UUID. This is UUIDv5's construction with the protocol's SHA-256 in
+/// place of SHA-1. Only the host's placement commitment is included: the
+/// id travels in the host's `BoardReady`, before the client's commitment
+/// is known.
+pub fn derive_match_id(namespace: uuid::Uuid, host_commit: &risc0_zkvm::sha::Digest, nonce: u64) -> uuid::Uuid {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(namespace.as_bytes());
+    ctx.update(host_commit.as_bytes());
+    ctx.update(&nonce.to_le_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&ctx.finish().as_ref()[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+/// Strip the brackets from an IPv6 literal such as `[::1]`, leaving host
+/// names and IPv4 addresses unchanged.
+fn unbracket(host: &str) -> &str {
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host)
+}
+
+/// Open a TCP connection to `host:port`. `host` may be a name, an IPv4
+/// address, or an IPv6 address with or without brackets. Every resolved
+/// address is tried in order; the last error is returned if none connect.
+pub fn connect_tcp(host: &str, port: u16) -> anyhow::Result<TcpStream> {
+    let addrs: Vec<SocketAddr> = (unbracket(host), port).to_socket_addrs().with_context(|| format!("resolving {}", host))?.collect();
+    let mut last_err = None;
+    for addr in addrs {
+        match TcpStream::connect(addr) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(anyhow::Error::new(e).context(format!("connecting to {}", addr))),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("{} resolved to no addresses", host)))
+}
+
 // TLS via OpenSSL
-use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslConnector};
+use openssl::hash::MessageDigest;
+use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslConnector, SslRef, SslVerifyMode};
+use openssl::x509::X509Ref;
+use std::path::PathBuf;
+
+/// TLS settings for `NetworkConnection::host_with` and `connect_with`, as
+/// paths to PEM files. `host` and `connect` fill one in from the
+/// `BATTLE_*` environment variables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Our certificate: required when hosting, an optional client
+    /// certificate when connecting.
+    pub cert: Option<PathBuf>,
+    /// Private key for `cert`.
+    pub key: Option<PathBuf>,
+    /// CA the peer's certificate must chain to: required when connecting;
+    /// when hosting, used to check client certificates.
+    pub ca: Option<PathBuf>,
+    /// Hosting only: refuse clients that do not present a certificate
+    /// signed by `ca`.
+    pub require_client_cert: bool,
+}
 
 // Use ring for X25519/ECDH and SHA-256 digest for deriving match secret
 use ring::agreement::{EphemeralPrivateKey, agree_ephemeral, X25519, UnparsedPublicKey};
@@ -27,44 +401,214 @@ pub struct NetworkConnection {
     expected_seq: u64,
     /// Per-match secret derived via DH over the TLS channel
     match_secret: Option<Vec<u8>>,
+    /// Largest frame accepted by `recv_framed`; see `DEFAULT_MAX_MESSAGE_BYTES`.
+    max_message_bytes: usize,
+    /// Optional referee socket; every outgoing envelope is copied here
+    /// (see `set_referee` and `crate::referee::RefereeSession`).
+    referee: Option<Box<dyn Write + Send>>,
+    /// SHA-256 fingerprint of the certificate the peer presented during TLS, if any.
+    peer_fingerprint: Option<String>,
+    /// `(from, text)` of every `Chat` received, in order.
+    chat_log: Vec<(String, String)>,
+    /// How `handshake_as_host` picks the match id.
+    match_id_strategy: MatchIdStrategy,
+    /// Incoming envelope cap; `None` (the default) accepts any rate.
+    rate_limit: Option<RateLimit>,
+    /// Arrival times of the envelopes inside the current `rate_limit` window.
+    recent_arrivals: VecDeque<Instant>,
+    /// Our first-player coin nonce, committed to in our `BoardReady` and
+    /// consumed by `flip_first_player`.
+    coin_nonce: Option<[u8; 32]>,
+    /// The `coin_commitment` from the peer's `BoardReady`.
+    peer_coin_commitment: Option<risc0_zkvm::sha::Digest>,
+    /// Handle on the underlying TCP socket, for the read timeout the
+    /// heartbeat polls with; `None` for non-TCP streams.
+    socket: Option<TcpStream>,
+    /// Set by `start_heartbeat`; all reads then go through its thread.
+    heartbeat: Option<Heartbeat>,
+    /// Board parameters declared in our `BoardReady`; see `set_fleet_spec`.
+    fleet: core::FleetSpec,
+}
+
+/// SHA-256 fingerprint of a certificate's DER encoding, as colon-separated
+/// uppercase hex (the format printed by `openssl x509 -fingerprint -sha256`).
+pub fn certificate_fingerprint(cert: &X509Ref) -> anyhow::Result<String> {
+    let digest = cert.digest(MessageDigest::sha256()).context("hashing peer certificate")?;
+    Ok(digest.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":"))
+}
+
+fn peer_certificate_fingerprint(ssl: &SslRef) -> anyhow::Result<Option<String>> {
+    ssl.peer_certificate().map(|cert| certificate_fingerprint(&cert)).transpose()
 }
 
 impl NetworkConnection {
-    fn write_line(&self, s: &str) -> anyhow::Result<()> {
+    /// Send one length-prefixed frame (4-byte big-endian length, then
+    /// `payload`). Payloads may contain any bytes, including newlines.
+    pub fn send_framed(&self, payload: &[u8]) -> Result<(), NetworkError> {
         let mut guard = self.stream.lock().unwrap();
-        let writer: &mut dyn Write = &mut **guard;
-        writeln!(writer, "{}", s)?;
-        writer.flush()?;
-        Ok(())
+        write_frame(&mut **guard, payload)
     }
 
-    fn read_line(&self) -> anyhow::Result<String> {
+    /// Receive one length-prefixed frame sent by `send_framed`. Heartbeat
+    /// control frames are handled here and never returned: a ping is
+    /// answered with a pong.
+    pub fn recv_framed(&mut self) -> Result<Vec<u8>, NetworkError> {
+        if let Some(heartbeat) = &self.heartbeat {
+            return heartbeat.frames.recv().unwrap_or(Err(NetworkError::Closed));
+        }
         let mut guard = self.stream.lock().unwrap();
-        // Create a BufReader over the locked stream (temporary)
-        let reader = &mut **guard;
-        let mut buf = BufReader::new(reader);
-        let mut line = String::new();
-        let n = buf.read_line(&mut line)?;
-        if n == 0 {
-            anyhow::bail!("connection closed by peer (EOF)");
+        loop {
+            let mut word = [0u8; 4];
+            if let Err(e) = guard.read_exact(&mut word) {
+                if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                    return Err(NetworkError::Closed);
+                }
+                return Err(e.into());
+            }
+            let mut buf = word.to_vec();
+            match take_frame(&mut buf, self.max_message_bytes)? {
+                Some(Frame::Control(CONTROL_PING)) => write_control(&mut **guard, CONTROL_PONG)?,
+                Some(Frame::Control(_)) => {}
+                _ => {
+                    let len = u32::from_be_bytes(word) as usize;
+                    let mut payload = vec![0u8; len];
+                    guard.read_exact(&mut payload)?;
+                    return Ok(payload);
+                }
+            }
+        }
+    }
+
+    /// Start pinging the peer every `config.interval` from a background
+    /// thread, which from then on also does all reading so pings are
+    /// answered and the connection stays busy while the local player
+    /// thinks. If nothing arrives from the peer for `config.timeout`,
+    /// `recv_framed` (and so `receive_enveloped`) fails with
+    /// `NetworkError::PeerTimedOut`. Both peers should enable it: a peer
+    /// without a heartbeat only answers pings while it is reading. Pings
+    /// are control frames outside the envelope sequence (see
+    /// `CONTROL_FRAME_BIT`). Only TCP-backed connections are supported;
+    /// `max_message_bytes` is fixed at its current value. Calling this
+    /// again while running does nothing.
+    pub fn start_heartbeat(&mut self, config: HeartbeatConfig) -> Result<(), NetworkError> {
+        if self.heartbeat.is_some() {
+            return Ok(());
         }
-        Ok(line)
+        let socket = self.socket.as_ref().ok_or_else(|| NetworkError::Protocol("heartbeat needs a TCP-backed connection".into()))?;
+        socket.set_read_timeout(Some(HEARTBEAT_POLL.min(config.interval)))?;
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (stream, flag, max_len) = (self.stream.clone(), stop.clone(), self.max_message_bytes);
+        thread::spawn(move || run_heartbeat(stream, config, max_len, tx, flag));
+        self.heartbeat = Some(Heartbeat { frames: rx, stop });
+        Ok(())
+    }
+
+    /// Current cap on incoming frame size in bytes.
+    pub fn max_message_bytes(&self) -> usize {
+        self.max_message_bytes
+    }
+
+    /// Change the cap on incoming frame size. Frames announcing a larger
+    /// length are rejected with an error before their payload is read.
+    pub fn set_max_message_bytes(&mut self, limit: usize) {
+        self.max_message_bytes = limit;
+    }
+
+    /// Limit how fast the peer may send envelopes. Once more than
+    /// `max_messages` arrive within `window`, `receive_enveloped` fails and
+    /// the match should be abandoned. Normal play sends about one envelope
+    /// per turn, but a chunked proof arrives as a burst of
+    /// `ShotResultChunk`s, so leave headroom for those. `None` disables it.
+    pub fn set_rate_limit(&mut self, limit: Option<RateLimit>) {
+        self.rate_limit = limit;
+        self.recent_arrivals.clear();
+    }
+
+    /// Record an incoming envelope against `rate_limit`, failing once the
+    /// peer has exceeded it.
+    fn check_rate_limit(&mut self) -> Result<(), NetworkError> {
+        match self.rate_limit {
+            Some(limit) => limit.admit(&mut self.recent_arrivals),
+            None => Ok(()),
+        }
+    }
+
+    /// Board, fleet and rules to play (classic by default). Declared in our
+    /// `BoardReady`; the handshake fails with `FleetMismatch` if the peer
+    /// declares anything else.
+    pub fn set_fleet_spec(&mut self, fleet: core::FleetSpec) {
+        self.fleet = fleet;
+    }
+
+    /// The board parameters this connection plays.
+    pub fn fleet_spec(&self) -> &core::FleetSpec {
+        &self.fleet
+    }
+
+    /// Tell the peer why the handshake is being abandoned, then fail with
+    /// `FleetMismatch`.
+    fn reject_fleet(&mut self, theirs: core::FleetSpec) -> NetworkError {
+        let err = NetworkError::FleetMismatch { ours: Box::new(self.fleet.clone()), theirs: Box::new(theirs) };
+        let _ = self.send_enveloped(&crate::network_protocol::GameMessage::Error { message: err.to_string() });
+        err
+    }
+
+    /// Choose how `handshake_as_host` picks the match id (random by default).
+    pub fn set_match_id_strategy(&mut self, strategy: MatchIdStrategy) {
+        self.match_id_strategy = strategy;
+    }
+
+    /// Enable broadcast mode: from now on each envelope sent with
+    /// `send_enveloped` is also written, as the same frame, to `referee`.
+    /// The referee copy is written first so the referee always sees a
+    /// request before the peer can answer it.
+    pub fn set_referee<W: Write + Send + 'static>(&mut self, referee: W) {
+        self.referee = Some(Box::new(referee));
+    }
+
+    /// SHA-256 fingerprint (colon-separated hex) of the peer's TLS
+    /// certificate, so players can confirm who they are connected to. The
+    /// client always sees the host's certificate. The host sees the
+    /// client's only when it was started with
+    /// `NetworkConfig::require_client_cert`; otherwise client certificates
+    /// are not requested and it gets `None`, as do connections made with
+    /// `from_stream`.
+    pub fn peer_fingerprint(&self) -> Option<String> {
+        self.peer_fingerprint.clone()
+    }
+
+    /// The match id agreed in the handshake; `None` before it.
+    pub fn match_id(&self) -> Option<uuid::Uuid> {
+        self.match_id
+    }
+
+    /// Wrap an already-connected stream without performing TLS or the DH
+    /// exchange. Intended for tests and in-process transports; callers
+    /// supply the match secret directly if HMAC checks are wanted.
+    pub fn from_stream<S: Read + Write + Send + 'static>(stream: S, match_secret: Option<Vec<u8>>) -> Self {
+        let socket = (&stream as &dyn std::any::Any).downcast_ref::<TcpStream>().and_then(|s| s.try_clone().ok());
+        let boxed: Box<dyn ReadWrite + Send> = Box::new(stream);
+        Self { stream: Arc::new(Mutex::new(boxed)), match_id: None, next_seq: 0, expected_seq: 0, match_secret, max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES, referee: None, peer_fingerprint: None, chat_log: Vec::new(), match_id_strategy: MatchIdStrategy::Random, rate_limit: None, recent_arrivals: VecDeque::new(), coin_nonce: None, peer_coin_commitment: None, socket, heartbeat: None, fleet: core::FleetSpec::classic() }
     }
     // (OpenSSL) Helper: create an SslAcceptor for server side using cert/key and optional CA for client auth.
-    fn make_ssl_acceptor(cert_path: &str, key_path: &str, ca_path: Option<&str>) -> anyhow::Result<SslAcceptor> {
+    fn make_ssl_acceptor(cert_path: &Path, key_path: &Path, ca_path: Option<&Path>, require_client_cert: bool) -> anyhow::Result<SslAcceptor> {
         let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).context("creating ssl acceptor")?;
         builder.set_certificate_file(cert_path, SslFiletype::PEM).context("set cert file")?;
         builder.set_private_key_file(key_path, SslFiletype::PEM).context("set key file")?;
-        // Server-only TLS: load CA if provided for verification, but do NOT require client certs.
-        // This keeps the server as the authenticating party, while allowing clients without certs.
+        // By default the TLS is server-only: the CA is loaded if provided, but clients without certs are
+        // allowed, keeping the server as the authenticating party. `require_client_cert` turns on mTLS.
         if let Some(ca) = ca_path {
             builder.set_ca_file(ca).context("set ca file")?;
-            // Do not call set_verify with FAIL_IF_NO_PEER_CERT — we intentionally avoid requiring client certs.
+        }
+        if require_client_cert {
+            anyhow::ensure!(ca_path.is_some(), "require_client_cert needs a CA to check client certificates against");
+            builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
         }
         Ok(builder.build())
     }
 
-    fn make_ssl_connector(ca_path: &str, client_cert: Option<&str>, client_key: Option<&str>) -> anyhow::Result<SslConnector> {
+    fn make_ssl_connector(ca_path: &Path, client_cert: Option<&Path>, client_key: Option<&Path>) -> anyhow::Result<SslConnector> {
         let mut builder = SslConnector::builder(SslMethod::tls()).context("creating ssl connector")?;
         builder.set_ca_file(ca_path).context("set ca file")?;
         if let (Some(cert), Some(key)) = (client_cert, client_key) {
@@ -83,13 +627,10 @@ impl NetworkConnection {
     let pub_b64 = general_purpose::STANDARD.encode(my_pub.as_ref());
 
         if initiator {
-            let req = serde_json::to_string(&serde_json::json!({"dh_pub": pub_b64}))?;
-            writeln!(stream, "{}", req)?;
-            stream.flush()?;
-            let mut reader = BufReader::new(&mut *stream);
-            let mut line = String::new();
-            reader.read_line(&mut line)?;
-            let v: serde_json::Value = serde_json::from_str(&line)?;
+            let req = serde_json::to_vec(&serde_json::json!({"dh_pub": pub_b64}))?;
+            write_frame(stream, &req)?;
+            let frame = read_frame(stream, MAX_DH_FRAME_BYTES)?;
+            let v: serde_json::Value = serde_json::from_slice(&frame)?;
             let peer_b64 = v.get("dh_pub").and_then(|x| x.as_str()).ok_or_else(|| anyhow::anyhow!("missing dh_pub"))?;
             let peer_bytes = general_purpose::STANDARD.decode(peer_b64)?;
             let peer_pub = UnparsedPublicKey::new(&X25519, peer_bytes);
@@ -100,15 +641,12 @@ impl NetworkConnection {
             // Derive secret fingerprint for internal use (not logged)
             return Ok(shared);
         } else {
-            let mut reader = BufReader::new(&mut *stream);
-            let mut line = String::new();
-            reader.read_line(&mut line)?;
-            let v: serde_json::Value = serde_json::from_str(&line)?;
+            let frame = read_frame(stream, MAX_DH_FRAME_BYTES)?;
+            let v: serde_json::Value = serde_json::from_slice(&frame)?;
             let peer_b64 = v.get("dh_pub").and_then(|x| x.as_str()).ok_or_else(|| anyhow::anyhow!("missing dh_pub"))?;
             let peer_bytes = general_purpose::STANDARD.decode(peer_b64)?;
-            let req = serde_json::to_string(&serde_json::json!({"dh_pub": pub_b64}))?;
-            writeln!(stream, "{}", req)?;
-            stream.flush()?;
+            let req = serde_json::to_vec(&serde_json::json!({"dh_pub": pub_b64}))?;
+            write_frame(stream, &req)?;
             let peer_pub = UnparsedPublicKey::new(&X25519, peer_bytes);
             let shared = agree_ephemeral(my_private, &peer_pub, |shared| {
                 let d = digest::digest(&digest::SHA256, shared);
@@ -119,30 +657,58 @@ impl NetworkConnection {
         }
     }
     /// Host: Create a server and wait for connection
-    /// Host: Create a TLS server and wait for an incoming connection.
+    /// Host: Create a TLS server on `bind` (IPv4 or IPv6, e.g. `0.0.0.0:7878`
+    /// or `[::1]:7878`) and wait for an incoming connection.
     ///
     /// TLS parameters are loaded from environment variables:
     /// - BATTLE_SERVER_CERT: path to server cert (PEM)
     /// - BATTLE_SERVER_KEY: path to server private key (PEM pkcs8 or rsa)
-    /// - BATTLE_CA_CERT: path to CA cert used to validate client certs (optional; if provided, client certs are required)
-    pub fn host(port: u16) -> anyhow::Result<Self> {
-        println!("🌐 Starting TLS server on port {}...", port);
-        let listener = TcpListener::bind(format!("0.0.0.0:{}", port))?;
+    /// - BATTLE_CA_CERT: path to CA cert used to validate client certs (optional; client certs are not required)
+    ///
+    /// Use `host_with` to pass them directly instead.
+    pub fn host(bind: SocketAddr) -> anyhow::Result<Self> {
+        Self::host_with(bind, &Self::server_config_from_env()?)
+    }
+
+    /// Like `host`, but accepts on an already-bound listener.
+    pub fn host_on(listener: TcpListener) -> anyhow::Result<Self> {
+        Self::host_on_with(listener, &Self::server_config_from_env()?)
+    }
+
+    fn server_config_from_env() -> anyhow::Result<NetworkConfig> {
+        let cert = std::env::var("BATTLE_SERVER_CERT").context("BATTLE_SERVER_CERT not set")?;
+        let key = std::env::var("BATTLE_SERVER_KEY").context("BATTLE_SERVER_KEY not set")?;
+        let ca = std::env::var("BATTLE_CA_CERT").ok();
+        Ok(NetworkConfig { cert: Some(cert.into()), key: Some(key.into()), ca: ca.map(PathBuf::from), require_client_cert: false })
+    }
+
+    /// Like `host`, with the TLS settings given by `config` rather than
+    /// read from the environment. `config.cert` and `config.key` are
+    /// required.
+    pub fn host_with(bind: SocketAddr, config: &NetworkConfig) -> anyhow::Result<Self> {
+        println!("🌐 Starting TLS server on {}...", bind);
+        let listener = TcpListener::bind(bind).with_context(|| format!("binding {}", bind))?;
+        Self::host_on_with(listener, config)
+    }
+
+    /// Like `host_with`, but accepts on an already-bound listener.
+    pub fn host_on_with(listener: TcpListener, config: &NetworkConfig) -> anyhow::Result<Self> {
+        let cert_path = config.cert.as_deref().context("hosting needs a server certificate (NetworkConfig::cert)")?;
+        let key_path = config.key.as_deref().context("hosting needs a server key (NetworkConfig::key)")?;
+        let acceptor = Self::make_ssl_acceptor(cert_path, key_path, config.ca.as_deref(), config.require_client_cert)?;
+
         println!("⏳ Waiting for opponent to connect...");
         let (tcp_stream, addr) = listener.accept()?;
+        let socket = tcp_stream.try_clone().ok();
         println!("✓ Opponent connected from: {}", addr);
 
-        // Load server cert/key from env
-        let cert_path = std::env::var("BATTLE_SERVER_CERT").context("BATTLE_SERVER_CERT not set")?;
-        let key_path = std::env::var("BATTLE_SERVER_KEY").context("BATTLE_SERVER_KEY not set")?;
-        let ca_path = std::env::var("BATTLE_CA_CERT").ok();
-
-        let acceptor = Self::make_ssl_acceptor(&cert_path, &key_path, ca_path.as_deref())?;
         let mut tls_stream = acceptor.accept(tcp_stream).context("accepting ssl")?;
+        // Client certs are only requested with `require_client_cert`, so this is normally None
+        let peer_fingerprint = peer_certificate_fingerprint(tls_stream.ssl())?;
         // After TLS handshake completes, perform X25519 DH over the encrypted channel to derive match_secret
         let secret = Self::perform_tls_handshake_and_dh(&mut tls_stream, false)?;
         let boxed: Box<dyn ReadWrite + Send> = Box::new(tls_stream);
-    let nc = Self { stream: Arc::new(Mutex::new(boxed)), match_id: None, next_seq: 0, expected_seq: 0, match_secret: Some(secret) };
+    let nc = Self { stream: Arc::new(Mutex::new(boxed)), match_id: None, next_seq: 0, expected_seq: 0, match_secret: Some(secret), max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES, referee: None, peer_fingerprint, chat_log: Vec::new(), match_id_strategy: MatchIdStrategy::Random, rate_limit: None, recent_arrivals: VecDeque::new(), coin_nonce: None, peer_coin_commitment: None, socket, heartbeat: None, fleet: core::FleetSpec::classic() };
         // No persisted match id yet; return connection
         Ok(nc)
     }
@@ -153,129 +719,413 @@ impl NetworkConnection {
     /// - BATTLE_CLIENT_CERT: path to client cert (PEM) (optional)
     /// - BATTLE_CLIENT_KEY: path to client key (PEM) (optional)
     /// - BATTLE_CA_CERT: path to CA cert to validate server cert (required)
+    ///
+    /// Use `connect_with` to pass them directly instead.
     pub fn connect(host: &str, port: u16) -> anyhow::Result<Self> {
+        let ca = std::env::var("BATTLE_CA_CERT").context("BATTLE_CA_CERT must be set to validate server cert")?;
+        let cert = std::env::var("BATTLE_CLIENT_CERT").ok();
+        let key = std::env::var("BATTLE_CLIENT_KEY").ok();
+        Self::connect_with(host, port, &NetworkConfig { cert: cert.map(PathBuf::from), key: key.map(PathBuf::from), ca: Some(ca.into()), require_client_cert: false })
+    }
+
+    /// Like `connect`, with the TLS settings given by `config` rather than
+    /// read from the environment. `config.ca` is required; `config.cert`
+    /// and `config.key`, if both set, are presented as a client
+    /// certificate. `require_client_cert` is ignored.
+    pub fn connect_with(host: &str, port: u16, config: &NetworkConfig) -> anyhow::Result<Self> {
+        let ca_path = config.ca.as_deref().context("connecting needs a CA to validate the server certificate (NetworkConfig::ca)")?;
+        let connector = Self::make_ssl_connector(ca_path, config.cert.as_deref(), config.key.as_deref())?;
+
         println!("🌐 Connecting to {}:{}...", host, port);
-        let tcp = TcpStream::connect(format!("{}:{}", host, port))?;
+        let tcp = connect_tcp(host, port)?;
+        let socket = tcp.try_clone().ok();
         println!("✓ TCP connection established");
 
-        let ca_path = std::env::var("BATTLE_CA_CERT").context("BATTLE_CA_CERT must be set to validate server cert")?;
-        let client_cert = std::env::var("BATTLE_CLIENT_CERT").ok();
-        let client_key = std::env::var("BATTLE_CLIENT_KEY").ok();
-
-        let connector = Self::make_ssl_connector(&ca_path, client_cert.as_deref(), client_key.as_deref())?;
-        let mut tls_stream = connector.connect(host, tcp).context("connecting ssl")?;
+        let mut tls_stream = connector.connect(unbracket(host), tcp).context("connecting ssl")?;
+        let peer_fingerprint = peer_certificate_fingerprint(tls_stream.ssl())?;
         // DH exchange (client initiates)
         let secret = Self::perform_tls_handshake_and_dh(&mut tls_stream, true)?;
         let boxed: Box<dyn ReadWrite + Send> = Box::new(tls_stream);
-        let nc = Self { stream: Arc::new(Mutex::new(boxed)), match_id: None, next_seq: 0, expected_seq: 0, match_secret: Some(secret) };
+        let nc = Self { stream: Arc::new(Mutex::new(boxed)), match_id: None, next_seq: 0, expected_seq: 0, match_secret: Some(secret), max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES, referee: None, peer_fingerprint, chat_log: Vec::new(), match_id_strategy: MatchIdStrategy::Random, rate_limit: None, recent_arrivals: VecDeque::new(), coin_nonce: None, peer_coin_commitment: None, socket, heartbeat: None, fleet: core::FleetSpec::classic() };
         Ok(nc)
     }
 
-    /// Host-side handshake: generate match_id, send our BoardReady, then
-    /// receive opponent's BoardReady. Returns (opponent_name, opponent_commit, opponent_proof)
-    pub fn handshake_as_host(&mut self, player_name: &str, commitment: risc0_zkvm::sha::Digest, proof: Option<crate::network_protocol::ProofData>) -> anyhow::Result<(String, risc0_zkvm::sha::Digest, Option<crate::network_protocol::ProofData>)> {
+    /// Like `connect`, but retries the TCP connect and TLS handshake up to
+    /// `attempts` times in all, sleeping `backoff` after the first failure
+    /// and doubling the wait after each further one. For peers that start
+    /// at about the same time, when the host may not be listening yet.
+    pub fn connect_with_retry(host: &str, port: u16, attempts: u32, backoff: Duration) -> anyhow::Result<Self> {
+        // A missing CA is a configuration problem no retry will fix
+        std::env::var("BATTLE_CA_CERT").context("BATTLE_CA_CERT must be set to validate server cert")?;
+        let mut delay = backoff;
+        let mut attempt = 1;
+        loop {
+            match Self::connect(host, port) {
+                Ok(nc) => return Ok(nc),
+                Err(e) if attempt < attempts => {
+                    println!("⚠ Attempt {}/{} failed: {:#}; retrying in {:?}", attempt, attempts, e, delay);
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.context(format!("giving up after {} attempts", attempt))),
+            }
+        }
+    }
+
+    /// Host-side handshake: pick the match_id (see `set_match_id_strategy`), send our BoardReady, then
+    /// receive opponent's BoardReady. `commitment` is our placement commitment and
+    /// `state_commitment` the full commit of our starting board (see `GameMessage::BoardReady`).
+    /// Returns (opponent_name, opponent_commitment, opponent_state_commitment, opponent_proof)
+    /// Afterwards the match secret is rebound to both commitments (`bind_match_secret`).
+    /// Fails with `FleetMismatch`, after telling the peer in an `Error`, if the
+    /// opponent declares other board parameters (see `set_fleet_spec`).
+    pub fn handshake_as_host(&mut self, player_name: &str, commitment: risc0_zkvm::sha::Digest, state_commitment: risc0_zkvm::sha::Digest, proof: Option<crate::network_protocol::SetupProof>) -> Result<(String, risc0_zkvm::sha::Digest, risc0_zkvm::sha::Digest, Option<crate::network_protocol::SetupProof>), NetworkError> {
         use crate::network_protocol::GameMessage;
-        let match_id = uuid::Uuid::new_v4();
+        let match_id = match self.match_id_strategy {
+            MatchIdStrategy::Random => uuid::Uuid::new_v4(),
+            MatchIdStrategy::Derived { namespace, nonce } => derive_match_id(namespace, &commitment, nonce),
+        };
         self.match_id = Some(match_id);
 
-    let msg = GameMessage::BoardReady { commitment, player_name: player_name.to_string(), proof };
+    let msg = GameMessage::BoardReady { commitment, state_commitment, player_name: player_name.to_string(), proof, coin_commitment: self.new_coin_commitment(), fleet: self.fleet.clone() };
     // Use send_enveloped so the message is HMAC-authenticated when match_secret is present.
     self.send_enveloped(&msg)?;
 
         // Wait for opponent's BoardReady
         let resp = self.receive_enveloped()?;
-        if let crate::network_protocol::GameMessage::BoardReady { commitment: opp_commit, player_name: opp_name, proof: opp_proof } = resp.payload {
-            Ok((opp_name, opp_commit, opp_proof))
+        if let crate::network_protocol::GameMessage::BoardReady { commitment: opp_commit, state_commitment: opp_state, player_name: opp_name, proof: opp_proof, coin_commitment: opp_coin, fleet: opp_fleet } = resp.payload {
+            if opp_fleet != self.fleet {
+                return Err(self.reject_fleet(opp_fleet));
+            }
+            self.peer_coin_commitment = Some(opp_coin);
+            self.match_secret = self.match_secret.take().map(|s| bind_match_secret(&s, &commitment, &opp_commit));
+            Ok((opp_name, opp_commit, opp_state, opp_proof))
+        } else if let GameMessage::Error { message } = resp.payload {
+            Err(NetworkError::Protocol(format!("opponent rejected the handshake: {}", message)))
         } else {
-            anyhow::bail!("expected BoardReady from opponent during handshake")
+            Err(NetworkError::Protocol("expected BoardReady from opponent during handshake".into()))
         }
     }
 
     /// Client-side handshake: receive host's BoardReady to set match_id, then send ours.
-    pub fn handshake_as_client(&mut self, player_name: &str, commitment: risc0_zkvm::sha::Digest, proof: Option<crate::network_protocol::ProofData>) -> anyhow::Result<(String, risc0_zkvm::sha::Digest, Option<crate::network_protocol::ProofData>)> {
+    /// Arguments, return value and the `FleetMismatch` check are as for `handshake_as_host`.
+    /// Afterwards the match secret is rebound to both commitments (`bind_match_secret`).
+    pub fn handshake_as_client(&mut self, player_name: &str, commitment: risc0_zkvm::sha::Digest, state_commitment: risc0_zkvm::sha::Digest, proof: Option<crate::network_protocol::SetupProof>) -> Result<(String, risc0_zkvm::sha::Digest, risc0_zkvm::sha::Digest, Option<crate::network_protocol::SetupProof>), NetworkError> {
         use crate::network_protocol::GameMessage;
         // Receive host's initial BoardReady
         let env = self.receive_enveloped()?;
-        if let crate::network_protocol::GameMessage::BoardReady { commitment: host_commit, player_name: host_name, proof: host_proof } = env.payload {
+        if let crate::network_protocol::GameMessage::BoardReady { commitment: host_commit, state_commitment: host_state, player_name: host_name, proof: host_proof, coin_commitment: host_coin, fleet: host_fleet } = env.payload {
             // adopt match id from host
             self.match_id = Some(env.match_id);
+            if host_fleet != self.fleet {
+                return Err(self.reject_fleet(host_fleet));
+            }
+            self.peer_coin_commitment = Some(host_coin);
             // send our BoardReady reply using send_enveloped so it contains an auth token when required
-            let msg = GameMessage::BoardReady { commitment, player_name: player_name.to_string(), proof };
+            let msg = GameMessage::BoardReady { commitment, state_commitment, player_name: player_name.to_string(), proof, coin_commitment: self.new_coin_commitment(), fleet: self.fleet.clone() };
             self.send_enveloped(&msg)?;
-            Ok((host_name, host_commit, host_proof))
+            self.match_secret = self.match_secret.take().map(|s| bind_match_secret(&s, &host_commit, &commitment));
+            Ok((host_name, host_commit, host_state, host_proof))
         } else {
-            anyhow::bail!("expected BoardReady from host during handshake")
+            Err(NetworkError::Protocol("expected BoardReady from host during handshake".into()))
+        }
+    }
+
+    /// Pick a fresh coin nonce for this match and return its commitment.
+    fn new_coin_commitment(&mut self) -> risc0_zkvm::sha::Digest {
+        let nonce: [u8; 32] = rand::random();
+        self.coin_nonce = Some(nonce);
+        coin_commitment(&nonce)
+    }
+
+    /// Decide who shoots first, after either handshake: reveal our coin
+    /// nonce, check the peer's reveal against the commitment from its
+    /// `BoardReady`, and combine both with `coin_host_starts`. Returns
+    /// whether the host starts; both peers get the same answer. A reveal
+    /// that does not open the commitment fails with `CoinRevealMismatch`.
+    pub fn flip_first_player(&mut self) -> Result<bool, NetworkError> {
+        use crate::network_protocol::GameMessage;
+        let (nonce, peer_commitment) = match (self.coin_nonce.take(), self.peer_coin_commitment.take()) {
+            (Some(n), Some(c)) => (n, c),
+            _ => return Err(NetworkError::Protocol("coin flip requires a completed BoardReady handshake".into())),
+        };
+        self.send_enveloped(&GameMessage::CoinReveal { nonce })?;
+        match self.receive_enveloped()?.payload {
+            GameMessage::CoinReveal { nonce: peer_nonce } => {
+                if coin_commitment(&peer_nonce) != peer_commitment {
+                    return Err(NetworkError::CoinRevealMismatch);
+                }
+                Ok(coin_host_starts(&nonce, &peer_nonce))
+            }
+            _ => Err(NetworkError::Protocol("expected CoinReveal after handshake".into())),
+        }
+    }
+
+    /// Ask the peer for another match after `GameOver`. Returns true once
+    /// the peer accepts (or sent its own `RematchRequest` at the same time),
+    /// with the connection reset by `reset_for_rematch`; false if it
+    /// answers anything else or hangs up.
+    pub fn request_rematch(&mut self) -> Result<bool, NetworkError> {
+        use crate::network_protocol::GameMessage;
+        self.send_enveloped(&GameMessage::RematchRequest)?;
+        match self.receive_enveloped() {
+            Ok(env) => match env.payload {
+                GameMessage::RematchAccept | GameMessage::RematchRequest => {
+                    self.reset_for_rematch();
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
+            Err(NetworkError::Closed) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Answer a received `RematchRequest` with `RematchAccept` and reset
+    /// the connection for the new match.
+    pub fn accept_rematch(&mut self) -> Result<(), NetworkError> {
+        self.send_enveloped(&crate::network_protocol::GameMessage::RematchAccept)?;
+        self.reset_for_rematch();
+        Ok(())
+    }
+
+    /// Start a new match on this connection: forget the match id and coin
+    /// state, restart both sequence counters at 0 and rotate the match
+    /// secret (`rematch_secret`). The next handshake picks a fresh match
+    /// id; a `Derived` strategy moves on to the next nonce so the id
+    /// differs even if both boards are reused.
+    fn reset_for_rematch(&mut self) {
+        self.match_id = None;
+        self.next_seq = 0;
+        self.expected_seq = 0;
+        self.coin_nonce = None;
+        self.peer_coin_commitment = None;
+        self.match_secret = self.match_secret.take().map(|s| rematch_secret(&s));
+        if let MatchIdStrategy::Derived { nonce, .. } = &mut self.match_id_strategy {
+            *nonce = nonce.wrapping_add(1);
         }
     }
 
     /// Send a message
     /// Send a message wrapped in an Envelope (match_id + seq).
-    pub fn send_enveloped(&mut self, payload: &crate::network_protocol::GameMessage) -> anyhow::Result<()> {
-        use crate::network_protocol::Envelope;
+    pub fn send_enveloped(&mut self, payload: &crate::network_protocol::GameMessage) -> Result<(), NetworkError> {
         // Ensure we have a match_id; the caller should set it during handshake.
         let match_id = if let Some(id) = self.match_id { id } else { uuid::Uuid::new_v4() };
-        let mut env = Envelope::new(match_id, self.next_seq, payload.clone());
-        // If we have a match_secret, compute HMAC over the envelope (without auth_token)
-        if let Some(secret) = &self.match_secret {
-            let mut tmp = env.clone();
-            tmp.auth_token = None;
-            let json_no_auth = serde_json::to_string(&tmp)?;
-            type HmacSha256 = Hmac<Sha256>;
-            let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
-            mac.update(json_no_auth.as_bytes());
-            let result = mac.finalize().into_bytes();
-            let token = general_purpose::STANDARD.encode(&result);
-            // no debug logging in production
-            env.auth_token = Some(token);
-        }
-        let json = serde_json::to_string(&env)?;
-        self.write_line(&json)?;
+        let json = seal_envelope(match_id, self.next_seq, payload, self.match_secret.as_deref())?;
+        if let Some(referee) = self.referee.as_mut() {
+            write_frame(referee, &json)?;
+        }
+        self.send_framed(&json)?;
         self.next_seq = self.next_seq.wrapping_add(1);
         Ok(())
     }
 
+    /// Send a proof as a sequence of `ShotResultChunk` envelopes rather than
+    /// one large line. The `ProofData` is bincode-serialized and split into
+    /// `PROOF_CHUNK_SIZE` pieces; pair with `receive_large_proof` on the peer.
+    pub fn send_large_proof(&mut self, pd: &crate::network_protocol::ProofData) -> Result<(), NetworkError> {
+        use crate::network_protocol::{GameMessage, MAX_PROOF_BYTES, PROOF_CHUNK_SIZE};
+        let bytes = bincode::serialize(pd).map_err(|e| NetworkError::Encode(format!("serializing ProofData for chunking: {}", e)))?;
+        if bytes.len() > MAX_PROOF_BYTES {
+            return Err(NetworkError::Encode(format!("ProofData is {} bytes, over the {} byte limit", bytes.len(), MAX_PROOF_BYTES)));
+        }
+        let total = bytes.len().div_ceil(PROOF_CHUNK_SIZE) as u32;
+        for (index, data) in bytes.chunks(PROOF_CHUNK_SIZE).enumerate() {
+            let msg = GameMessage::ShotResultChunk { index: index as u32, total, data: data.to_vec() };
+            self.send_enveloped(&msg)?;
+        }
+        Ok(())
+    }
+
+    /// Receive a chunked proof sent with `send_large_proof`, verifying each
+    /// envelope as usual and returning the reassembled `ProofData`.
+    pub fn receive_large_proof(&mut self) -> Result<crate::network_protocol::ProofData, NetworkError> {
+        use crate::network_protocol::{GameMessage, ProofReassembler};
+        let mut reassembler = ProofReassembler::new();
+        loop {
+            let env = self.receive_enveloped()?;
+            match env.payload {
+                GameMessage::ShotResultChunk { index, total, data } => {
+                    if let Some(pd) = reassembler.push(index, total, data)? {
+                        return Ok(pd);
+                    }
+                }
+                _ => return Err(NetworkError::Protocol("expected ShotResultChunk while receiving chunked proof".into())),
+            }
+        }
+    }
+
+    /// Send a `Chat` message. It takes the next sequence number like any
+    /// other envelope, so it can be sent between (or during) turns.
+    pub fn send_chat(&mut self, from: &str, text: &str) -> Result<(), NetworkError> {
+        self.send_enveloped(&crate::network_protocol::GameMessage::Chat { from: from.to_string(), text: text.to_string() })
+    }
+
+    /// Chat messages received so far as `(from, text)`, oldest first.
+    pub fn chat_log(&self) -> &[(String, String)] {
+        &self.chat_log
+    }
+
     /// Receive a message (blocking)
     /// Receive an enveloped message and verify match_id and sequence number.
-    pub fn receive_enveloped(&mut self) -> anyhow::Result<crate::network_protocol::Envelope> {
-        let line = self.read_line()?;
-        let env: crate::network_protocol::Envelope = serde_json::from_str(&line)
-            .with_context(|| format!("failed to parse incoming envelope (raw={:?})", line))?;
-
-        // If we have a match_secret, validate the HMAC auth_token
-        if let Some(secret) = &self.match_secret {
-            let mut tmp = env.clone();
-            let token = tmp.auth_token.clone();
-            tmp.auth_token = None;
-            let json_no_auth = serde_json::to_string(&tmp)?;
-            type HmacSha256 = Hmac<Sha256>;
-            let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC can take key of any size");
-            mac.update(json_no_auth.as_bytes());
-            let expected = mac.finalize().into_bytes();
-            let expected_b64 = general_purpose::STANDARD.encode(&expected);
-            // no debug logging in production
-            if token.is_none() || token.unwrap() != expected_b64 {
-                anyhow::bail!("auth token missing or invalid");
+    /// `Chat` envelopes are verified the same way, then printed, logged
+    /// (see `chat_log`) and skipped; the next non-chat envelope is returned.
+    pub fn receive_enveloped(&mut self) -> Result<crate::network_protocol::Envelope, NetworkError> {
+        loop {
+            let env = self.receive_one_enveloped()?;
+            match env.payload {
+                crate::network_protocol::GameMessage::Chat { from, text } => {
+                    println!("💬 {}: {}", from, text);
+                    self.chat_log.push((from, text));
+                }
+                _ => return Ok(env),
             }
         }
+    }
 
-        // If we don't yet have a match_id, accept the first one seen
-        if self.match_id.is_none() {
-            self.match_id = Some(env.match_id);
+    fn receive_one_enveloped(&mut self) -> Result<crate::network_protocol::Envelope, NetworkError> {
+        let frame = self.recv_framed()?;
+        self.check_rate_limit()?;
+        open_envelope(&frame, self.match_secret.as_deref(), &mut self.match_id, &mut self.expected_seq)
+    }
+
+    /// Split into independent halves so one thread can send (chat, a
+    /// background status sender) while another blocks in
+    /// `Receiver::receive_enveloped`. Each half takes the state it needs:
+    /// the `Sender` the outgoing sequence number and referee copy, the
+    /// `Receiver` the expected sequence number, rate limit, chat log and
+    /// any running heartbeat. Split after the handshake, since both halves
+    /// keep the match id and secret it settled; a rematch needs an unsplit
+    /// connection.
+    ///
+    /// A TLS stream cannot be split into two independently usable halves:
+    /// OpenSSL keeps one session state for both directions, and reading can
+    /// need to write (and vice versa) during renegotiation or alerts. So
+    /// both halves still share the one locked stream, but the `Receiver`
+    /// never blocks while holding it: the TCP socket gets a short read
+    /// timeout (`HEARTBEAT_POLL`) and the receiver polls, releasing the
+    /// lock between polls and stepping aside while a send is waiting. Reads
+    /// keep partial frames in the receiver's own buffer, so a timeout in
+    /// the middle of a frame loses nothing. Streams other than TCP (see
+    /// `from_stream`) cannot time out; their receiver blocks under the lock
+    /// as an unsplit connection does.
+    pub fn split(self) -> Result<(Sender, Receiver), NetworkError> {
+        if let (Some(socket), None) = (&self.socket, &self.heartbeat) {
+            socket.set_read_timeout(Some(HEARTBEAT_POLL))?;
+        }
+        let sends_waiting = Arc::new(AtomicUsize::new(0));
+        let sender = Sender { stream: self.stream.clone(), match_id: self.match_id, next_seq: self.next_seq, match_secret: self.match_secret.clone(), referee: self.referee, sends_waiting: sends_waiting.clone() };
+        let receiver = Receiver { stream: self.stream, match_id: self.match_id, expected_seq: self.expected_seq, match_secret: self.match_secret, max_message_bytes: self.max_message_bytes, chat_log: self.chat_log, rate_limit: self.rate_limit, recent_arrivals: self.recent_arrivals, heartbeat: self.heartbeat, buf: Vec::new(), sends_waiting };
+        Ok((sender, receiver))
+    }
+}
+
+/// Sending half of a `NetworkConnection`, from `NetworkConnection::split`.
+pub struct Sender {
+    stream: Arc<Mutex<Box<dyn ReadWrite + Send>>>,
+    match_id: Option<uuid::Uuid>,
+    next_seq: u64,
+    match_secret: Option<Vec<u8>>,
+    referee: Option<Box<dyn Write + Send>>,
+    /// Sends waiting for the stream lock; the `Receiver` yields to them.
+    sends_waiting: Arc<AtomicUsize>,
+}
+
+impl Sender {
+    /// As `NetworkConnection::send_framed`.
+    pub fn send_framed(&self, payload: &[u8]) -> Result<(), NetworkError> {
+        self.sends_waiting.fetch_add(1, Ordering::SeqCst);
+        let mut guard = self.stream.lock().unwrap();
+        self.sends_waiting.fetch_sub(1, Ordering::SeqCst);
+        write_frame(&mut **guard, payload)
+    }
+
+    /// As `NetworkConnection::send_enveloped`.
+    pub fn send_enveloped(&mut self, payload: &crate::network_protocol::GameMessage) -> Result<(), NetworkError> {
+        let match_id = self.match_id.ok_or_else(|| NetworkError::Protocol("split before the handshake set a match id".into()))?;
+        let json = seal_envelope(match_id, self.next_seq, payload, self.match_secret.as_deref())?;
+        if let Some(referee) = self.referee.as_mut() {
+            write_frame(referee, &json)?;
         }
+        self.send_framed(&json)?;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        Ok(())
+    }
 
-        // Validate match id
-        if let Some(id) = self.match_id {
-            if env.match_id != id {
-                anyhow::bail!("mismatched match_id: expected {} got {}", id, env.match_id);
+    /// As `NetworkConnection::send_chat`.
+    pub fn send_chat(&mut self, from: &str, text: &str) -> Result<(), NetworkError> {
+        self.send_enveloped(&crate::network_protocol::GameMessage::Chat { from: from.to_string(), text: text.to_string() })
+    }
+}
+
+/// Receiving half of a `NetworkConnection`, from `NetworkConnection::split`.
+pub struct Receiver {
+    stream: Arc<Mutex<Box<dyn ReadWrite + Send>>>,
+    match_id: Option<uuid::Uuid>,
+    expected_seq: u64,
+    match_secret: Option<Vec<u8>>,
+    max_message_bytes: usize,
+    chat_log: Vec<(String, String)>,
+    rate_limit: Option<RateLimit>,
+    recent_arrivals: VecDeque<Instant>,
+    heartbeat: Option<Heartbeat>,
+    /// Bytes read but not yet returned as a frame.
+    buf: Vec<u8>,
+    sends_waiting: Arc<AtomicUsize>,
+}
+
+impl Receiver {
+    /// As `NetworkConnection::recv_framed`, but without holding the stream
+    /// lock while waiting (see `NetworkConnection::split`).
+    pub fn recv_framed(&mut self) -> Result<Vec<u8>, NetworkError> {
+        if let Some(heartbeat) = &self.heartbeat {
+            return heartbeat.frames.recv().unwrap_or(Err(NetworkError::Closed));
+        }
+        let mut chunk = vec![0u8; 16 * 1024];
+        loop {
+            match take_frame(&mut self.buf, self.max_message_bytes)? {
+                Some(Frame::Data(payload)) => return Ok(payload),
+                Some(Frame::Control(CONTROL_PING)) => write_control(&mut **self.stream.lock().unwrap(), CONTROL_PONG)?,
+                Some(Frame::Control(_)) => {}
+                None => {
+                    while self.sends_waiting.load(Ordering::SeqCst) > 0 {
+                        thread::yield_now();
+                    }
+                    let read = self.stream.lock().unwrap().read(&mut chunk);
+                    match read {
+                        Ok(0) => return Err(NetworkError::Closed),
+                        Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut | std::io::ErrorKind::Interrupted) => {}
+                        Err(e) => return Err(e.into()),
+                    }
+                }
             }
         }
+    }
 
-        // Validate sequence
-        if env.seq != self.expected_seq {
-            anyhow::bail!("unexpected sequence number: expected {} got {}", self.expected_seq, env.seq);
+    /// As `NetworkConnection::receive_enveloped`, including the handling
+    /// of `Chat` (see `chat_log`).
+    pub fn receive_enveloped(&mut self) -> Result<crate::network_protocol::Envelope, NetworkError> {
+        loop {
+            let frame = self.recv_framed()?;
+            if let Some(limit) = self.rate_limit {
+                limit.admit(&mut self.recent_arrivals)?;
+            }
+            let env = open_envelope(&frame, self.match_secret.as_deref(), &mut self.match_id, &mut self.expected_seq)?;
+            match env.payload {
+                crate::network_protocol::GameMessage::Chat { from, text } => {
+                    println!("💬 {}: {}", from, text);
+                    self.chat_log.push((from, text));
+                }
+                _ => return Ok(env),
+            }
         }
-        self.expected_seq = self.expected_seq.wrapping_add(1);
+    }
 
-        Ok(env)
+    /// Chat messages received so far, including any received before the
+    /// split, as `(from, text)`, oldest first.
+    pub fn chat_log(&self) -> &[(String, String)] {
+        &self.chat_log
     }
 }
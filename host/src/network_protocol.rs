@@ -1,17 +1,37 @@
 use serde::{Deserialize, Serialize};
 // Removed duplicate serde import
 use risc0_zkvm::sha::Digest;
-use core::{HitType, Position, RoundCommit};
+use core::{FleetSpec, HitType, Position, RoundCommit};
 use uuid::Uuid;
+use crate::error::{NetworkError, ProofError};
 
 /// Core game messages.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameMessage {
-    /// Initial handshake: send board commitment + optional proof
+    /// Initial handshake: send board commitments + optional proof.
+    /// `commitment` is the placement commitment (`GameState::hash_ships_only`),
+    /// checked against the revealed layout at the end of the game;
+    /// `state_commitment` is the full `GameState::commit()` of the starting
+    /// board, where the defender's `RoundCommit` chain begins.
+    /// `coin_commitment` commits to the nonce later sent in `CoinReveal`
+    /// (see `network::coin_commitment`). `fleet` is the board size and
+    /// fleet the sender plays; the handshake fails unless both agree.
+    /// `proof`, if present, is a setup proof that the board behind
+    /// `commitment` and `state_commitment` is legal (see
+    /// `crate::proofs::prove_board_setup`).
     BoardReady {
         commitment: Digest,
+        state_commitment: Digest,
         player_name: String,
-        proof: Option<ProofData>,
+        proof: Option<SetupProof>,
+        coin_commitment: Digest,
+        fleet: FleetSpec,
+    },
+
+    /// Opens the `coin_commitment` from our `BoardReady`; both nonces
+    /// together decide who shoots first (see `network::coin_host_starts`).
+    CoinReveal {
+        nonce: [u8; 32],
     },
 
     /// Request to take a shot
@@ -26,27 +46,191 @@ pub enum GameMessage {
         proof: ProofData,
     },
 
+    /// Salvo-mode request: fire all `positions` in one turn
+    TakeSalvo {
+        positions: Vec<Position>,
+    },
+
+    /// Response to `TakeSalvo`: one proof covering every shot in order.
+    /// `hit_types[i]` is the result for `positions[i]`.
+    SalvoResult {
+        positions: Vec<Position>,
+        hit_types: Vec<HitType>,
+        proof: ProofData,
+    },
+
+    /// One piece of a large `ProofData` split by `send_large_proof`.
+    /// `data` holds bytes `[index * PROOF_CHUNK_SIZE ..]` of the
+    /// bincode-serialized proof; the receiver reassembles all `total`
+    /// chunks before deserializing.
+    ShotResultChunk {
+        index: u32,
+        total: u32,
+        data: Vec<u8>,
+    },
+
     /// Game over notification
     GameOver {
         winner: String,
     },
 
+    /// Offer another match over this connection once `GameOver` has been
+    /// exchanged. Answered by `RematchAccept`, or by a crossing
+    /// `RematchRequest` when both players asked at once; any other reply,
+    /// or closing the connection, declines. A `RefereeSession` covers a
+    /// single match and does not follow the connection into the next one.
+    RematchRequest,
+
+    /// Accept a `RematchRequest`. The next envelope on either side opens
+    /// the new match with a fresh `BoardReady` handshake.
+    RematchAccept,
+
     /// Error message
     Error {
         message: String,
     },
+
+    /// Ends the match in the sender's favour: the receiver sent more
+    /// invalid (out-of-bounds or repeated) shots than the sender's
+    /// `GameCoordinator::invalid_shot_limit` allows. Nothing follows it.
+    Forfeit {
+        reason: String,
+    },
+
+    /// Free-text chat, allowed at any point in the match. Enveloped and
+    /// sequenced like every other message; `receive_enveloped` prints it
+    /// and keeps waiting for the next game message.
+    Chat {
+        from: String,
+        text: String,
+    },
 }
 
+/// Layout version of `ProofData` written by this build. Bump it whenever
+/// the `Receipt` serialization, the journal or the `RoundCommit` layout
+/// changes, so older readers fail with `ProofError::UnsupportedVersion`
+/// instead of a bincode error. Version 2 added `RoundCommit::invalid`,
+/// version 3 the placement hash after the journal's initial commit,
+/// version 4 the turn state and shot owners after the fleet digest.
+pub const PROOF_DATA_VERSION: u16 = 4;
+
 /// Serializable proof data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofData {
+    /// `PROOF_DATA_VERSION` when written. Kept first so `decode` can read
+    /// it before the rest. JSON written before the tag existed reads as 0;
+    /// the `RoundCommit` fields added since then take their defaults.
+    #[serde(default)]
+    pub version: u16,
     pub receipt_bytes: Vec<u8>,
+    /// The round the receipt proves.
     pub commit: RoundCommit,
 }
 
 impl ProofData {
     pub fn from_bytes(receipt_bytes: Vec<u8>, commit: RoundCommit) -> Self {
-        Self { receipt_bytes, commit }
+        Self { version: PROOF_DATA_VERSION, receipt_bytes, commit }
+    }
+
+    /// Decode bincode-serialized `ProofData`, checking the version tag
+    /// before deserializing anything else.
+    pub fn decode(bytes: &[u8]) -> Result<ProofData, ProofError> {
+        let version = match bytes {
+            [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]),
+            _ => return Err(ProofError::Encoding("proof data too short to hold a version tag".into())),
+        };
+        check_proof_version(version)?;
+        bincode::deserialize(bytes).map_err(|e| ProofError::Encoding(format!("deserializing ProofData: {}", e)))
+    }
+
+    /// Fails with `ProofError::UnsupportedVersion` unless this build can
+    /// read `self.receipt_bytes`.
+    pub fn check_version(&self) -> Result<(), ProofError> {
+        check_proof_version(self.version)
+    }
+}
+
+/// A zero-shot proof for `BoardReady` (see
+/// `crate::proofs::prove_board_setup`). Its journal has no rounds, so
+/// unlike `ProofData` it carries no `RoundCommit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupProof {
+    /// `PROOF_DATA_VERSION` when written.
+    pub version: u16,
+    pub receipt_bytes: Vec<u8>,
+}
+
+impl SetupProof {
+    pub fn from_bytes(receipt_bytes: Vec<u8>) -> Self {
+        Self { version: PROOF_DATA_VERSION, receipt_bytes }
+    }
+}
+
+/// Whether proof data tagged `version` can be read by this build: the
+/// current `PROOF_DATA_VERSION` or untagged (0) legacy records.
+pub fn check_proof_version(version: u16) -> Result<(), ProofError> {
+    if version == 0 || version == PROOF_DATA_VERSION {
+        Ok(())
+    } else {
+        Err(ProofError::UnsupportedVersion { found: version, supported: PROOF_DATA_VERSION })
+    }
+}
+
+/// Maximum number of serialized proof bytes carried by one `ShotResultChunk`.
+pub const PROOF_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest serialized `ProofData` a chunked transfer may carry. Receipts
+/// are well under this; it bounds what a peer can make us buffer.
+pub const MAX_PROOF_BYTES: usize = 32 * 1024 * 1024;
+
+/// Reassembles a `ProofData` from an in-order `ShotResultChunk` sequence.
+///
+/// Chunks must arrive in order (index 0, 1, ...) and agree on `total`;
+/// anything else is rejected so a peer cannot splice two transfers. A
+/// chunk over `PROOF_CHUNK_SIZE`, or a `total` that could add up to more
+/// than `MAX_PROOF_BYTES`, is rejected before anything is buffered.
+#[derive(Debug, Default)]
+pub struct ProofReassembler {
+    total: Option<u32>,
+    next_index: u32,
+    buf: Vec<u8>,
+}
+
+impl ProofReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next chunk. Returns `Ok(Some(pd))` once the final chunk has
+    /// been accepted and the proof deserialized, `Ok(None)` while more
+    /// chunks are expected.
+    pub fn push(&mut self, index: u32, total: u32, data: Vec<u8>) -> Result<Option<ProofData>, NetworkError> {
+        if total == 0 {
+            return Err(NetworkError::Protocol("chunked proof announced zero chunks".into()));
+        }
+        if total as usize > MAX_PROOF_BYTES.div_ceil(PROOF_CHUNK_SIZE) {
+            return Err(NetworkError::Protocol(format!("chunked proof announced {} chunks, over the {} byte limit", total, MAX_PROOF_BYTES)));
+        }
+        if data.len() > PROOF_CHUNK_SIZE {
+            return Err(NetworkError::Protocol(format!("proof chunk of {} bytes exceeds {}", data.len(), PROOF_CHUNK_SIZE)));
+        }
+        match self.total {
+            None => self.total = Some(total),
+            Some(t) if t != total => return Err(NetworkError::Protocol(format!("chunk total changed mid-transfer: expected {} got {}", t, total))),
+            Some(_) => {}
+        }
+        if index != self.next_index {
+            return Err(NetworkError::Protocol(format!("out-of-order proof chunk: expected {} got {}", self.next_index, index)));
+        }
+        self.buf.extend_from_slice(&data);
+        self.next_index += 1;
+        if self.next_index < total {
+            return Ok(None);
+        }
+        let pd = ProofData::decode(&self.buf)
+            .map_err(|e| NetworkError::Malformed(format!("reassembled ProofData: {}", e)))?;
+        *self = Self::default();
+        Ok(Some(pd))
     }
 }
 
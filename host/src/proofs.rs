@@ -1,175 +1,370 @@
-use anyhow::{Context, Result, bail};
-use core::{GameState, Position, RoundCommit};
+use anyhow::{Context, Result};
+use crate::error::ProofError;
+use core::{DisclosureMode, FleetSpec, GameState, Player, Position, RoundCommit, TurnState, MAX_SHOTS_PER_PROOF};
 use uuid::Uuid;
 use methods::{METHOD_ELF, METHOD_ID};
-use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
-use risc0_zkvm::serde::{Deserializer, Error as SerdeError};
-use serde::Serialize;
-use anyhow::anyhow;
 use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{default_executor, ExecutorEnv, ExternalProver, Prover, ProverOpts, Receipt};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Serialize)]
+// Verification lives in `crate::verify` so verifier-only binaries need not
+// touch the prover; re-exported here for existing callers.
+pub use crate::verify::{
+    check_commit_chain, check_fleet, check_placement, check_salvo_commits, check_ship_type_reveal, extract_round_commits, extract_round_commits_from_journal, journal_words,
+    receipt_from_proofdata, verify_remote_round_proof, verify_salvo_result_for_shooter, verify_setup_proof, verify_shot_result_for_shooter,
+};
+
+#[derive(Clone, Serialize)]
 pub struct GuestInput {
     pub initial: GameState,
     pub shots: Vec<Position>,
     pub match_id: Uuid,
     pub seq: u64,
+    /// Who fired each of `shots`. When non-empty it must match `shots` in
+    /// length and the guest rejects any shot by a player who is not to
+    /// move, starting from `turn` and passing the turn as
+    /// `fleet.turn_rules` says. Leave empty for salvos, where one player
+    /// fires several shots by design.
+    pub turn_owner: Vec<Player>,
+    /// Turn state before the first shot. Committed to the journal with
+    /// `turn_owner`, so the shooter can check it (see
+    /// `crate::verify::verify_shot_result_for_shooter`).
+    pub turn: TurnState,
+    /// Board parameters agreed in the handshake. The guest rejects a board
+    /// that does not fit them and commits `fleet.digest()` to the journal.
+    pub fleet: FleetSpec,
+    /// How sinkings appear in the journal. Under `HiddenShipType` the key
+    /// is private input: the journal only carries the commitments, which
+    /// the defender opens after the game (see
+    /// `crate::verify::check_ship_type_reveal`).
+    pub disclosure: DisclosureMode,
 }
 
-/// NOTE: In this development environment the riscv guest prover APIs and
-/// the riscv toolchain may not be available. To keep the host crate
-/// compiling while you iterate on host-side code we provide stubbed
-/// implementations here. These return an error indicating the prover is
-/// disabled. When you install the RISC0 toolchain and want host-local
-/// proving, replace these stubs with calls to the real prover API.
-pub fn produce_and_verify_proof(input: &GuestInput) -> Result<Receipt> {
-    // Build an executor environment and write the guest input into stdin for the guest
-    let mut builder = ExecutorEnv::builder();
-    builder.write(input).context("serializing guest input")?;
-    let env = builder.build().context("building executor env")?;
-
-    // Run the default prover (chosen via RISC0_PROVER env or feature flags)
-    let info = default_prover().prove(env, METHOD_ELF).context("prover failed")?;
-    let receipt = info.receipt;
-
-    // Verify the receipt locally against the expected METHOD_ID
-    receipt.verify(METHOD_ID).context("receipt verification failed")?;
+/// Credentials for proving on Bonsai, passed explicitly instead of relying
+/// on the caller's environment.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BonsaiConfig {
+    pub api_url: String,
+    pub api_key: String,
+}
 
-    Ok(receipt)
+impl std::fmt::Debug for BonsaiConfig {
+    // Never print the API key
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BonsaiConfig").field("api_url", &self.api_url).field("api_key", &"<redacted>").finish()
+    }
 }
 
-pub fn extract_round_commits(receipt: &Receipt) -> Result<Vec<RoundCommit>> {
-    // The journal contains a sequence of committed objects. The guest writes
-    // an initial GameState commit (Digest) followed by one RoundCommit per
-    // shot. We stream-deserialize over the journal bytes to extract the
-    // RoundCommit entries while skipping the initial digest.
+/// Proving backend used by `produce_and_verify_proof_with`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProverBackend {
+    /// CPU proving on this machine via the installed r0vm.
+    Local,
+    /// GPU proving on this machine; requires building `host` with `--features cuda`.
+    Cuda,
+    /// Remote proving on Bonsai with the given credentials.
+    Bonsai(BonsaiConfig),
+}
 
-    let bytes = &receipt.journal.bytes;
+impl ProverBackend {
+    /// Fail early with a descriptive error if this backend cannot be used by
+    /// the current build/configuration, instead of silently proving elsewhere.
+    pub fn ensure_available(&self) -> Result<(), ProofError> {
+        match self {
+            ProverBackend::Local => Ok(()),
+            ProverBackend::Cuda => {
+                if cfg!(feature = "cuda") {
+                    Ok(())
+                } else {
+                    Err(ProofError::BackendUnavailable("CUDA prover backend requested but host was built without the `cuda` feature".into()))
+                }
+            }
+            ProverBackend::Bonsai(cfg) => {
+                if cfg.api_url.trim().is_empty() || cfg.api_key.trim().is_empty() {
+                    return Err(ProofError::BackendUnavailable("Bonsai prover backend requires a non-empty api_url and api_key".into()));
+                }
+                Ok(())
+            }
+        }
+    }
+}
 
-    // Convert the journal bytes into a Vec<u32> (little-endian). We avoid
-    // depending on `bytemuck` here to keep the host crate minimal.
-    if bytes.len() % 4 != 0 {
-        return Err(anyhow!("journal bytes length not a multiple of 4"));
+/// Check `input` against the guest's own preconditions, so a bad input is
+/// rejected in microseconds instead of after a full prover run: the board
+/// must pass `check()` and hold exactly the ships of `fleet`, every shot must be on the board
+/// (off-board shots would make commits `check_commit_chain` rejects),
+/// there may be at most `MAX_SHOTS_PER_PROOF` of them, and `turn_owner` must be
+/// empty or name the shooter of each shot.
+pub fn validate_guest_input(input: &GuestInput) -> Result<(), ProofError> {
+    let invalid = |msg: String| Err(ProofError::InvalidInput(msg));
+    if !input.initial.check() {
+        return invalid("initial board fails GameState::check()".into());
+    }
+    if !input.initial.is_placement_complete(&input.fleet) {
+        return invalid("initial board is not exactly the agreed fleet".into());
     }
-    let mut owned_words: Vec<u32> = Vec::with_capacity(bytes.len() / 4);
-    for chunk in bytes.chunks_exact(4) {
-        let w = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-        owned_words.push(w);
+    if input.shots.len() > MAX_SHOTS_PER_PROOF {
+        return invalid(format!("{} shots exceeds the limit of {}", input.shots.len(), MAX_SHOTS_PER_PROOF));
     }
-    let words_slice: &[u32] = owned_words.as_slice();
+    if let Some(shot) = input.shots.iter().find(|&&s| !input.initial.in_bounds(s)) {
+        return invalid(format!("shot {:?} is off the board", shot));
+    }
+    if !input.turn_owner.is_empty() && input.turn_owner.len() != input.shots.len() {
+        return invalid(format!("turn_owner names {} shooters for {} shots", input.turn_owner.len(), input.shots.len()));
+    }
+    Ok(())
+}
+
+/// Produce and locally verify a proof with the local r0vm
+/// (`ProverBackend::Local`).
+pub fn produce_and_verify_proof(input: &GuestInput) -> Result<Receipt, ProofError> {
+    produce_and_verify_proof_with(input, &ProverBackend::Local)
+}
 
-    let mut deser = Deserializer::new(words_slice);
+/// The r0vm binary `ProverBackend::Local` proves with, looked up on `PATH`
+/// (where rzup installs it).
+pub const R0VM: &str = "r0vm";
 
-    // First decode the initial digest (GameState::commit() output) and ignore it.
-    // Digest type used by GameState::commit
-    let _: risc0_zkvm::sha::Digest = match serde::Deserialize::deserialize(&mut deser) {
-        Ok(d) => d,
-        Err(e) => {
-            return Err(anyhow!("failed to read initial commit from journal: {:?}", e));
+/// How often a Bonsai session is polled for its receipt.
+pub const BONSAI_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Produce and locally verify a proof on an explicitly chosen backend.
+/// Unavailable backends are rejected up front (see
+/// `ProverBackend::ensure_available`) rather than falling back to another.
+/// The prover is built from `backend` alone: nothing is read from or
+/// written to the process environment.
+pub fn produce_and_verify_proof_with(input: &GuestInput, backend: &ProverBackend) -> Result<Receipt, ProofError> {
+    backend.ensure_available()?;
+    check_shot_count(input)?;
+    let prover_failed = |e: anyhow::Error| ProofError::Prover { backend: format!("{:?}", backend), source: e.into() };
+
+    let receipt = match backend {
+        ProverBackend::Local => {
+            let env = guest_env(input)?;
+            ExternalProver::new("ipc", R0VM).prove_with_opts(env, METHOD_ELF, &ProverOpts::default()).map_err(prover_failed)?.receipt
         }
+        ProverBackend::Cuda => prove_on_gpu(input).map_err(prover_failed)?,
+        ProverBackend::Bonsai(cfg) => prove_on_bonsai(input, cfg).map_err(prover_failed)?,
     };
 
-    // Now read zero-or-more RoundCommit entries until we hit EOF.
-    let mut commits: Vec<RoundCommit> = Vec::new();
-    loop {
-        match serde::Deserialize::deserialize(&mut deser) {
-            Ok(rc) => commits.push(rc),
-            Err(SerdeError::DeserializeUnexpectedEnd) => break,
-            Err(e) => return Err(anyhow!("failed to deserialize RoundCommit: {:?}", e)),
-        }
-    }
+    // Verify the receipt locally against the expected METHOD_ID
+    receipt.verify(METHOD_ID).map_err(|e| ProofError::ReceiptInvalid(e.to_string()))?;
 
-    Ok(commits)
+    Ok(receipt)
 }
 
-/// Verify cryptographic integrity of a remote round proof and ensure it is
-/// bound to the provided optional match/session id and sequence number.
-/// If `expected_match` or `expected_seq` are None, those checks are skipped
-/// (useful for local single-process proofs/tests).
-pub fn verify_remote_round_proof(receipt: &Receipt, server_state: &GameState, shot: Position, expected_match: Option<Uuid>, expected_seq: Option<u64>) -> Result<Vec<RoundCommit>> {
-    // Verify cryptographic integrity and extract commits
-    receipt.verify(METHOD_ID).context("receipt verification failed")?;
-    let commits = extract_round_commits(receipt)?;
+/// Prove in-process; the `cuda` feature puts risc0's local prover on the GPU.
+#[cfg(feature = "cuda")]
+fn prove_on_gpu(input: &GuestInput) -> Result<Receipt> {
+    let env = guest_env(input)?;
+    Ok(risc0_zkvm::LocalProver::new("cuda").prove_with_opts(env, METHOD_ELF, &ProverOpts::default())?.receipt)
+}
 
-    if commits.is_empty() {
-        bail!("no round commits found in receipt")
-    }
+#[cfg(not(feature = "cuda"))]
+fn prove_on_gpu(_input: &GuestInput) -> Result<Receipt> {
+    // `ensure_available` refuses `Cuda` in this build
+    anyhow::bail!("host was built without the `cuda` feature")
+}
 
-    // Ensure the receiver's known server_state matches the pre-image of the first commit
-    let expected_old = server_state.commit();
-    if commits[0].old_state != expected_old {
-        bail!("mismatched base state in provided proof")
+/// Prove on Bonsai with the credentials in `cfg`: upload the guest image
+/// and input, start a session and poll it until the receipt is ready.
+fn prove_on_bonsai(input: &GuestInput, cfg: &BonsaiConfig) -> Result<Receipt> {
+    let client = bonsai_sdk::blocking::Client::from_parts(cfg.api_url.clone(), cfg.api_key.clone(), risc0_zkvm::VERSION)?;
+    let image_id = hex::encode(Digest::from(METHOD_ID));
+    client.upload_img(&image_id, METHOD_ELF.to_vec())?;
+    // The guest reads its input with `env::read`: `risc0_zkvm::serde`
+    // words, each stored as 4 little-endian bytes
+    let words = risc0_zkvm::serde::to_vec(input)?;
+    let input_id = client.upload_input(words.iter().flat_map(|w| w.to_le_bytes()).collect())?;
+    let session = client.create_session(image_id, input_id, vec![], false)?;
+    loop {
+        let status = session.status(&client)?;
+        match status.status.as_str() {
+            "RUNNING" => thread::sleep(BONSAI_POLL_INTERVAL),
+            "SUCCEEDED" => {
+                let url = status.receipt_url.context("Bonsai session succeeded without a receipt")?;
+                return Ok(bincode::deserialize(&client.download(&url)?)?);
+            }
+            other => anyhow::bail!("Bonsai session {} ended {}: {}", session.uuid, other, status.error_msg.unwrap_or_default()),
+        }
     }
+}
 
-    // Ensure one of the commits corresponds to the shot in question
-    if !commits.iter().any(|c| c.shot == shot) {
-        bail!("receipt does not contain a commit for the requested shot")
+/// Refuse an over-long shot list before any proving work, whether or not
+/// the caller ran `validate_guest_input`.
+fn check_shot_count(input: &GuestInput) -> Result<(), ProofError> {
+    if input.shots.len() > MAX_SHOTS_PER_PROOF {
+        return Err(ProofError::InvalidInput(format!("{} shots exceeds the limit of {}", input.shots.len(), MAX_SHOTS_PER_PROOF)));
     }
+    Ok(())
+}
 
-    // If match/session binding was requested, ensure at least one commit
-    // in the proof carries the expected match id and sequence number.
-    if let Some(exp_mid) = expected_match {
-        if let Some(exp_seq) = expected_seq {
-            if !commits.iter().any(|c| c.match_id == exp_mid && c.seq == exp_seq) {
-                bail!("receipt proof not bound to expected match_id/seq");
-            }
+/// Serialize `input` into an executor environment for the guest. Every
+/// local prover and executor run starts here, so it applies
+/// `check_shot_count` too.
+fn guest_env(input: &GuestInput) -> Result<ExecutorEnv<'static>, ProofError> {
+    check_shot_count(input)?;
+    let mut builder = ExecutorEnv::builder();
+    builder.write(input).map_err(|e| ProofError::Encoding(format!("serializing guest input: {}", e)))?;
+    builder.build().map_err(|e| ProofError::Encoding(format!("building executor env: {}", e)))
+}
+
+/// How often `produce_and_verify_proof_cancellable` checks the cancel flag
+/// and reports progress.
+pub const PROOF_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Like `produce_and_verify_proof`, but gives up with `ProofError::Cancelled` as soon as
+/// `cancel` is set, checking every `PROOF_POLL_INTERVAL`. `progress`, if
+/// given, is called with the elapsed time at each check so a GUI can show
+/// it is still working.
+///
+/// The RISC0 prover API has no hook between segments, so proving runs on a
+/// worker thread and cancelling abandons it rather than stopping it: the
+/// in-flight proof runs to completion in the background and its result is
+/// discarded.
+pub fn produce_and_verify_proof_cancellable(input: &GuestInput, cancel: Arc<AtomicBool>, progress: Option<&dyn Fn(Duration)>) -> Result<Receipt, ProofError> {
+    if cancel.load(Ordering::Relaxed) {
+        return Err(ProofError::Cancelled);
+    }
+    let (tx, rx) = mpsc::channel();
+    let input = input.clone();
+    thread::spawn(move || {
+        // The receiver is gone if we were cancelled; nothing to report then
+        let _ = tx.send(produce_and_verify_proof(&input));
+    });
+    let start = Instant::now();
+    loop {
+        match rx.recv_timeout(PROOF_POLL_INTERVAL) {
+            Ok(result) => return result,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Err(ProofError::Prover { backend: format!("{:?}", ProverBackend::Local), source: "prover thread exited without a result".into() }),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+        if cancel.load(Ordering::Relaxed) {
+            return Err(ProofError::Cancelled);
+        }
+        if let Some(report) = progress {
+            report(start.elapsed());
         }
     }
+}
+
+/// Run the guest in execution-only mode (no proof) and return the
+/// RoundCommits it writes to the journal. Much faster than proving, so it
+/// suits development and logic checks; the result carries no cryptographic
+/// guarantee and must never be sent to a peer as evidence.
+pub fn execute_only(input: &GuestInput) -> Result<Vec<RoundCommit>, ProofError> {
+    let env = guest_env(input)?;
 
+    let session = default_executor().execute(env, METHOD_ELF).map_err(|e| ProofError::Prover { backend: "executor".into(), source: e.into() })?;
+    let commits = extract_round_commits_from_journal(&session.journal.bytes)?;
+    check_commit_chain(&commits)?;
     Ok(commits)
 }
 
-pub fn proofdata_from_receipt(receipt: &Receipt, commit: RoundCommit) -> Result<crate::network_protocol::ProofData> {
-    let receipt_bytes = bincode::serialize(receipt).context("serializing Receipt to bytes")?;
-    Ok(crate::network_protocol::ProofData::from_bytes(receipt_bytes, commit))
-}
+/// Directory holding per-match receipt logs, one `<match_id>.log` each.
+pub const RECEIPTS_DIR: &str = "receipts";
 
-pub fn receipt_from_proofdata(pd: &crate::network_protocol::ProofData) -> Result<Receipt> {
-    let receipt: Receipt = bincode::deserialize(&pd.receipt_bytes).context("deserializing Receipt from bytes")?;
-    Ok(receipt)
+/// One line of a receipt log (JSON): a proof received for `seq`, as
+/// base64-encoded bincode, and the commit it is claimed to prove.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptLogEntry {
+    /// The `ProofData::version` the receipt was received with; 0 for
+    /// entries logged before versions were recorded.
+    #[serde(default)]
+    pub version: u16,
+    pub seq: u64,
+    pub receipt_b64: String,
+    pub commit: RoundCommit,
 }
 
-/// Verify a receipt for a shooter (who does not hold the defender's full
-/// GameState). This verifies the receipt cryptographically, extracts the
-/// round commits, finds the commit bound to the provided match/seq and
-/// shot, ensures the commit.old_state equals `expected_old`, and returns
-/// the matching RoundCommit (which contains the new_state the shooter can
-/// adopt as the opponent's updated commitment).
-pub fn verify_shot_result_for_shooter(receipt: &Receipt, expected_old: Digest, shot: Position, expected_match: Option<Uuid>, expected_seq: Option<u64>) -> Result<RoundCommit> {
-    // 1) cryptographic verification
-    receipt.verify(METHOD_ID).context("receipt verification failed")?;
-
-    // 2) extract commits
-    let commits = extract_round_commits(receipt)?;
-    if commits.is_empty() {
-        bail!("no round commits found in receipt");
-    }
+/// Match ids that have a receipt log in `RECEIPTS_DIR`.
+pub fn list_saved_matches() -> Result<Vec<Uuid>> {
+    list_saved_matches_in(Path::new(RECEIPTS_DIR))
+}
 
-    // 3) locate commit matching shot and optional binding
-    let mut found: Option<RoundCommit> = None;
-    for c in commits.iter() {
-        if c.shot == shot {
-            if let (Some(mid), Some(sq)) = (expected_match, expected_seq) {
-                if c.match_id == mid && c.seq == sq {
-                    found = Some(c.clone());
-                    break;
-                }
-            } else {
-                found = Some(c.clone());
-                break;
+/// Like `list_saved_matches`, for logs kept in `dir`. A missing directory
+/// has no matches; files not named `<uuid>.log` are ignored.
+pub fn list_saved_matches_in(dir: &Path) -> Result<Vec<Uuid>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("listing {}", dir.display())),
+    };
+    let mut ids = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "log") {
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| Uuid::parse_str(s).ok()) {
+                ids.push(id);
             }
         }
     }
+    ids.sort();
+    Ok(ids)
+}
 
-    let commit = match found {
-        Some(c) => c,
-        None => bail!("no matching RoundCommit found for shot/match/seq"),
-    };
+/// Latest verified `RoundCommit` saved for `match_id` in `RECEIPTS_DIR`,
+/// so a reconnecting coordinator can resume with
+/// `opponent_commit = Some(rc.new_state)`.
+pub fn latest_commit_for_match(match_id: Uuid) -> Result<Option<RoundCommit>> {
+    latest_commit_for_match_in(Path::new(RECEIPTS_DIR), match_id)
+}
 
-    // 4) ensure old_state matches expected_old (the shooter's recorded opponent commit)
-    if commit.old_state != expected_old {
-        bail!("commit.old_state does not match expected old digest");
+/// Like `latest_commit_for_match`, for logs kept in `dir`. Entries are
+/// tried from the end of the log; one that does not parse, whose receipt
+/// fails `verify_proofdata`, or whose commit belongs to another match is
+/// skipped, so a torn last line from a crash falls back to the previous
+/// entry. Returns `None` if there is no log or no entry verifies.
+pub fn latest_commit_for_match_in(dir: &Path, match_id: Uuid) -> Result<Option<RoundCommit>> {
+    let path = dir.join(format!("{}.log", match_id));
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+    };
+    for line in text.lines().rev().filter(|l| !l.trim().is_empty()) {
+        let entry: ReceiptLogEntry = match serde_json::from_str(line) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        if entry.commit.match_id != match_id {
+            continue;
+        }
+        let bytes = match general_purpose::STANDARD.decode(&entry.receipt_b64) {
+            Ok(b) => b,
+            Err(_) => continue,
+        };
+        let pd = crate::network_protocol::ProofData { version: entry.version, receipt_bytes: bytes, commit: entry.commit };
+        if let Ok(rc) = crate::verify::verify_proofdata(&pd) {
+            return Ok(Some(rc));
+        }
     }
+    Ok(None)
+}
 
-    Ok(commit)
+/// Prove, before any shots, that `state` is a legal board for `fleet`
+/// without revealing it, for the `proof` field of `BoardReady`. This is
+/// the usual guest run with no shots: it only checks the board and commits
+/// `state.commit()`, `state.hash_ships_only()` and `fleet.digest()`. The
+/// opponent checks it with `verify_setup_proof`. A board that is not
+/// exactly the fleet under its rules (`GameState::is_placement_complete`)
+/// is refused with `InvalidInput` before proving.
+pub fn prove_board_setup(state: &GameState, fleet: &FleetSpec) -> Result<crate::network_protocol::SetupProof, ProofError> {
+    let input = GuestInput { initial: state.clone(), shots: vec![], match_id: Uuid::nil(), seq: 0, turn_owner: vec![], turn: TurnState::default(), fleet: fleet.clone(), disclosure: DisclosureMode::Open };
+    validate_guest_input(&input)?;
+    let receipt = produce_and_verify_proof(&input)?;
+    let receipt_bytes = bincode::serialize(&receipt).map_err(|e| ProofError::Encoding(format!("serializing Receipt to bytes: {}", e)))?;
+    Ok(crate::network_protocol::SetupProof::from_bytes(receipt_bytes))
 }
+
+pub fn proofdata_from_receipt(receipt: &Receipt, commit: RoundCommit) -> Result<crate::network_protocol::ProofData, ProofError> {
+    let receipt_bytes = bincode::serialize(receipt).map_err(|e| ProofError::Encoding(format!("serializing Receipt to bytes: {}", e)))?;
+    Ok(crate::network_protocol::ProofData::from_bytes(receipt_bytes, commit))
+}
+
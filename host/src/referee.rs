@@ -0,0 +1,299 @@
+// Neutral third-party referee for a networked match.
+//
+// Both players enable broadcast mode (`NetworkConnection::set_referee`) so
+// every envelope they send is also written to the referee. The referee holds
+// the per-match secret, checks each envelope's HMAC, match id and sequence
+// number, verifies every shot/salvo proof against the defender's last known
+// commitment, and keeps the accepted envelopes as a transcript that it signs
+// with its own Ed25519 key.
+use anyhow::{Context, Result, anyhow, bail};
+use core::{FleetSpec, Player, Position};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use base64::{engine::general_purpose, Engine as _};
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::sync::mpsc;
+use std::thread;
+use uuid::Uuid;
+
+use crate::network::{bind_match_secret, envelope_auth_token, read_frame, DEFAULT_MAX_MESSAGE_BYTES};
+use crate::network_protocol::{Envelope, GameMessage};
+use crate::verify::{check_fleet, check_placement, receipt_from_proofdata, verify_salvo_result_for_shooter, verify_shot_result_for_shooter};
+
+/// One accepted envelope and the seat that sent it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub from: Player,
+    pub envelope: Envelope,
+}
+
+/// Authoritative record of a match. `signature` is an Ed25519 signature by
+/// `public_key` (both base64) over the JSON encoding of `(match_id, entries)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTranscript {
+    pub match_id: Option<Uuid>,
+    pub entries: Vec<TranscriptEntry>,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SignedTranscript {
+    fn signing_bytes(match_id: &Option<Uuid>, entries: &[TranscriptEntry]) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&(match_id, entries))?)
+    }
+
+    /// Check the signature against the embedded public key. Callers that
+    /// trust a particular referee should also compare `public_key`.
+    pub fn verify(&self) -> Result<()> {
+        let key = general_purpose::STANDARD.decode(&self.public_key).context("decoding referee public key")?;
+        let sig = general_purpose::STANDARD.decode(&self.signature).context("decoding transcript signature")?;
+        let msg = Self::signing_bytes(&self.match_id, &self.entries)?;
+        UnparsedPublicKey::new(&ED25519, key).verify(&msg, &sig).map_err(|_| anyhow!("transcript signature is invalid"))
+    }
+}
+
+/// Outstanding request from a shooter, waiting for the defender's proof.
+#[derive(Debug, Clone)]
+enum Pending {
+    Shot { position: Position, seq: u64 },
+    Salvo { positions: Vec<Position>, seq: u64 },
+}
+
+/// Referee state for one match. Player One is the host, whose first
+/// `BoardReady` fixes the match id. The secret given at setup is the
+/// players' DH secret; once both boards are known the referee re-derives
+/// the board-bound secret with `bind_match_secret` exactly as they do.
+pub struct RefereeSession {
+    match_secret: Vec<u8>,
+    signer: Ed25519KeyPair,
+    match_id: Option<Uuid>,
+    expected_seq: [u64; 2],
+    /// Each player's latest full-state commitment, starting from the
+    /// `state_commitment` in `BoardReady`.
+    commitments: [Option<Digest>; 2],
+    /// Each player's placement commitment from `BoardReady`.
+    placements: [Option<Digest>; 2],
+    /// Board parameters each player declared in `BoardReady`; result
+    /// proofs must be for this fleet.
+    fleets: [Option<FleetSpec>; 2],
+    /// Shot or salvo fired by each player that has not been answered yet.
+    pending: [Option<Pending>; 2],
+    entries: Vec<TranscriptEntry>,
+}
+
+impl RefereeSession {
+    pub fn new(match_secret: Vec<u8>, signer: Ed25519KeyPair) -> Self {
+        Self { match_secret, signer, match_id: None, expected_seq: [0; 2], commitments: [None; 2], placements: [None; 2], fleets: [None, None], pending: [None, None], entries: Vec::new() }
+    }
+
+    /// Like `new`, with a freshly generated signing key.
+    pub fn with_ephemeral_key(match_secret: Vec<u8>) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).map_err(|e| anyhow!("generating referee key: {:?}", e))?;
+        let signer = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|e| anyhow!("loading referee key: {:?}", e))?;
+        Ok(Self::new(match_secret, signer))
+    }
+
+    /// Base64 Ed25519 public key that signs this session's transcript.
+    pub fn public_key(&self) -> String {
+        general_purpose::STANDARD.encode(self.signer.public_key().as_ref())
+    }
+
+    /// Envelopes accepted so far, in arrival order.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// Validate one envelope sent by `from` and, if it passes, append it to
+    /// the transcript. An error means the envelope was rejected and nothing
+    /// was recorded.
+    pub fn observe(&mut self, from: Player, env: Envelope) -> Result<()> {
+        let me = from.index();
+        let expected = envelope_auth_token(&self.match_secret, &env)?;
+        if env.auth_token.as_deref() != Some(expected.as_str()) {
+            bail!("envelope {} from {:?} failed HMAC check", env.seq, from);
+        }
+        match self.match_id {
+            Some(id) if env.match_id != id => bail!("envelope from {:?} has mismatched match_id: expected {} got {}", from, id, env.match_id),
+            Some(_) => {}
+            None => self.match_id = Some(env.match_id),
+        }
+        if env.seq != self.expected_seq[me] {
+            bail!("unexpected sequence number from {:?}: expected {} got {}", from, self.expected_seq[me], env.seq);
+        }
+
+        match &env.payload {
+            GameMessage::BoardReady { commitment, state_commitment, fleet, .. } => {
+                if self.placements[me].is_some() {
+                    bail!("{:?} sent a second BoardReady", from);
+                }
+                if self.fleets[from.other().index()].as_ref().is_some_and(|theirs| theirs != fleet) {
+                    bail!("{:?} declared a different fleet from its opponent", from);
+                }
+                self.fleets[me] = Some(fleet.clone());
+                self.placements[me] = Some(*commitment);
+                self.commitments[me] = Some(*state_commitment);
+                // Players switch to the board-bound secret after BoardReady; follow them
+                if let [Some(host_commit), Some(client_commit)] = self.placements {
+                    self.match_secret = bind_match_secret(&self.match_secret, &host_commit, &client_commit);
+                }
+            }
+            GameMessage::TakeShot { position } => {
+                self.pending[me] = Some(Pending::Shot { position: *position, seq: env.seq });
+            }
+            GameMessage::TakeSalvo { positions } => {
+                self.pending[me] = Some(Pending::Salvo { positions: positions.clone(), seq: env.seq });
+            }
+            GameMessage::ShotResult { position, hit_type, proof } => {
+                let seq = match &self.pending[from.other().index()] {
+                    Some(Pending::Shot { position: p, seq }) if p == position => *seq,
+                    _ => bail!("{:?} sent ShotResult for {:?} with no matching TakeShot", from, position),
+                };
+                let expected_old = self.commitments[me].ok_or_else(|| anyhow!("{:?} has no board commitment", from))?;
+                // Cheap consistency checks first so a forged claim is reported precisely
+                if proof.commit.shot != *position || proof.commit.hit != *hit_type {
+                    bail!("ShotResult from {:?} disagrees with its proof commit", from);
+                }
+                if proof.commit.old_state != expected_old {
+                    bail!("ShotResult from {:?} does not start from its last commitment", from);
+                }
+                let receipt = receipt_from_proofdata(proof)?;
+                self.check_binding(from, &receipt).with_context(|| format!("proof from {:?} rejected", from))?;
+                let rc = verify_shot_result_for_shooter(&receipt, expected_old, *position, self.match_id, Some(seq), None)
+                    .with_context(|| format!("proof from {:?} rejected", from))?;
+                if rc.hit != *hit_type {
+                    bail!("ShotResult from {:?} claims {:?} but proof shows {:?}", from, hit_type, rc.hit);
+                }
+                self.commitments[me] = Some(rc.new_state);
+                self.pending[from.other().index()] = None;
+            }
+            GameMessage::SalvoResult { positions, hit_types, proof } => {
+                let seq = match &self.pending[from.other().index()] {
+                    Some(Pending::Salvo { positions: p, seq }) if p == positions => *seq,
+                    _ => bail!("{:?} sent SalvoResult with no matching TakeSalvo", from),
+                };
+                let expected_old = self.commitments[me].ok_or_else(|| anyhow!("{:?} has no board commitment", from))?;
+                let receipt = receipt_from_proofdata(proof)?;
+                self.check_binding(from, &receipt).with_context(|| format!("salvo proof from {:?} rejected", from))?;
+                let commits = verify_salvo_result_for_shooter(&receipt, expected_old, positions, self.match_id, Some(seq), None)
+                    .with_context(|| format!("salvo proof from {:?} rejected", from))?;
+                if commits.len() != hit_types.len() || commits.iter().zip(hit_types).any(|(c, h)| c.hit != *h) {
+                    bail!("SalvoResult from {:?} disagrees with its proof", from);
+                }
+                self.commitments[me] = commits.last().map(|c| c.new_state);
+                self.pending[from.other().index()] = None;
+            }
+            GameMessage::Error { .. } => {
+                // A defender that cannot prove reports an Error; the shot is abandoned
+                self.pending[from.other().index()] = None;
+            }
+            // Chunked proofs are not used by `play_game`; record them as-is
+            GameMessage::ShotResultChunk { .. } | GameMessage::GameOver { .. } | GameMessage::Forfeit { .. } | GameMessage::Chat { .. } | GameMessage::CoinReveal { .. } | GameMessage::RematchRequest | GameMessage::RematchAccept => {}
+        }
+
+        self.expected_seq[me] = self.expected_seq[me].wrapping_add(1);
+        self.entries.push(TranscriptEntry { from, envelope: env });
+        Ok(())
+    }
+
+    /// Fleet `from` declared in its `BoardReady`.
+    fn fleet(&self, from: Player) -> Result<&FleetSpec> {
+        self.fleets[from.index()].as_ref().ok_or_else(|| anyhow!("{:?} has no declared fleet", from))
+    }
+
+    /// `check_fleet` and `check_placement` for a proof from `from`, against
+    /// the fleet and placement commitment from its `BoardReady`.
+    fn check_binding(&self, from: Player, receipt: &Receipt) -> Result<()> {
+        let placement = self.placements[from.index()].ok_or_else(|| anyhow!("{:?} has no placement commitment", from))?;
+        check_fleet(receipt, self.fleet(from)?)?;
+        check_placement(receipt, placement)?;
+        Ok(())
+    }
+
+    /// Whether `env` from `from` can be judged yet. The two sockets are read
+    /// independently, so an envelope can arrive before the other player's
+    /// envelope that caused it: a message sent after the handshake may beat
+    /// the opponent's `BoardReady` (and so the secret rebinding), and a
+    /// result may beat the shot it answers.
+    fn is_ready(&self, from: Player, env: &Envelope) -> bool {
+        let (me, them) = (from.index(), from.other().index());
+        if self.placements[me].is_some() && self.placements[them].is_none() {
+            return false;
+        }
+        match env.payload {
+            GameMessage::ShotResult { .. } | GameMessage::SalvoResult { .. } | GameMessage::Error { .. } => self.pending[them].is_some(),
+            _ => true,
+        }
+    }
+
+    /// Read frames from both players' referee sockets until both close.
+    /// Envelopes are observed in arrival order, except that one arriving
+    /// ahead of the other player's envelope it depends on is held back
+    /// (with everything after it from the same player) until that envelope
+    /// arrives. Stops at the first rejected envelope and returns its error.
+    pub fn run<A, B>(&mut self, one: A, two: B) -> Result<()>
+    where
+        A: Read + Send + 'static,
+        B: Read + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel::<(Player, Result<Envelope>)>();
+        fn spawn_reader<R: Read + Send + 'static>(mut r: R, from: Player, tx: mpsc::Sender<(Player, Result<Envelope>)>) {
+            thread::spawn(move || loop {
+                let frame = match read_frame(&mut r, DEFAULT_MAX_MESSAGE_BYTES) {
+                    Ok(f) => f,
+                    // EOF or a broken socket ends this player's stream
+                    Err(_) => return,
+                };
+                let parsed = serde_json::from_slice::<Envelope>(&frame).context("parsing envelope sent to referee");
+                if tx.send((from, parsed)).is_err() {
+                    return;
+                }
+            });
+        }
+        spawn_reader(one, Player::One, tx.clone());
+        spawn_reader(two, Player::Two, tx);
+        let mut held: [VecDeque<Envelope>; 2] = [VecDeque::new(), VecDeque::new()];
+        for (from, env) in rx {
+            held[from.index()].push_back(env?);
+            // Observe whatever is ready now, from either player, until nothing moves
+            let mut progressed = true;
+            while progressed {
+                progressed = false;
+                for from in [Player::One, Player::Two] {
+                    while let Some(env) = held[from.index()].front() {
+                        if !self.is_ready(from, env) {
+                            break;
+                        }
+                        let env = held[from.index()].pop_front().expect("front exists");
+                        self.observe(from, env)?;
+                        progressed = true;
+                    }
+                }
+            }
+        }
+        // Both streams ended: anything still held never became valid
+        for from in [Player::One, Player::Two] {
+            for env in std::mem::take(&mut held[from.index()]) {
+                self.observe(from, env)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sign the transcript recorded so far.
+    pub fn transcript(&self) -> Result<SignedTranscript> {
+        let msg = SignedTranscript::signing_bytes(&self.match_id, &self.entries)?;
+        let signature = general_purpose::STANDARD.encode(self.signer.sign(&msg).as_ref());
+        Ok(SignedTranscript { match_id: self.match_id, entries: self.entries.clone(), public_key: self.public_key(), signature })
+    }
+
+    /// Write the signed transcript as JSON.
+    pub fn write_transcript<W: Write>(&self, w: W) -> Result<()> {
+        serde_json::to_writer_pretty(w, &self.transcript()?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+// Bot-vs-bot tournaments for balance-testing shot strategies.
+//
+// `run` plays many headless games between two `Strategy` implementations
+// on random boards, with no I/O and no proving: each game is the same
+// turn loop as the scripted demo (`TurnRules::default()` through
+// `next_state`), driven by the strategies instead of a script. Everything
+// random, boards and strategy choices alike, comes from one `StdRng`
+// seeded with the tournament seed, so a tournament replays exactly.
+
+use core::{evaluate_outcome, CellState, GameOutcome, GameState, HitType, Player, Position, TurnRules};
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
+
+/// Chooses where a bot fires.
+pub trait Strategy {
+    /// Next cell to fire at, given the shooter's view of the opponent's
+    /// board (only `grid` is filled in). Must be in bounds and not already
+    /// shot; `run` panics otherwise.
+    fn next_shot(&mut self, view: &GameState, rng: &mut dyn RngCore) -> Position;
+    /// The result of the shot just returned by `next_shot`.
+    fn observe(&mut self, _shot: Position, _result: &HitType) {}
+    /// Forget everything learned in the previous game.
+    fn reset(&mut self) {}
+}
+
+/// Fires at a uniformly random cell not yet shot.
+#[derive(Debug, Default)]
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn next_shot(&mut self, view: &GameState, rng: &mut dyn RngCore) -> Position {
+        *view.unshot_cells().choose(rng).expect("a board with every cell shot has no ships afloat")
+    }
+}
+
+/// Hunts on a checkerboard until it hits, then works through the hit
+/// cell's orthogonal neighbours before hunting again.
+#[derive(Debug, Default)]
+pub struct HuntTargetStrategy {
+    targets: Vec<Position>,
+}
+
+impl Strategy for HuntTargetStrategy {
+    fn next_shot(&mut self, view: &GameState, rng: &mut dyn RngCore) -> Position {
+        while let Some(pos) = self.targets.pop() {
+            if view.grid[pos.y as usize][pos.x as usize] == CellState::Empty {
+                return pos;
+            }
+        }
+        // Every ship covers at least two cells, so one colour of the
+        // checkerboard finds them all
+        let cells = view.unshot_cells();
+        let parity: Vec<Position> = cells.iter().copied().filter(|p| (p.x + p.y) % 2 == 0).collect();
+        *parity.choose(rng).or_else(|| cells.choose(rng)).expect("a board with every cell shot has no ships afloat")
+    }
+
+    fn observe(&mut self, shot: Position, result: &HitType) {
+        if *result != HitType::Miss {
+            self.targets.extend(shot.orthogonal_neighbors());
+        }
+    }
+
+    fn reset(&mut self) {
+        self.targets.clear();
+    }
+}
+
+/// One strategy's results over a tournament.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SideResult {
+    pub wins: usize,
+    /// Shots fired in the games this side won, summed.
+    pub winning_shots: u64,
+}
+
+impl SideResult {
+    /// Mean number of shots this side needed to win, or `None` if it never
+    /// won.
+    pub fn avg_shots_to_win(&self) -> Option<f64> {
+        (self.wins > 0).then(|| self.winning_shots as f64 / self.wins as f64)
+    }
+}
+
+/// Result of `run`. Every game has a winner: the turn passes on every
+/// miss, so one fleet always sinks first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TournamentResult {
+    pub games: usize,
+    pub a: SideResult,
+    pub b: SideResult,
+}
+
+/// Play `games` games of `strategy_a` against `strategy_b` on fresh
+/// uniformly random boards. `strategy_a` shoots first in even-numbered
+/// games and `strategy_b` in odd-numbered ones. The same `seed` always
+/// produces the same result.
+pub fn run(strategy_a: &mut dyn Strategy, strategy_b: &mut dyn Strategy, games: usize, seed: u64) -> TournamentResult {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut result = TournamentResult { games, ..TournamentResult::default() };
+    for game in 0..games {
+        let a_starts = game % 2 == 0;
+        // Player One always shoots first; seat the strategies to match
+        let (winner, shots) = if a_starts { play_game(strategy_a, strategy_b, &mut rng) } else { play_game(strategy_b, strategy_a, &mut rng) };
+        let side = if (winner == Player::One) == a_starts { &mut result.a } else { &mut result.b };
+        side.wins += 1;
+        side.winning_shots += shots as u64;
+    }
+    result
+}
+
+/// One game with `first` as Player One. Returns the winner and the number
+/// of shots it fired.
+fn play_game(first: &mut dyn Strategy, second: &mut dyn Strategy, rng: &mut StdRng) -> (Player, u32) {
+    let mut boards = [GameState::new([0; 16]), GameState::new([0; 16])];
+    for board in &mut boards {
+        assert!(board.place_ships_uniformly(rng), "the classic fleet always fits");
+    }
+    let mut views = [GameState::new([0; 16]), GameState::new([0; 16])];
+    let mut shots = [0u32; 2];
+    first.reset();
+    second.reset();
+
+    let rules = TurnRules::default();
+    let mut turn = Player::One;
+    loop {
+        let (me, them) = match turn { Player::One => (0, 1), Player::Two => (1, 0) };
+        let strategy: &mut dyn Strategy = if turn == Player::One { &mut *first } else { &mut *second };
+        let pos = strategy.next_shot(&views[me], rng);
+        let hit = boards[them].apply_shot(pos).unwrap_or_else(|| panic!("{:?}'s strategy fired at {:?}, which is out of bounds or already shot", turn, pos));
+        let cell = if hit == HitType::Miss { CellState::Miss } else { CellState::Hit };
+        views[me].grid[pos.y as usize][pos.x as usize] = cell;
+        strategy.observe(pos, &hit);
+        shots[me] += 1;
+
+        if let GameOutcome::Winner(winner) = evaluate_outcome(&boards[0], &boards[1], false) {
+            return (winner, shots[me]);
+        }
+        turn = rules.next_state(turn, &hit).next_player;
+    }
+}
@@ -0,0 +1,52 @@
+// Channel protocol between a GUI front end and the networked game.
+//
+// The GUI keeps the `UiEnd` returned by `make_channels` and hands the
+// `GameEnd` to `GameCoordinator::play_game_channels`, usually on its own
+// thread. The game then reads shots from `UiCommand`s instead of stdin and
+// reports through `UiEvent`s instead of stdout.
+use core::{GameOutcome, GameState, HitType, Position};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Input from the local player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiCommand {
+    /// Fire one shot (Classic mode).
+    Shoot(Position),
+    /// Fire a whole salvo (Salvo mode).
+    Salvo(Vec<Position>),
+}
+
+/// Progress reported by the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiEvent {
+    /// A line `play_game` would have printed.
+    Log(String),
+    /// Current boards: our own (revealed) and what we know of the opponent's.
+    Boards { local: Box<GameState>, opponent_view: Box<GameState> },
+    /// A shot we fired was verified, so the GUI can animate the hit, miss
+    /// or sinking at `pos`. Sent before the `Boards` update that shows it.
+    ShotResolved { pos: Position, result: HitType },
+    /// The game is waiting for a `UiCommand` carrying `count` shots.
+    AwaitingShots { count: usize },
+    /// The game ended; the outcome is from the local player's seat.
+    GameOver(GameOutcome),
+}
+
+/// GUI side of the channels.
+pub struct UiEnd {
+    pub commands: Sender<UiCommand>,
+    pub events: Receiver<UiEvent>,
+}
+
+/// Game side of the channels, for `GameCoordinator::play_game_channels`.
+pub struct GameEnd {
+    pub commands: Receiver<UiCommand>,
+    pub events: Sender<UiEvent>,
+}
+
+/// Create a connected pair of channel ends.
+pub fn make_channels() -> (UiEnd, GameEnd) {
+    let (cmd_tx, cmd_rx) = mpsc::channel();
+    let (ev_tx, ev_rx) = mpsc::channel();
+    (UiEnd { commands: cmd_tx, events: ev_rx }, GameEnd { commands: cmd_rx, events: ev_tx })
+}
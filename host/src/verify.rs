@@ -0,0 +1,352 @@
+// Verification-only helpers: decode guest journals and check receipts.
+//
+// Nothing here references `METHOD_ELF` or a prover, only `METHOD_ID` and
+// the receipt verifier, so this module builds without the `prove` feature
+// and thin clients such as a referee can verify both players' proofs
+// without linking the prover. `proofs` re-exports these for callers that
+// also prove.
+use crate::error::ProofError;
+use core::{FleetSpec, GameState, HitType, Player, Position, RoundCommit, ShipTypeOpening, TurnState};
+use uuid::Uuid;
+use methods::METHOD_ID;
+use risc0_zkvm::Receipt;
+use risc0_zkvm::serde::{Deserializer, Error as SerdeError};
+use risc0_zkvm::sha::Digest;
+
+/// Check `receipt` against `METHOD_ID`.
+fn verify_receipt(receipt: &Receipt) -> Result<(), ProofError> {
+    receipt.verify(METHOD_ID).map_err(|e| ProofError::ReceiptInvalid(e.to_string()))
+}
+
+pub fn extract_round_commits(receipt: &Receipt) -> Result<Vec<RoundCommit>, ProofError> {
+    extract_round_commits_from_journal(&receipt.journal.bytes)
+}
+
+/// Split raw journal bytes into the u32 words the guest committed.
+///
+/// Endianness contract: `env::commit` serializes into u32 words with
+/// `risc0_zkvm::serde` and the zkVM (RISC-V, little-endian) stores each
+/// word as 4 little-endian bytes. The journal is therefore a fixed LE byte
+/// stream, and we decode it with `u32::from_le_bytes` rather than a
+/// native-endian cast so a big-endian verifier reads the same words, and
+/// so the same `RoundCommit`s, as the prover.
+pub fn journal_words(bytes: &[u8]) -> Result<Vec<u32>, ProofError> {
+    // We avoid depending on `bytemuck` here to keep the host crate minimal.
+    if bytes.len() % 4 != 0 {
+        return Err(ProofError::MalformedJournal("journal bytes length not a multiple of 4".into()));
+    }
+    Ok(bytes.chunks_exact(4).map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+}
+
+/// Decode RoundCommits from raw guest journal bytes (see `extract_round_commits`).
+/// Words are decoded little-endian on every platform; see `journal_words`.
+pub fn extract_round_commits_from_journal(bytes: &[u8]) -> Result<Vec<RoundCommit>, ProofError> {
+    decode_journal(bytes).map(|journal| journal.commits)
+}
+
+/// Fail with `FleetMismatch` unless `receipt` was proven for `fleet`, the
+/// board parameters agreed in the handshake.
+pub fn check_fleet(receipt: &Receipt, fleet: &FleetSpec) -> Result<(), ProofError> {
+    if decode_journal(&receipt.journal.bytes)?.fleet != fleet.digest() {
+        return Err(ProofError::FleetMismatch);
+    }
+    Ok(())
+}
+
+/// Fail with `PlacementMismatch` unless `receipt` was proven for a board
+/// whose `GameState::hash_ships_only()` is `placement`, the commitment
+/// from the defender's `BoardReady`. Together with the commit chain this
+/// ties every proof to the ships announced at the start of the match.
+pub fn check_placement(receipt: &Receipt, placement: Digest) -> Result<(), ProofError> {
+    if decode_journal(&receipt.journal.bytes)?.placement != placement {
+        return Err(ProofError::PlacementMismatch);
+    }
+    Ok(())
+}
+
+/// Fail with `TurnMismatch` unless `receipt` was proven for shots fired
+/// by `owners` starting from `turn`, as the verifier tracked them.
+fn check_turn(receipt: &Receipt, turn: TurnState, owners: &[Player]) -> Result<(), ProofError> {
+    let journal = decode_journal(&receipt.journal.bytes)?;
+    if journal.turn != turn || journal.owners != owners {
+        return Err(ProofError::TurnMismatch);
+    }
+    Ok(())
+}
+
+/// What the guest writes to the journal, in order.
+struct Journal {
+    /// `commit()` of the board the proof starts from.
+    initial: Digest,
+    /// `hash_ships_only()` of the same board.
+    placement: Digest,
+    /// `FleetSpec::digest()` of the agreed match parameters.
+    fleet: Digest,
+    /// Turn state before the first shot.
+    turn: TurnState,
+    /// Who fired each shot; empty for salvos and setup proofs.
+    owners: Vec<Player>,
+    /// One per shot.
+    commits: Vec<RoundCommit>,
+}
+
+fn decode_journal(bytes: &[u8]) -> Result<Journal, ProofError> {
+    // The journal contains a sequence of committed objects. The guest writes
+    // an initial GameState commit (Digest), the placement hash, the fleet
+    // digest and the turn, followed by one RoundCommit per shot. We stream-deserialize
+    // over the journal bytes to extract the RoundCommit entries.
+    let owned_words = journal_words(bytes)?;
+    let words_slice: &[u32] = owned_words.as_slice();
+
+    let mut deser = Deserializer::new(words_slice);
+
+    // First decode the initial digest (GameState::commit() output).
+    // Digest type used by GameState::commit
+    let initial: risc0_zkvm::sha::Digest = match serde::Deserialize::deserialize(&mut deser) {
+        Ok(d) => d,
+        Err(e) => {
+            return Err(ProofError::MalformedJournal(format!("failed to read initial commit from journal: {:?}", e)));
+        }
+    };
+    let placement: Digest = serde::Deserialize::deserialize(&mut deser).map_err(|e| ProofError::MalformedJournal(format!("failed to read placement hash from journal: {:?}", e)))?;
+    let fleet: Digest = serde::Deserialize::deserialize(&mut deser).map_err(|e| ProofError::MalformedJournal(format!("failed to read fleet digest from journal: {:?}", e)))?;
+    let turn: TurnState = serde::Deserialize::deserialize(&mut deser).map_err(|e| ProofError::MalformedJournal(format!("failed to read turn state from journal: {:?}", e)))?;
+    let owners: Vec<Player> = serde::Deserialize::deserialize(&mut deser).map_err(|e| ProofError::MalformedJournal(format!("failed to read turn owners from journal: {:?}", e)))?;
+
+    // Now read zero-or-more RoundCommit entries until we hit EOF.
+    let mut commits: Vec<RoundCommit> = Vec::new();
+    loop {
+        match serde::Deserialize::deserialize(&mut deser) {
+            Ok(rc) => commits.push(rc),
+            Err(SerdeError::DeserializeUnexpectedEnd) => break,
+            Err(e) => return Err(ProofError::MalformedJournal(format!("failed to deserialize RoundCommit: {:?}", e))),
+        }
+    }
+
+    Ok(Journal { initial, placement, fleet, turn, owners, commits })
+}
+
+/// Verify cryptographic integrity of a remote round proof and ensure it is
+/// bound to the provided optional match/session id and sequence number.
+/// If `expected_match` or `expected_seq` are None, those checks are skipped
+/// (useful for local single-process proofs/tests).
+///
+/// `expected_old` is the defender's commitment before the round: a shooter
+/// passes its recorded opponent commitment, a caller holding the full
+/// board passes `state.commit()`.
+pub fn verify_remote_round_proof(receipt: &Receipt, expected_old: Digest, shot: Position, expected_match: Option<Uuid>, expected_seq: Option<u64>) -> Result<Vec<RoundCommit>, ProofError> {
+    // Verify cryptographic integrity and extract commits
+    verify_receipt(receipt)?;
+    let commits = extract_round_commits(receipt)?;
+
+    if commits.is_empty() {
+        return Err(ProofError::Rejected("no round commits found in receipt".into()));
+    }
+
+    // Reject receipts whose commits do not form one coherent sequence
+    check_commit_chain(&commits)?;
+
+    // Ensure the receiver's known commitment is the pre-image of the first commit
+    if commits[0].old_state != expected_old {
+        return Err(ProofError::BaseStateMismatch);
+    }
+
+    // Ensure one of the commits corresponds to the shot in question
+    if !commits.iter().any(|c| c.shot == shot) {
+        return Err(ProofError::Rejected("receipt does not contain a commit for the requested shot".into()));
+    }
+
+    // If match/session binding was requested, ensure at least one commit
+    // in the proof carries the expected match id and sequence number.
+    if let Some(exp_mid) = expected_match {
+        if let Some(exp_seq) = expected_seq {
+            if !commits.iter().any(|c| c.match_id == exp_mid && c.seq == exp_seq) {
+                return Err(ProofError::NotBound);
+            }
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Check that a receipt's commits describe one plausible game sequence:
+/// - each commit starts from the previous commit's `new_state`
+/// - every shot is in bounds and targets a distinct cell
+///
+/// A receipt that passes `receipt.verify` could otherwise splice unrelated
+/// commits or repeat a cell to replay an earlier hit.
+pub fn check_commit_chain(commits: &[RoundCommit]) -> Result<(), ProofError> {
+    for (i, c) in commits.iter().enumerate() {
+        if !c.shot.in_bounds() {
+            return Err(ProofError::Rejected(format!("commit {} has out-of-bounds shot {:?}", i, c.shot)));
+        }
+        if commits[..i].iter().any(|prev| prev.shot == c.shot) {
+            return Err(ProofError::Rejected(format!("commit {} repeats shot {:?}", i, c.shot)));
+        }
+        if i > 0 && commits[i - 1].new_state != c.old_state {
+            return Err(ProofError::Rejected(format!("commit chain broken between commits {} and {}", i - 1, i)));
+        }
+        if !c.reveal_is_well_formed() {
+            return Err(ProofError::Rejected(format!("commit {} reveals cells {:?} that are not the footprint of a {:?} result", i, c.sunk_cells, c.hit)));
+        }
+    }
+    Ok(())
+}
+
+/// Open a hidden sinking once the game is over. `rc` is a verified commit
+/// whose `hit` is `HitType::SunkHidden`; `opening` must open it, and
+/// `revealed`, the defender's board as revealed at the end of the game
+/// (checked against its placement commitment by the caller), must have a
+/// ship of the opened type covering `rc.shot`. Returns the disclosed
+/// `HitType::Sunk`.
+pub fn check_ship_type_reveal(rc: &RoundCommit, opening: &ShipTypeOpening, revealed: &GameState) -> Result<HitType, ProofError> {
+    if !matches!(rc.hit, HitType::SunkHidden(_)) {
+        return Err(ProofError::Rejected(format!("commit for {:?} is {:?}, not a hidden sinking", rc.shot, rc.hit)));
+    }
+    if !opening.opens(&rc.hit) {
+        return Err(ProofError::Rejected(format!("opening does not match the ship type commitment for {:?}", rc.shot)));
+    }
+    if !revealed.ships.iter().any(|s| s.ship_type == opening.ship_type && s.footprint_contains(rc.shot)) {
+        return Err(ProofError::Rejected(format!("revealed board has no {:?} at {:?}", opening.ship_type, rc.shot)));
+    }
+    Ok(HitType::Sunk(opening.ship_type))
+}
+
+/// Deserialize the receipt in `pd`, after checking its version tag.
+pub fn receipt_from_proofdata(pd: &crate::network_protocol::ProofData) -> Result<Receipt, ProofError> {
+    pd.check_version()?;
+    decode_receipt(&pd.receipt_bytes)
+}
+
+fn decode_receipt(bytes: &[u8]) -> Result<Receipt, ProofError> {
+    bincode::deserialize(bytes).map_err(|e| ProofError::Encoding(format!("deserializing Receipt from bytes: {}", e)))
+}
+
+/// Verify a receipt for a shooter (who does not hold the defender's full
+/// GameState). Runs `verify_remote_round_proof` against `expected_old`,
+/// then finds the commit for `shot` bound to the provided match/seq and
+/// returns it (its new_state is what the shooter adopts as the opponent's
+/// updated commitment). A commit the guest flagged as `invalid` is
+/// `ProofError::InvalidShot`.
+///
+/// With `expected_turn`, the shooter's turn state before the shot, the
+/// proof must also have been made for a shot by `expected_turn.to_move`
+/// from that state, which the guest checked against the agreed
+/// `TurnRules`; otherwise `ProofError::TurnMismatch`.
+pub fn verify_shot_result_for_shooter(receipt: &Receipt, expected_old: Digest, shot: Position, expected_match: Option<Uuid>, expected_seq: Option<u64>, expected_turn: Option<TurnState>) -> Result<RoundCommit, ProofError> {
+    let commits = verify_remote_round_proof(receipt, expected_old, shot, expected_match, expected_seq)?;
+    if let Some(turn) = expected_turn {
+        check_turn(receipt, turn, &[turn.to_move])?;
+    }
+
+    // Locate the commit matching the shot and optional binding
+    let mut found: Option<RoundCommit> = None;
+    for c in commits.iter() {
+        if c.shot == shot {
+            if let (Some(mid), Some(sq)) = (expected_match, expected_seq) {
+                if c.match_id == mid && c.seq == sq {
+                    found = Some(c.clone());
+                    break;
+                }
+            } else {
+                found = Some(c.clone());
+                break;
+            }
+        }
+    }
+
+    match found {
+        Some(RoundCommit { invalid: Some(e), .. }) => Err(ProofError::InvalidShot(e)),
+        Some(c) => Ok(c),
+        None => Err(ProofError::NotBound),
+    }
+}
+
+/// Verify a salvo receipt for the shooter. The receipt must contain one
+/// RoundCommit per shot in `shots`, in the same order, chained so each
+/// commit's `old_state` is the previous commit's `new_state` and the first
+/// starts from `expected_old`. When `expected_match`/`expected_seq` are
+/// given every commit must carry them, and none may be flagged `invalid`.
+/// Returns the commits in salvo order; the last commit's `new_state` is the
+/// opponent's updated commitment. `expected_turn` is checked as in
+/// `verify_shot_result_for_shooter`, except that a salvo names no owners.
+pub fn verify_salvo_result_for_shooter(receipt: &Receipt, expected_old: Digest, shots: &[Position], expected_match: Option<Uuid>, expected_seq: Option<u64>, expected_turn: Option<TurnState>) -> Result<Vec<RoundCommit>, ProofError> {
+    verify_receipt(receipt)?;
+    let commits = extract_round_commits(receipt)?;
+    check_salvo_commits(&commits, expected_old, shots, expected_match, expected_seq)?;
+    if let Some(turn) = expected_turn {
+        check_turn(receipt, turn, &[])?;
+    }
+    Ok(commits)
+}
+
+/// Structural checks behind `verify_salvo_result_for_shooter`, split out so
+/// they can be exercised without a prover.
+pub fn check_salvo_commits(commits: &[RoundCommit], expected_old: Digest, shots: &[Position], expected_match: Option<Uuid>, expected_seq: Option<u64>) -> Result<(), ProofError> {
+    if commits.len() != shots.len() {
+        return Err(ProofError::Rejected(format!("salvo proof has {} commits for {} shots", commits.len(), shots.len())));
+    }
+    check_commit_chain(commits)?;
+    let mut prev = expected_old;
+    for (c, shot) in commits.iter().zip(shots) {
+        if c.shot != *shot {
+            return Err(ProofError::Rejected(format!("salvo commit for {:?} does not match requested shot {:?}", c.shot, shot)));
+        }
+        if c.old_state != prev {
+            return Err(ProofError::Rejected(format!("salvo commit chain broken at shot {:?}", shot)));
+        }
+        if let Some(e) = c.invalid {
+            return Err(ProofError::InvalidShot(e));
+        }
+        if let (Some(mid), Some(sq)) = (expected_match, expected_seq) {
+            if c.match_id != mid || c.seq != sq {
+                return Err(ProofError::NotBound);
+            }
+        }
+        prev = c.new_state;
+    }
+    Ok(())
+}
+
+/// Verify a setup proof from `BoardReady` (see
+/// `crate::proofs::prove_board_setup`): the receipt must verify against
+/// `METHOD_ID` and its journal must hold no shots, only the placement hash
+/// `placement` and board commit `state_commitment` announced in the same
+/// `BoardReady` and the agreed `fleet`. The guest only commits a board
+/// that is exactly the fleet under its rules, so this proves the
+/// opponent's hidden board is legal.
+pub fn verify_setup_proof(proof: &crate::network_protocol::SetupProof, placement: Digest, state_commitment: Digest, fleet: &FleetSpec) -> Result<(), ProofError> {
+    crate::network_protocol::check_proof_version(proof.version)?;
+    let receipt = decode_receipt(&proof.receipt_bytes)?;
+    verify_receipt(&receipt)?;
+    let journal = decode_journal(&receipt.journal.bytes)?;
+    if !journal.commits.is_empty() {
+        return Err(ProofError::Rejected(format!("setup proof carries {} shots", journal.commits.len())));
+    }
+    if journal.fleet != fleet.digest() {
+        return Err(ProofError::FleetMismatch);
+    }
+    if journal.placement != placement {
+        return Err(ProofError::PlacementMismatch);
+    }
+    if journal.initial != state_commitment {
+        return Err(ProofError::BaseStateMismatch);
+    }
+    Ok(())
+}
+
+/// Verify a standalone `ProofData`: the receipt must verify against
+/// `METHOD_ID`, its commits must form a valid chain, and the claimed
+/// `pd.commit` must be one of the journal's commits. Returns that commit.
+/// Callers that track commitments should additionally compare
+/// `old_state` against their own record.
+pub fn verify_proofdata(pd: &crate::network_protocol::ProofData) -> Result<RoundCommit, ProofError> {
+    let receipt = receipt_from_proofdata(pd)?;
+    verify_receipt(&receipt)?;
+    let commits = extract_round_commits(&receipt)?;
+    check_commit_chain(&commits)?;
+    if !commits.contains(&pd.commit) {
+        return Err(ProofError::Rejected("claimed commit is not present in the receipt journal".into()));
+    }
+    Ok(pd.commit.clone())
+}
@@ -1,136 +1,113 @@
 // Simple ASCII visualization helpers for player boards.
 //
-// This module provides functions to pretty-print a `PlayerBoard` produced
-// by `board_init.rs`. It supports optionally hiding ship positions so the
+// This module provides functions to pretty-print a `core::GameState`, such
+// as one produced by `board_init.rs`. It supports optionally hiding ship positions so the
 // opponent's board can be displayed without revealing ship locations.
+// Each board is drawn at its own `dims`, so rectangular boards render as
+// `height` rows of `width` columns. Hits are `X`, and cells of a sunk ship
+// `#` when the board marks them (`GameState::mark_sunk_cells`).
+//
+// Every column is as wide as its widest entry: the largest column index
+// (two characters at least) or a cell glyph of `BoardStyle::glyph_width`
+// terminal columns, so headers and cells stay aligned with wide glyphs
+// such as emoji.
 
-use core::{GameState, CellState, BOARD_SIZE};
+use core::{BoardDims, GameState, CellState};
 
-/// Render a single `GameState` to stdout. If `reveal_ships` is false,
-/// ship cells (derived from `GameState.ships`) are hidden unless they are
-/// hit in the grid.
-pub fn display_board(state: &GameState, reveal_ships: bool) {
-    // Header
-    print!("   ");
-    for x in 0..BOARD_SIZE { print!("{:2} ", x); }
-    println!();
-    // Build a fast lookup of ship-occupied cells when revealing ships
-    let mut ship_map = vec![vec![false; BOARD_SIZE]; BOARD_SIZE];
-    if reveal_ships {
-        for ship in &state.ships {
-            for p in ship.get_coordinates() {
-                let x = p.x as usize;
-                let y = p.y as usize;
-                ship_map[y][x] = true;
-            }
+/// The glyphs a board is drawn with. The default is the ASCII set above.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardStyle {
+    pub empty: char,
+    /// An unshot ship cell, when ships are revealed.
+    pub ship: char,
+    pub miss: char,
+    pub hit: char,
+    pub sunk: char,
+    /// Terminal columns each glyph takes: 1 for ASCII, 2 for most emoji.
+    pub glyph_width: usize,
+}
+
+impl Default for BoardStyle {
+    fn default() -> Self {
+        Self { empty: '.', ship: 'S', miss: 'o', hit: 'X', sunk: '#', glyph_width: 1 }
+    }
+}
+
+impl BoardStyle {
+    fn glyph(&self, cell: CellState, ship_shown: bool) -> char {
+        match cell {
+            CellState::Empty => if ship_shown { self.ship } else { self.empty },
+            CellState::Miss => self.miss,
+            CellState::Hit => self.hit,
+            CellState::Sunk => self.sunk,
         }
     }
-    for y in 0..BOARD_SIZE {
-        print!("{:2} ", y);
-        for x in 0..BOARD_SIZE {
-            let cell = state.grid[y][x];
-            let ch = match cell {
-                CellState::Empty => {
-                    if reveal_ships && ship_map[y][x] { 'S' } else { '.' }
-                }
-                CellState::Miss => 'o',
-                CellState::Hit => 'X',
-            };
-            print!(" {ch} ");
+
+    /// Widths of the row label and of each column for a board of `dims`.
+    fn layout(&self, dims: BoardDims) -> (usize, usize) {
+        let digits = |n: u32| n.saturating_sub(1).to_string().len().max(2);
+        (digits(dims.height), digits(dims.width).max(self.glyph_width))
+    }
+}
+
+/// The header line and one line per row of `state`, as drawn by
+/// `display_board_str_with`, without newlines.
+fn board_lines(state: &GameState, reveal_ships: bool, style: &BoardStyle) -> Vec<String> {
+    let (width, height) = (state.dims.width as usize, state.dims.height as usize);
+    let (label, column) = style.layout(state.dims);
+    let mut header = " ".repeat(label + 1);
+    for x in 0..width { header.push_str(&format!("{:>column$} ", x)); }
+    let mut lines = vec![header];
+    // Build a fast lookup of ship-occupied cells
+    let ship_map = state.occupancy();
+    for y in 0..height {
+        let mut line = format!("{:>label$} ", y);
+        for x in 0..width {
+            line.push_str(&" ".repeat(column - style.glyph_width));
+            line.push(style.glyph(state.grid[y][x], reveal_ships && ship_map[y][x].is_some()));
+            line.push(' ');
         }
-        println!();
+        lines.push(line);
     }
+    lines
+}
+
+/// Render a single `GameState` to stdout. If `reveal_ships` is false,
+/// ship cells (derived from `GameState.ships`) are hidden unless they are
+/// hit in the grid.
+pub fn display_board(state: &GameState, reveal_ships: bool) {
+    display_board_with(state, reveal_ships, &BoardStyle::default());
+}
+
+/// `display_board` drawn with `style`'s glyphs.
+pub fn display_board_with(state: &GameState, reveal_ships: bool, style: &BoardStyle) {
+    print!("{}", display_board_str_with(state, reveal_ships, style));
 }
 
 /// Render a `GameState` to a String instead of printing. Useful for tests
 /// that need to assert the output (hide/reveal behavior).
 pub fn display_board_str(state: &GameState, reveal_ships: bool) -> String {
-    let mut out = String::new();
-    out.push_str("   ");
-    for x in 0..BOARD_SIZE { out.push_str(&format!("{:2} ", x)); }
-    out.push('\n');
-
-    let mut ship_map = vec![vec![false; BOARD_SIZE]; BOARD_SIZE];
-    if reveal_ships {
-        for ship in &state.ships {
-            for p in ship.get_coordinates() {
-                let x = p.x as usize;
-                let y = p.y as usize;
-                ship_map[y][x] = true;
-            }
-        }
-    }
+    display_board_str_with(state, reveal_ships, &BoardStyle::default())
+}
 
-    for y in 0..BOARD_SIZE {
-        out.push_str(&format!("{:2} ", y));
-        for x in 0..BOARD_SIZE {
-            let cell = state.grid[y][x];
-            let ch = match cell {
-                CellState::Empty => {
-                    if reveal_ships && ship_map[y][x] { 'S' } else { '.' }
-                }
-                CellState::Miss => 'o',
-                CellState::Hit => 'X',
-            };
-            out.push_str(&format!(" {ch} "));
-        }
-        out.push('\n');
-    }
-    out
+/// `display_board_str` drawn with `style`'s glyphs.
+pub fn display_board_str_with(state: &GameState, reveal_ships: bool, style: &BoardStyle) -> String {
+    board_lines(state, reveal_ships, style).into_iter().map(|line| line + "\n").collect()
 }
 
 /// Display both players' boards side-by-side. `reveal_self` will reveal the
-/// left player's ships; the right player's ships remain hidden.
+/// left player's ships; the right player's ships remain hidden. If one
+/// board is taller, the other side is padded with blanks.
 pub fn display_dual(left: &GameState, right: &GameState, reveal_left: bool) {
-    // Left header
-    print!("   ");
-    for x in 0..BOARD_SIZE { print!("{:2} ", x); }
-    print!("    ");
-    // Right header
-    print!("   ");
-    for x in 0..BOARD_SIZE { print!("{:2} ", x); }
-    println!();
-    // Precompute ship maps
-    let mut left_map = vec![vec![false; BOARD_SIZE]; BOARD_SIZE];
-    if reveal_left {
-        for ship in &left.ships {
-            for p in ship.get_coordinates() {
-                left_map[p.y as usize][p.x as usize] = true;
-            }
-        }
-    }
-
-    let mut right_map = vec![vec![false; BOARD_SIZE]; BOARD_SIZE];
-    for ship in &right.ships {
-        for p in ship.get_coordinates() {
-            right_map[p.y as usize][p.x as usize] = true;
-        }
-    }
-
-    for y in 0..BOARD_SIZE {
-        // left
-        print!("{:2} ", y);
-        for x in 0..BOARD_SIZE {
-            let cell = left.grid[y][x];
-            let ch = match cell {
-                CellState::Empty => if reveal_left && left_map[y][x] { 'S' } else { '.' },
-                CellState::Miss => 'o',
-                CellState::Hit => 'X',
-            };
-            print!(" {ch} ");
-        }
-        print!("    ");
-        // right (never reveal ships)
-        print!("{:2} ", y);
-        for x in 0..BOARD_SIZE {
-            let cell = right.grid[y][x];
-            let ch = match cell {
-                CellState::Empty => '.',
-                CellState::Miss => 'o',
-                CellState::Hit => 'X',
-            };
-            print!(" {ch} ");
+    let style = BoardStyle::default();
+    let (left_lines, right_lines) = (board_lines(left, reveal_left, &style), board_lines(right, false, &style));
+    let (label, column) = style.layout(left.dims);
+    let left_columns = label + 1 + (column + 1) * left.dims.width as usize;
+    for i in 0..left_lines.len().max(right_lines.len()) {
+        match left_lines.get(i) {
+            Some(line) => print!("{}    ", line),
+            None => print!("{:left_columns$}    ", ""),
         }
-        println!();
+        println!("{}", right_lines.get(i).map_or("", String::as_str));
     }
 }
@@ -1,5 +1,7 @@
-use core::{GameState, ShipType, Direction, Position, HitType, BOARD_SIZE};
-use host::visualize::display_board_str;
+use core::{evaluate_outcome, BoardDims, GameOutcome, GameState, ShipType, Direction, Position, HitType, BOARD_SIZE};
+use host::board_init::place_ships_from;
+use host::game_round::{demo_boards, resolve_shot, run_demo, TurnOutcome};
+use host::visualize::{display_board_str, display_board_str_with, BoardStyle};
 use rand::{SeedableRng, rngs::StdRng, Rng};
 
 // Comprehensive test suite merging prior e2e tests and adding randomized
@@ -18,6 +20,35 @@ fn test_basic_hit_then_sunk_behavior() {
     assert_eq!(p2.apply_shot(Position::new(1,0)), Some(HitType::Sunk(ShipType::Destroyer)));
 }
 
+#[test]
+fn test_turn_outcome_reports_sunk_and_extra_shot() {
+    let rules = core::TurnRules::default();
+    let mut p2 = GameState::new([0;16]);
+    p2.place_ship(ShipType::Destroyer, Position::new(0,0), Direction::Horizontal);
+    p2.place_ship(ShipType::Submarine, Position::new(0,2), Direction::Horizontal);
+
+    assert_eq!(resolve_shot(&mut p2, Position::new(0,0), &rules), Some(TurnOutcome { result: HitType::Hit, game_over: false, extra_shot: true }));
+    assert_eq!(resolve_shot(&mut p2, Position::new(1,0), &rules), Some(TurnOutcome { result: HitType::Sunk(ShipType::Destroyer), game_over: false, extra_shot: false }));
+    assert_eq!(resolve_shot(&mut p2, Position::new(1,0), &rules), None);
+}
+
+#[test]
+fn test_demo_boards_play_to_completion() {
+    run_demo();
+    let (p1, mut p2) = demo_boards();
+    assert!(p1.is_placement_complete(&core::FleetSpec::classic()));
+    assert!(p2.is_placement_complete(&core::FleetSpec::classic()));
+
+    // Sweep Player 2's columns; the last shot ends the game
+    let rules = core::TurnRules::default();
+    let cells: Vec<Position> = [(0, 5), (2, 4), (4, 3), (6, 3), (8, 2)].into_iter().flat_map(|(x, len)| (0..len).map(move |y| Position::new(x, y))).collect();
+    for (i, &pos) in cells.iter().enumerate() {
+        let outcome = resolve_shot(&mut p2, pos, &rules).expect("fresh in-bounds shot");
+        assert_ne!(outcome.result, HitType::Miss);
+        assert_eq!(outcome.game_over, i + 1 == cells.len());
+    }
+}
+
 #[test]
 fn test_miss_and_repeated_shot_behavior() {
     let mut p = GameState::new([0;16]);
@@ -40,6 +71,91 @@ fn test_visualization_hidden_and_revealed() {
     assert!(revealed.contains('S'));
 }
 
+#[test]
+fn test_board_columns_line_up_with_wide_glyphs() {
+    // BoardDims stops at BOARD_SIZE, so the widest board is 10 columns
+    let dims = BoardDims::new(BOARD_SIZE as u32, 7).unwrap();
+    let mut p = GameState::with_dims([0;16], dims);
+    assert!(p.place_ship(ShipType::Carrier, Position::new(5,2), Direction::Horizontal));
+    p.apply_shot(Position::new(9,2));
+    p.apply_shot(Position::new(0,6));
+    let emoji = BoardStyle { empty: '🌊', ship: '🚢', miss: '⚪', hit: '💥', sunk: '🔥', glyph_width: 2 };
+
+    for style in [BoardStyle::default(), emoji] {
+        // Terminal column at which each whitespace-separated token ends
+        let token_ends = |line: &str| {
+            let (mut col, mut ends, mut in_token) = (0, Vec::new(), false);
+            for ch in line.chars() {
+                if ch == ' ' {
+                    if in_token { ends.push(col); }
+                    in_token = false;
+                    col += 1;
+                } else {
+                    in_token = true;
+                    col += if ch.is_ascii() { 1 } else { style.glyph_width };
+                }
+            }
+            if in_token { ends.push(col); }
+            ends
+        };
+        let rendered = display_board_str_with(&p, true, &style);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 1 + 7);
+        let header = token_ends(lines[0]);
+        assert_eq!(header.len(), BOARD_SIZE);
+        for line in &lines[1..] {
+            // Skip the row label; every cell ends where its column header does
+            assert_eq!(token_ends(line)[1..], header[..], "{:?} misaligned under\n{}", line, lines[0]);
+        }
+        assert!(lines[3].contains(style.ship) && lines[3].contains(style.hit) && lines[7].contains(style.miss));
+    }
+    assert_eq!(display_board_str_with(&p, false, &BoardStyle::default()), display_board_str(&p, false));
+}
+
+#[test]
+fn test_seeded_prompt_placement_is_deterministic() {
+    let place = |script: &str, seed: u64| place_ships_from("P", &mut std::io::Cursor::new(script), &mut StdRng::seed_from_u64(seed));
+
+    let random = place("R\n", 42);
+    assert!(random.is_placement_complete(&core::FleetSpec::classic()));
+    assert_eq!(random, place("R\n", 42));
+    assert_ne!(random.ships, place("R\n", 43).ships);
+
+    // Manual carrier, then the rest filled from the same seeded RNG
+    let mixed = place("M\n0 9 H\nR\n", 42);
+    assert!(mixed.is_placement_complete(&core::FleetSpec::classic()));
+    assert_eq!(mixed.ships[0].position, Position::new(0, 9));
+    assert_eq!(mixed, place("M\n0 9 H\nR\n", 42));
+}
+
+#[test]
+fn test_rectangular_board_places_and_renders_ten_by_eight() {
+    let dims = BoardDims::new(10, 8).expect("10x8 fits the grid");
+    let mut p = GameState::with_dims([0;16], dims);
+
+    // Row 7 is the bottom row; row 8 would be on a square board
+    assert!(!p.can_place_ship(ShipType::Carrier, Position::new(0,8), Direction::Horizontal));
+    assert!(!p.can_place_ship(ShipType::Carrier, Position::new(5,7), Direction::Vertical));
+    assert!(p.place_ship(ShipType::Carrier, Position::new(5,7), Direction::Horizontal));
+    assert_eq!(p.apply_shot(Position::new(0,8)), None, "row 8 is off a 10x8 board");
+    assert_eq!(p.unshot_cells().len(), 80);
+
+    let rendered = display_board_str(&p, true);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines.len(), 1 + 8, "header plus 8 rows");
+    assert_eq!(lines[0].split_whitespace().count(), 10);
+    for (y, line) in lines[1..].iter().enumerate() {
+        let cells: Vec<&str> = line.split_whitespace().skip(1).collect();
+        assert_eq!(cells.len(), 10, "row {} should have 10 columns", y);
+    }
+    assert_eq!(lines[8].split_whitespace().collect::<Vec<_>>(), ["7", ".", ".", ".", ".", ".", "S", "S", "S", "S", "S"]);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut random = GameState::with_dims([0;16], dims);
+    assert!(random.place_ships_randomly(&mut rng));
+    assert!(random.ships.iter().flat_map(|s| s.get_coordinates()).all(|c| c.y < 8));
+}
+
 #[test]
 fn test_randomized_full_game_simulations() {
     // Run several seeded simulations to increase confidence
@@ -86,7 +202,7 @@ fn test_randomized_full_game_simulations() {
                             match p2.apply_shot(Position::new(x as u32, y as u32)) {
                                 Some(HitType::Miss) => { turn = 1; break; }
                                 Some(HitType::Hit) => { break; }
-                                Some(HitType::Sunk(_)) => { turn = 1; break; }
+                                Some(HitType::Sunk(_) | HitType::SunkHidden(_)) => { turn = 1; break; }
                                 None => continue,
                             }
                         }
@@ -107,7 +223,7 @@ fn test_randomized_full_game_simulations() {
                             match p1.apply_shot(Position::new(x as u32, y as u32)) {
                                 Some(HitType::Miss) => { turn = 0; break; }
                                 Some(HitType::Hit) => { break; }
-                                Some(HitType::Sunk(_)) => { turn = 0; break; }
+                                Some(HitType::Sunk(_) | HitType::SunkHidden(_)) => { turn = 0; break; }
                                 None => continue,
                             }
                         }
@@ -120,9 +236,8 @@ fn test_randomized_full_game_simulations() {
         }
 
         // ensure one side lost
-        let p1_all = p1.ships.iter().all(|s| s.is_sunk());
-        let p2_all = p2.ships.iter().all(|s| s.is_sunk());
-        assert!(p1_all ^ p2_all, "Exactly one player's fleet should be sunk (seed {})", seed);
+        let outcome = evaluate_outcome(&p1, &p2, false);
+        assert!(matches!(outcome, GameOutcome::Winner(_)), "Exactly one player's fleet should be sunk (seed {}): {:?}", seed, outcome);
     }
 }
 
@@ -202,3 +317,82 @@ fn test_place_ships_randomly_and_check() {
     assert!(s.check(), "state.check() should be true after random placement");
 }
 
+#[test]
+fn test_scripted_demo_hits_move_cap_as_draw() {
+    // The demo script ends with both fleets afloat.
+    assert_eq!(host::game::run_game_master_demo(core::TurnRules::default()), GameOutcome::Draw);
+}
+
+#[test]
+fn test_scripted_demo_reports_shot_stats() {
+    use core::Player;
+    // P1 misses at (9,9); P2 hits (0,0), keeps the turn and misses (0,1);
+    // P1 hits P2's vertical carrier at (0,2).
+    let report = host::game::run_game_master_report(core::TurnRules::default());
+    assert_eq!(report.outcome, GameOutcome::Draw);
+    let p1 = report.stats(Player::One);
+    assert_eq!((p1.shots, p1.hits, p1.turns), (2, 1, 2));
+    assert_eq!(p1.hit_rate(), 0.5);
+    let p2 = report.stats(Player::Two);
+    assert_eq!((p2.shots, p2.hits, p2.turns), (2, 1, 1));
+    assert!(p1.sunk.is_empty() && p2.sunk.is_empty());
+
+    // Without extra shots on a hit the same script alternates shooters:
+    // P1 misses, P2 hits (0,0), P1 hits (0,1), P2 hits (0,2).
+    let strict = core::TurnRules { extra_turn_on_hit: false, extra_turn_on_sunk: false };
+    let report = host::game::run_game_master_report(strict);
+    let p1 = report.stats(Player::One);
+    assert_eq!((p1.shots, p1.hits, p1.turns), (2, 1, 2));
+    let p2 = report.stats(Player::Two);
+    assert_eq!((p2.shots, p2.hits, p2.turns), (2, 2, 2));
+}
+
+#[test]
+fn test_proven_shot_updates_view_from_commit() {
+    use host::game_round::{resolve_shot_proven, ProofMode};
+    let rules = core::TurnRules::default();
+    let (_, mut opponent) = demo_boards();
+    let mut view = GameState::new([0;16]);
+    let before = opponent.clone();
+
+    // Illegal shots never reach the guest
+    let mut statuses = Vec::new();
+    let off_board = resolve_shot_proven(&mut opponent, &mut view, Position::new(10, 0), &rules, ProofMode::ExecuteOnly, &mut |s| statuses.push(s.to_string()));
+    assert!(off_board.unwrap().is_none());
+    assert!(statuses.is_empty());
+
+    // The Destroyer at (8,0)-(8,1): hit, then sunk
+    for (pos, expected) in [(Position::new(8, 0), HitType::Hit), (Position::new(8, 1), HitType::Sunk(ShipType::Destroyer))] {
+        let res = resolve_shot_proven(&mut opponent, &mut view, pos, &rules, ProofMode::ExecuteOnly, &mut |s| statuses.push(s.to_string()));
+        let (outcome, rc) = match res {
+            Ok(r) => r.expect("fresh in-bounds shot"),
+            Err(e) => {
+                eprintln!("guest unavailable, skipping proven GUI shot test: {}", e);
+                assert_eq!(opponent, before, "a failed guest run must not touch the board");
+                return;
+            }
+        };
+        assert_eq!(outcome.result, expected);
+        assert_eq!(rc.hit, expected);
+        assert_eq!(rc.new_state, opponent.commit());
+    }
+    assert_eq!(statuses, ["proving...", "proving..."]);
+    assert_eq!(view.grid[0][8], core::CellState::Hit);
+    assert_eq!(view.grid[1][8], core::CellState::Hit);
+    assert_eq!(view.unshot_cells().len(), BOARD_SIZE * BOARD_SIZE - 2);
+}
+
+#[test]
+fn test_hunt_target_beats_random_over_a_seeded_tournament() {
+    use host::tournament::{run, HuntTargetStrategy, RandomStrategy};
+    let games = 100;
+    let result = run(&mut HuntTargetStrategy::default(), &mut RandomStrategy, games, 2024);
+    assert_eq!(result.a.wins + result.b.wins, games);
+    assert_eq!(result, run(&mut HuntTargetStrategy::default(), &mut RandomStrategy, games, 2024), "a seed replays the same tournament");
+
+    // Under a fair coin the win count has mean 50 and standard deviation 5;
+    // demand more than four deviations (one-sided p < 0.0001)
+    let z = (result.a.wins as f64 - games as f64 / 2.0) / (games as f64 / 4.0).sqrt();
+    assert!(z > 4.0, "hunt-and-target won only {} of {} games", result.a.wins, games);
+    assert!(result.a.avg_shots_to_win().unwrap() < 80.0, "{:?}", result);
+}
@@ -0,0 +1,48 @@
+use core::Player;
+use host::clock::{Clock, TimeSource};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time source that only moves when the test advances it.
+#[derive(Clone, Default)]
+struct FakeTime(Arc<Mutex<Duration>>);
+
+impl FakeTime {
+    fn advance(&self, secs: u64) {
+        *self.0.lock().unwrap() += Duration::from_secs(secs);
+    }
+}
+
+impl TimeSource for FakeTime {
+    fn now(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[test]
+fn thinking_time_accrues_to_the_player_on_the_clock() {
+    let time = FakeTime::default();
+    let mut clock = Clock::with_source(Box::new(time.clone()));
+
+    clock.start(Player::One);
+    time.advance(3);
+    // Re-starting the running player must not reset their stretch
+    clock.start(Player::One);
+    time.advance(2);
+    clock.stop();
+
+    // Proving happens off the clock
+    time.advance(60);
+
+    clock.start(Player::Two);
+    time.advance(4);
+    assert_eq!(clock.total(Player::Two), Duration::from_secs(4), "running time counts before stop");
+    // Handing over banks Player Two's time
+    clock.start(Player::One);
+    time.advance(1);
+    clock.stop();
+
+    assert_eq!(clock.total(Player::One), Duration::from_secs(6));
+    assert_eq!(clock.total(Player::Two), Duration::from_secs(4));
+    assert_eq!(clock.to_string(), "Thinking time: P1 6.0s, P2 4.0s");
+}
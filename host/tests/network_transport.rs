@@ -0,0 +1,982 @@
+use anyhow::Result;
+use core::{GameState, HitType, Position, RoundCommit};
+use host::network::{bind_match_secret, connect_tcp, NetworkConnection, DEFAULT_MAX_MESSAGE_BYTES};
+use host::network_protocol::{ProofData, ProofReassembler, MAX_PROOF_BYTES, PROOF_CHUNK_SIZE};
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+// Transport-level tests over a loopback TCP pair. TLS and the DH exchange
+// are skipped; both ends share a fixed match secret so HMAC checks still run.
+
+/// Returns (host, client) connections that have completed the BoardReady
+/// handshake and therefore agree on a match id.
+fn connected_pair() -> Result<(NetworkConnection, NetworkConnection)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let client = TcpStream::connect(addr)?;
+    let (server, _) = listener.accept()?;
+    let secret = vec![7u8; 32];
+    let mut host = NetworkConnection::from_stream(server, Some(secret.clone()));
+    let mut client = NetworkConnection::from_stream(client, Some(secret));
+
+    let board = GameState::new([0; 16]);
+    let (placement, commit) = (board.hash_ships_only(), board.commit());
+    let handle = thread::spawn(move || -> Result<NetworkConnection> {
+        client.handshake_as_client("client", placement, commit, None)?;
+        Ok(client)
+    });
+    host.handshake_as_host("host", placement, commit, None)?;
+    let client = handle.join().expect("client handshake thread panicked")?;
+    Ok((host, client))
+}
+
+fn sample_commit() -> RoundCommit {
+    let state = GameState::new([0; 16]);
+    RoundCommit {
+        match_id: uuid::Uuid::nil(),
+        seq: 3,
+        old_state: state.commit(),
+        new_state: state.commit(),
+        shot: Position::new(4, 2),
+        hit: HitType::Hit,
+        sunk_cells: vec![],
+        revealed_water: vec![],
+        invalid: None,
+    }
+}
+
+#[test]
+fn large_proof_chunks_reassemble_exactly() -> Result<()> {
+    let (mut sender, mut receiver) = connected_pair()?;
+
+    // 2 MB of non-repeating bytes so misplaced chunks would be detected
+    let receipt_bytes: Vec<u8> = (0..2 * 1024 * 1024u32).map(|i| (i.wrapping_mul(31) >> 3) as u8).collect();
+    let pd = ProofData::from_bytes(receipt_bytes.clone(), sample_commit());
+
+    let handle = thread::spawn(move || sender.send_large_proof(&pd));
+    let received = receiver.receive_large_proof()?;
+    handle.join().expect("sender thread panicked")?;
+
+    assert_eq!(received.receipt_bytes.len(), receipt_bytes.len());
+    assert!(received.receipt_bytes == receipt_bytes, "reassembled bytes differ");
+    assert_eq!(received.commit, sample_commit());
+    Ok(())
+}
+
+#[test]
+fn proof_reassembler_refuses_oversized_transfers_up_front() {
+    // An announced total past MAX_PROOF_BYTES is refused on the first chunk
+    let too_many = (MAX_PROOF_BYTES / PROOF_CHUNK_SIZE) as u32 + 1;
+    let mut reassembler = ProofReassembler::new();
+    assert!(reassembler.push(0, too_many, vec![0; PROOF_CHUNK_SIZE]).is_err());
+    assert!(ProofReassembler::new().push(0, u32::MAX, vec![0]).is_err());
+
+    // So is a single chunk larger than PROOF_CHUNK_SIZE
+    assert!(ProofReassembler::new().push(0, 2, vec![0; PROOF_CHUNK_SIZE + 1]).is_err());
+
+    // The largest allowed total is still accepted chunk by chunk
+    let max_total = (MAX_PROOF_BYTES / PROOF_CHUNK_SIZE) as u32;
+    assert!(matches!(ProofReassembler::new().push(0, max_total, vec![0; PROOF_CHUNK_SIZE]), Ok(None)));
+}
+
+#[test]
+fn framed_payload_with_newline_round_trips() -> Result<()> {
+    let (sender, mut receiver) = connected_pair()?;
+
+    let payload = b"first line\nsecond line\n\x00\xff".to_vec();
+    sender.send_framed(&payload)?;
+    sender.send_framed(b"")?;
+    assert_eq!(receiver.recv_framed()?, payload);
+    assert_eq!(receiver.recv_framed()?, Vec::<u8>::new());
+    Ok(())
+}
+
+#[test]
+fn oversize_frame_is_rejected_before_allocation() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let mut raw = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let mut conn = NetworkConnection::from_stream(server, None);
+    assert_eq!(conn.max_message_bytes(), DEFAULT_MAX_MESSAGE_BYTES);
+
+    // Announce a ~4 GB frame but send only a few bytes; a naive reader would
+    // try to allocate the full length up front.
+    raw.write_all(&0xFFFF_FFF0u32.to_be_bytes())?;
+    raw.write_all(b"junk")?;
+    let err = conn.recv_framed().expect_err("oversize frame must be rejected");
+    assert!(err.to_string().contains("exceeds limit"), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn custom_message_limit_applies_to_envelopes() -> Result<()> {
+    let (mut sender, mut receiver) = connected_pair()?;
+    receiver.set_max_message_bytes(1024);
+
+    let pd = ProofData::from_bytes(vec![0xAB; 4096], sample_commit());
+    let msg = host::network_protocol::GameMessage::ShotResult { position: Position::new(4, 2), hit_type: HitType::Hit, proof: pd };
+    sender.send_enveloped(&msg)?;
+    assert!(receiver.receive_enveloped().is_err());
+    Ok(())
+}
+
+#[test]
+fn match_secret_is_bound_to_both_board_commitments() -> Result<()> {
+    let dh = [7u8; 32];
+    let a = GameState::new([1; 16]).commit();
+    let b = GameState::new([2; 16]).commit();
+    let c = GameState::new([3; 16]).commit();
+    let bound = bind_match_secret(&dh, &a, &b);
+    assert_ne!(bound, bind_match_secret(&dh, &a, &c));
+    assert_ne!(bound, bind_match_secret(&dh, &b, &a));
+
+    // A peer that believes the client committed to `c` derives a different
+    // key, so envelopes from the real session fail authentication.
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let mut sender = NetworkConnection::from_stream(server, Some(bound));
+    let mut receiver = NetworkConnection::from_stream(client, Some(bind_match_secret(&dh, &a, &c)));
+    sender.send_enveloped(&host::network_protocol::GameMessage::TakeShot { position: Position::new(1, 1) })?;
+    let err = receiver.receive_enveloped().expect_err("mismatched board binding must fail HMAC");
+    assert!(err.to_string().contains("auth token"), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn handshake_refuses_incomplete_board() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let _peer = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let state = GameState::new([0; 16]);
+    let commit = state.commit();
+    let net = NetworkConnection::from_stream(server, None);
+    let mut coord = host::game::GameCoordinator::new(state, commit, net, "host".into(), true);
+
+    // Fails locally; the silent peer would otherwise leave this blocked
+    let err = coord.handshake().expect_err("empty board must not be committed");
+    assert!(err.to_string().contains("placement is incomplete"), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn prover_failure_is_reported_without_ending_the_match() -> Result<()> {
+    use core::{Direction, GameOutcome, Player, Ship, ShipType};
+    use host::clock::{Clock, TimeSource};
+    use host::network_protocol::GameMessage;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    /// Moves one second on every reading, so each stretch on the clock is 1s.
+    struct TickingTime(AtomicU64);
+    impl TimeSource for TickingTime {
+        fn now(&self) -> Duration {
+            Duration::from_secs(self.0.fetch_add(1, Ordering::SeqCst))
+        }
+    }
+
+    let (mut shooter, defender_net) = connected_pair()?;
+    let mut board = GameState::new([0; 16]);
+    board.place_ship(ShipType::Carrier, Position::new(0, 0), Direction::Horizontal);
+    // Inject an invalid board: a second Carrier overlapping the first, which
+    // validation rejects before the prover is ever started
+    board.ships.push(Ship::new(ShipType::Carrier, Position::new(0, 0), Direction::Vertical));
+    let commit = board.commit();
+    let mut defender = host::game::GameCoordinator::new(board, commit, defender_net, "defender".into(), false);
+    defender.clock = Clock::with_source(Box::new(TickingTime(AtomicU64::new(0))));
+    let handle = thread::spawn(move || defender.play_game());
+
+    shooter.send_enveloped(&GameMessage::TakeShot { position: Position::new(0, 0) })?;
+    match shooter.receive_enveloped()?.payload {
+        GameMessage::Error { message } => assert!(message.contains("invalid guest input"), "unexpected error: {message}"),
+        other => panic!("expected Error, got {:?}", other),
+    }
+
+    // The defender is still in the match and handles the next message
+    shooter.send_enveloped(&GameMessage::GameOver { winner: "defender".into() })?;
+    let result = handle.join().expect("defender thread panicked")?;
+    assert_eq!(result.outcome, GameOutcome::Winner(Player::Two));
+    // The defender only ever waited on Player One, once per message
+    assert_eq!((result.p1_time, result.p2_time), (Duration::from_secs(2), Duration::ZERO));
+    Ok(())
+}
+
+#[test]
+fn connects_over_ipv6_loopback() -> Result<()> {
+    let listener = match TcpListener::bind("[::1]:0") {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("IPv6 loopback unavailable, skipping: {}", e);
+            return Ok(());
+        }
+    };
+    let port = listener.local_addr()?.port();
+
+    // Both the bare and the bracketed literal resolve to ::1
+    for host in ["::1", "[::1]"] {
+        let client = connect_tcp(host, port)?;
+        let (server, peer) = listener.accept()?;
+        assert!(peer.is_ipv6(), "{host} connected from {peer}");
+
+        let sender = NetworkConnection::from_stream(client, None);
+        let mut receiver = NetworkConnection::from_stream(server, None);
+        sender.send_framed(b"over v6")?;
+        assert_eq!(receiver.recv_framed()?, b"over v6");
+    }
+    Ok(())
+}
+
+#[test]
+fn channel_driven_game_reports_through_ui_events() -> Result<()> {
+    use host::network_protocol::GameMessage;
+    use host::ui::{make_channels, UiCommand, UiEvent};
+
+    let (net, mut peer) = connected_pair()?;
+    let board = GameState::new([0; 16]);
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    coord.opponent_commit = Some(board.commit());
+    let (ui, game) = make_channels();
+    let handle = thread::spawn(move || coord.play_game_channels(game.commands, game.events));
+
+    // The first shot is rejected locally; the second reaches the peer, who cannot prove it
+    ui.commands.send(UiCommand::Shoot(Position::new(10, 0)))?;
+    ui.commands.send(UiCommand::Shoot(Position::new(3, 3)))?;
+    drop(ui.commands);
+    match peer.receive_enveloped()?.payload {
+        GameMessage::TakeShot { position } => assert_eq!(position, Position::new(3, 3)),
+        other => panic!("expected TakeShot, got {:?}", other),
+    }
+    peer.send_enveloped(&GameMessage::Error { message: "prover failed: no toolchain".into() })?;
+
+    // With the script exhausted the game stops at the next prompt
+    let err = handle.join().expect("game thread panicked").expect_err("closed command channel must end the game");
+    assert!(err.to_string().contains("command channel closed"), "unexpected error: {err}");
+
+    let events: Vec<UiEvent> = ui.events.iter().collect();
+    assert!(matches!(events[0], UiEvent::Boards { .. }));
+    let logs: Vec<&str> = events.iter().filter_map(|e| if let UiEvent::Log(m) = e { Some(m.as_str()) } else { None }).collect();
+    assert_eq!(logs, ["Position out of bounds", "Opponent could not answer the shot: prover failed: no toolchain. Try again."]);
+    assert_eq!(events.iter().filter(|e| **e == UiEvent::AwaitingShots { count: 1 }).count(), 3);
+    Ok(())
+}
+
+#[test]
+fn channel_driven_game_ends_with_game_over_event() -> Result<()> {
+    use core::{GameOutcome, Player};
+    use host::network_protocol::GameMessage;
+    use host::ui::{make_channels, UiEvent};
+
+    let (mut peer, net) = connected_pair()?;
+    let board = GameState::new([0; 16]);
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "client".into(), false);
+    let (ui, game) = make_channels();
+    let handle = thread::spawn(move || coord.play_game_channels(game.commands, game.events));
+
+    peer.send_enveloped(&GameMessage::GameOver { winner: "client".into() })?;
+    assert_eq!(handle.join().expect("game thread panicked")?.outcome, GameOutcome::Winner(Player::Two));
+    let events: Vec<UiEvent> = ui.events.iter().collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], UiEvent::Log("Game over: winner = client".into()));
+    assert!(matches!(&events[1], UiEvent::Log(m) if m.starts_with("Thinking time: P1 ")), "expected clock summary, got {:?}", events[1]);
+    assert_eq!(events[2], UiEvent::GameOver(GameOutcome::Winner(Player::Two)));
+    Ok(())
+}
+
+#[test]
+fn chat_between_turns_keeps_sequence_valid() -> Result<()> {
+    use host::network_protocol::GameMessage;
+    let (mut host, mut client) = connected_pair()?;
+
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(1, 2) })?;
+    host.send_chat("host", "you sank my battleship")?;
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(3, 4) })?;
+    client.send_chat("client", "not yet")?;
+
+    // The chat is consumed in between the two shots, and the second
+    // shot still has the next sequence number after it.
+    let first = client.receive_enveloped()?;
+    assert!(matches!(first.payload, GameMessage::TakeShot { position } if position == Position::new(1, 2)));
+    assert!(client.chat_log().is_empty());
+    let second = client.receive_enveloped()?;
+    assert!(matches!(second.payload, GameMessage::TakeShot { position } if position == Position::new(3, 4)));
+    assert_eq!(second.seq, first.seq + 2);
+    assert_eq!(client.chat_log(), &[("host".to_string(), "you sank my battleship".to_string())]);
+
+    // Chat counts toward the client's own sequence as well
+    client.send_enveloped(&GameMessage::Error { message: "after chat".into() })?;
+    let reply = host.receive_enveloped()?;
+    assert!(matches!(reply.payload, GameMessage::Error { .. }));
+    assert_eq!(host.chat_log().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn verified_shot_emits_shot_resolved_event() -> Result<()> {
+    use host::network_protocol::GameMessage;
+    use host::proofs::{extract_round_commits, produce_and_verify_proof, proofdata_from_receipt, GuestInput};
+    use host::ui::{make_channels, UiCommand, UiEvent};
+
+    let (net, mut peer) = connected_pair()?;
+    let (board, defender) = host::game_round::demo_boards();
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    coord.opponent_commit = Some(defender.commit());
+    coord.opponent_placement = Some(defender.hash_ships_only());
+    let (ui, game) = make_channels();
+    let handle = thread::spawn(move || coord.play_game_channels(game.commands, game.events));
+
+    let shot = Position::new(0, 0);
+    ui.commands.send(UiCommand::Shoot(shot))?;
+    drop(ui.commands);
+    let env = peer.receive_enveloped()?;
+    assert!(matches!(env.payload, GameMessage::TakeShot { position } if position == shot));
+
+    let input = GuestInput { initial: defender, shots: vec![shot], match_id: env.match_id, seq: env.seq, turn_owner: vec![core::Player::One], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+    let receipt = match produce_and_verify_proof(&input) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("prover unavailable or failed, skipping ShotResolved test: {}", e);
+            peer.send_enveloped(&GameMessage::Error { message: "prover failed".into() })?;
+            let _ = handle.join().expect("game thread panicked");
+            return Ok(());
+        }
+    };
+    let rc = extract_round_commits(&receipt)?.last().unwrap().clone();
+    let proof = proofdata_from_receipt(&receipt, rc.clone())?;
+    peer.send_enveloped(&GameMessage::ShotResult { position: shot, hit_type: rc.hit.clone(), proof })?;
+    let _ = handle.join().expect("game thread panicked");
+
+    let events: Vec<UiEvent> = ui.events.iter().collect();
+    let resolved: Vec<&UiEvent> = events.iter().filter(|e| matches!(e, UiEvent::ShotResolved { .. })).collect();
+    assert_eq!(resolved, [&UiEvent::ShotResolved { pos: shot, result: rc.hit }]);
+    Ok(())
+}
+
+#[test]
+fn final_sinking_with_extra_turn_on_sunk_wins() -> Result<()> {
+    use core::{CellState, GameOutcome, Player, TurnRules};
+    use host::network_protocol::GameMessage;
+    use host::proofs::{extract_round_commits, produce_and_verify_proof, proofdata_from_receipt, GuestInput};
+    use host::ui::{make_channels, UiCommand};
+
+    // Every ship cell but the last one of the destroyer has already been hit
+    let (board, mut defender) = host::game_round::demo_boards();
+    let last = Position::new(8, 1);
+    let cells: Vec<Position> = defender.ships.iter().flat_map(|s| s.get_coordinates()).filter(|&p| p != last).collect();
+    let (mut net, mut peer) = connected_pair()?;
+    let fleet = core::FleetSpec { turn_rules: TurnRules { extra_turn_on_hit: true, extra_turn_on_sunk: true }, ..core::FleetSpec::classic() };
+    net.set_fleet_spec(fleet.clone());
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    for &pos in &cells {
+        defender.apply_shot(pos);
+        coord.opponent_view.merge_observation(pos, CellState::Hit).unwrap();
+    }
+    coord.opponent_commit = Some(defender.commit());
+    coord.opponent_placement = Some(defender.hash_ships_only());
+    let (ui, game) = make_channels();
+    let handle = thread::spawn(move || coord.play_game_channels(game.commands, game.events));
+
+    // Closing the command channel makes a second shot prompt fail the game
+    ui.commands.send(UiCommand::Shoot(last))?;
+    drop(ui.commands);
+    let env = peer.receive_enveloped()?;
+    assert!(matches!(env.payload, GameMessage::TakeShot { position } if position == last));
+
+    let input = GuestInput { initial: defender, shots: vec![last], match_id: env.match_id, seq: env.seq, turn_owner: vec![Player::One], turn: core::TurnState::default(), fleet, disclosure: core::DisclosureMode::Open };
+    let receipt = match produce_and_verify_proof(&input) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("prover unavailable or failed, skipping extra_turn_on_sunk test: {}", e);
+            peer.send_enveloped(&GameMessage::Error { message: "prover failed".into() })?;
+            let _ = handle.join().expect("game thread panicked");
+            return Ok(());
+        }
+    };
+    let rc = extract_round_commits(&receipt)?.last().unwrap().clone();
+    assert!(matches!(rc.hit, core::HitType::Sunk(core::ShipType::Destroyer)));
+    let proof = proofdata_from_receipt(&receipt, rc.clone())?;
+    peer.send_enveloped(&GameMessage::ShotResult { position: last, hit_type: rc.hit, proof })?;
+    peer.send_enveloped(&GameMessage::GameOver { winner: "host".into() })?;
+    assert_eq!(handle.join().expect("game thread panicked")?.outcome, GameOutcome::Winner(Player::One));
+    Ok(())
+}
+
+#[test]
+fn derived_match_id_is_stable_and_adopted_by_client() -> Result<()> {
+    use host::network::{derive_match_id, MatchIdStrategy};
+
+    let namespace = uuid::Uuid::from_u128(0x6ba7b810_9dad_11d1_80b4_00c04fd430c8);
+    let board = GameState::new([0; 16]);
+    let (placement, commit) = (board.hash_ships_only(), board.commit());
+    let fixed = risc0_zkvm::sha::Digest::from([0x11u8; 32]);
+    let fixed_id = derive_match_id(namespace, &fixed, 42);
+    assert_eq!(fixed_id.to_string(), "986513f2-f353-85f3-ad77-f7a797015605");
+    assert_eq!(fixed_id.get_version_num(), 8);
+    assert_ne!(derive_match_id(namespace, &fixed, 43), fixed_id);
+    assert_ne!(derive_match_id(uuid::Uuid::nil(), &fixed, 42), fixed_id);
+    let id = derive_match_id(namespace, &placement, 42);
+    assert_eq!(derive_match_id(namespace, &placement, 42), id);
+    assert_ne!(derive_match_id(namespace, &GameState::new([1; 16]).hash_ships_only(), 42), id);
+
+    // The host uses it for the match and the client adopts it
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let mut host = NetworkConnection::from_stream(server, Some(vec![7u8; 32]));
+    let mut client = NetworkConnection::from_stream(client, Some(vec![7u8; 32]));
+    host.set_match_id_strategy(MatchIdStrategy::Derived { namespace, nonce: 42 });
+    let handle = thread::spawn(move || -> Result<NetworkConnection> {
+        client.handshake_as_client("client", placement, commit, None)?;
+        Ok(client)
+    });
+    host.handshake_as_host("host", placement, commit, None)?;
+    let mut client = handle.join().expect("client handshake thread panicked")?;
+
+    host.send_enveloped(&host::network_protocol::GameMessage::Error { message: "ping".into() })?;
+    assert_eq!(client.receive_enveloped()?.match_id, id);
+    Ok(())
+}
+
+#[test]
+fn out_of_turn_take_shot_is_refused() -> Result<()> {
+    use host::network_protocol::GameMessage;
+    use host::ui::{make_channels, UiCommand};
+
+    let (net, mut peer) = connected_pair()?;
+    let (board, _) = host::game_round::demo_boards();
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    coord.opponent_commit = Some(board.commit());
+    let (ui, game) = make_channels();
+    let handle = thread::spawn(move || {
+        let result = coord.play_game_channels(game.commands, game.events);
+        (coord, result)
+    });
+
+    ui.commands.send(UiCommand::Shoot(Position::new(3, 3)))?;
+    drop(ui.commands);
+    assert!(matches!(peer.receive_enveloped()?.payload, GameMessage::TakeShot { .. }));
+
+    // While our shot is outstanding the peer fires back at one of our ships
+    peer.send_enveloped(&GameMessage::TakeShot { position: Position::new(0, 0) })?;
+    match peer.receive_enveloped()?.payload {
+        GameMessage::Error { message } => assert!(message.contains("out of turn"), "unexpected error: {message}"),
+        other => panic!("expected Error, got {:?}", other),
+    }
+    peer.send_enveloped(&GameMessage::Error { message: "prover failed".into() })?;
+
+    let (coord, result) = handle.join().expect("game thread panicked");
+    assert!(result.is_err(), "closed command channel must end the game");
+    assert_eq!(coord.local_state, board);
+    assert_eq!(coord.local_state.commit(), board.commit());
+    Ok(())
+}
+
+#[test]
+fn replayed_shot_result_is_applied_once() -> Result<()> {
+    use risc0_zkvm::sha::Digest;
+
+    let (net, _peer) = connected_pair()?;
+    let board = GameState::new([0; 16]);
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    coord.opponent_commit = Some(board.commit());
+
+    let rc = RoundCommit { new_state: Digest::from([9u8; 32]), ..sample_commit() };
+    assert!(coord.apply_verified_result(&rc)?);
+    let (view, commit) = (coord.opponent_view.clone(), coord.opponent_commit);
+    assert_eq!(view.grid[2][4], core::CellState::Hit);
+    assert_eq!(commit, Some(rc.new_state));
+
+    // The same (match_id, seq, shot) again changes nothing, even with a later commitment
+    let replay = RoundCommit { new_state: Digest::from([7u8; 32]), ..rc.clone() };
+    assert!(!coord.apply_verified_result(&replay)?);
+    assert_eq!(coord.opponent_view, view);
+    assert_eq!(coord.opponent_commit, commit);
+
+    // A different sequence number is a new shot
+    let next = RoundCommit { seq: rc.seq + 1, shot: Position::new(5, 2), ..replay };
+    assert!(coord.apply_verified_result(&next)?);
+    assert_eq!(coord.applied_shots.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn invalid_shot_result_is_not_recorded_as_a_miss() -> Result<()> {
+    let (net, _peer) = connected_pair()?;
+    let board = GameState::new([0; 16]);
+    let mut coord = host::game::GameCoordinator::new(board.clone(), board.commit(), net, "host".into(), true);
+    coord.opponent_commit = Some(board.commit());
+
+    let rc = RoundCommit { hit: HitType::Miss, invalid: Some(core::ShotError::OutOfBounds), ..sample_commit() };
+    let err = coord.apply_verified_result(&rc).unwrap_err();
+    assert!(err.to_string().contains("invalid"), "{err}");
+    assert_eq!(coord.opponent_view.grid[2][4], core::CellState::Empty);
+    assert_eq!(coord.opponent_commit, Some(board.commit()));
+    assert!(coord.applied_shots.is_empty());
+    Ok(())
+}
+
+#[test]
+fn message_flood_trips_rate_limit() -> Result<()> {
+    use host::network::RateLimit;
+    use host::network_protocol::GameMessage;
+    use std::time::Duration;
+
+    let (mut host, mut client) = connected_pair()?;
+    client.set_rate_limit(Some(RateLimit { max_messages: 5, window: Duration::from_secs(60) }));
+
+    // A normal exchange stays under the limit
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(1, 1) })?;
+    assert!(matches!(client.receive_enveloped()?.payload, GameMessage::TakeShot { .. }));
+
+    // A chat flood does not
+    for i in 0..20 {
+        host.send_chat("host", &format!("spam {i}"))?;
+    }
+    host.send_enveloped(&GameMessage::Error { message: "end of flood".into() })?;
+    let err = client.receive_enveloped().expect_err("flood must trip the rate limit");
+    assert!(err.to_string().contains("rate limit exceeded"), "unexpected error: {err}");
+    assert_eq!(client.chat_log().len(), 4);
+    Ok(())
+}
+
+#[test]
+fn forged_envelope_hmac_is_auth_token_invalid() -> Result<()> {
+    use host::error::NetworkError;
+    use host::network::envelope_auth_token;
+    use host::network_protocol::{Envelope, GameMessage};
+
+    let (host, mut client) = connected_pair()?;
+    // Signed with a secret other than the match secret the pair shares
+    let mut env = Envelope::new(uuid::Uuid::nil(), 0, GameMessage::TakeShot { position: Position::new(0, 0) });
+    env.auth_token = Some(envelope_auth_token(&[9u8; 32], &env)?);
+    host.send_framed(&serde_json::to_vec(&env)?)?;
+
+    let err = client.receive_enveloped().expect_err("forged envelope must be rejected");
+    assert!(matches!(err, NetworkError::AuthTokenInvalid), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn coin_flip_is_unbiased_and_agreed() -> Result<()> {
+    use host::network::coin_host_starts;
+
+    // Uniform nonces: within 4 sigma of an even split over 10_000 flips
+    let trials = 10_000;
+    let host_starts = (0..trials).filter(|_| coin_host_starts(&rand::random(), &rand::random())).count();
+    assert!((4800..=5200).contains(&host_starts), "host started {host_starts} of {trials} flips");
+
+    // A fixed host nonce cannot bias it either: the client's bit decides
+    let fixed = [0u8; 32];
+    assert!(coin_host_starts(&fixed, &[2u8; 32]));
+    assert!(!coin_host_starts(&fixed, &[3u8; 32]));
+
+    // Both peers derive the same answer over the wire
+    let (mut host, mut client) = connected_pair()?;
+    let handle = thread::spawn(move || client.flip_first_player());
+    let host_view = host.flip_first_player()?;
+    assert_eq!(handle.join().expect("client flip thread panicked")?, host_view);
+    Ok(())
+}
+
+#[test]
+fn coin_reveal_inconsistent_with_commitment_is_rejected() -> Result<()> {
+    use host::error::NetworkError;
+    use host::network_protocol::GameMessage;
+
+    let (mut host, mut client) = connected_pair()?;
+    // The client ignores the nonce it committed to and reveals another
+    client.send_enveloped(&GameMessage::CoinReveal { nonce: [0xAB; 32] })?;
+    let err = host.flip_first_player().expect_err("inconsistent reveal must be rejected");
+    assert!(matches!(err, NetworkError::CoinRevealMismatch), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn silent_peer_is_dropped_by_heartbeat() -> Result<()> {
+    use host::error::NetworkError;
+    use host::network::HeartbeatConfig;
+    use std::time::{Duration, Instant};
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    // Stays connected but never reads or answers a ping
+    let _silent = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let mut host = NetworkConnection::from_stream(server, None);
+    host.start_heartbeat(HeartbeatConfig { interval: Duration::from_millis(20), timeout: Duration::from_millis(200) })?;
+
+    let start = Instant::now();
+    let err = host.recv_framed().expect_err("silent peer must be declared dropped");
+    assert!(matches!(err, NetworkError::PeerTimedOut { .. }), "unexpected error: {err}");
+    assert!(start.elapsed() < Duration::from_secs(5), "took {:?} to notice", start.elapsed());
+    Ok(())
+}
+
+#[test]
+fn heartbeats_keep_an_idle_match_alive() -> Result<()> {
+    use host::network::HeartbeatConfig;
+    use host::network_protocol::GameMessage;
+    use std::time::Duration;
+
+    let (mut host, mut client) = connected_pair()?;
+    let config = HeartbeatConfig { interval: Duration::from_millis(20), timeout: Duration::from_millis(200) };
+    host.start_heartbeat(config)?;
+    client.start_heartbeat(config)?;
+
+    // Idle for longer than the timeout; pings keep both sides alive and
+    // do not use up envelope sequence numbers
+    thread::sleep(Duration::from_millis(500));
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(2, 3) })?;
+    let env = client.receive_enveloped()?;
+    assert!(matches!(env.payload, GameMessage::TakeShot { position } if position == Position::new(2, 3)));
+    client.send_enveloped(&GameMessage::Error { message: "still here".into() })?;
+    assert!(matches!(host.receive_enveloped()?.payload, GameMessage::Error { .. }));
+
+    // A peer without a heartbeat answers pings while it reads
+    let (mut pinged, mut plain) = connected_pair()?;
+    pinged.start_heartbeat(config)?;
+    let handle = thread::spawn(move || plain.receive_enveloped().map(|env| (plain, env)));
+    thread::sleep(Duration::from_millis(500));
+    pinged.send_enveloped(&GameMessage::Error { message: "ping survived".into() })?;
+    let (_plain, env) = handle.join().expect("reader thread panicked")?;
+    assert!(matches!(env.payload, GameMessage::Error { .. }));
+    Ok(())
+}
+
+#[test]
+fn rematch_starts_a_fresh_match_on_the_same_connection() -> Result<()> {
+    use host::network_protocol::{Envelope, GameMessage};
+
+    // Match one: the pair's handshake used seq 0, so this shot is seq 1
+    let (mut host, mut client) = connected_pair()?;
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(1, 1) })?;
+    let first = client.receive_enveloped()?;
+    assert_eq!(first.seq, 1);
+    client.send_enveloped(&GameMessage::GameOver { winner: "host".into() })?;
+    assert!(matches!(host.receive_enveloped()?.payload, GameMessage::GameOver { .. }));
+
+    let next = GameState::new([1; 16]);
+    let (placement, commit) = (next.hash_ships_only(), next.commit());
+    let handle = thread::spawn(move || -> Result<Envelope> {
+        assert!(matches!(client.receive_enveloped()?.payload, GameMessage::RematchRequest));
+        client.accept_rematch()?;
+        client.handshake_as_client("client", placement, commit, None)?;
+        Ok(client.receive_enveloped()?)
+    });
+    assert!(host.request_rematch()?, "peer accepted the rematch");
+    host.handshake_as_host("host", placement, commit, None)?;
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(1, 1) })?;
+    let second = handle.join().expect("client rematch thread panicked")?;
+
+    // Match two restarts the sequence space under a new match id
+    assert_eq!(second.seq, 1);
+    assert_ne!(second.match_id, first.match_id);
+    Ok(())
+}
+
+#[test]
+fn rematch_rotates_the_match_secret() -> Result<()> {
+    use host::error::NetworkError;
+    use host::network::envelope_auth_token;
+    use host::network_protocol::{Envelope, GameMessage};
+
+    let (mut host, mut client) = connected_pair()?;
+    let handle = thread::spawn(move || {
+        assert!(matches!(client.receive_enveloped(), Ok(Envelope { payload: GameMessage::RematchRequest, .. })));
+        client.accept_rematch()?;
+        let board = GameState::new([1; 16]);
+        client.handshake_as_client("client", board.hash_ships_only(), board.commit(), None).map(|_| ())
+    });
+    assert!(host.request_rematch()?);
+
+    // A BoardReady authenticated with match one's secret must not open match two
+    let empty = GameState::new([0; 16]);
+    let old_secret = bind_match_secret(&[7u8; 32], &empty.hash_ships_only(), &empty.hash_ships_only());
+    let mut stale = Envelope::new(uuid::Uuid::new_v4(), 0, GameMessage::BoardReady { commitment: empty.hash_ships_only(), state_commitment: empty.commit(), player_name: "host".into(), proof: None, coin_commitment: empty.commit(), fleet: core::FleetSpec::classic() });
+    stale.auth_token = Some(envelope_auth_token(&old_secret, &stale)?);
+    host.send_framed(&serde_json::to_vec(&stale)?)?;
+    let err = handle.join().expect("client thread panicked").expect_err("stale BoardReady must be rejected");
+    assert!(matches!(err, NetworkError::AuthTokenInvalid), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn declined_rematch_reports_false() -> Result<()> {
+    use host::network_protocol::GameMessage;
+
+    let (mut host, mut client) = connected_pair()?;
+    let handle = thread::spawn(move || {
+        assert!(matches!(client.receive_enveloped(), Ok(env) if matches!(env.payload, GameMessage::RematchRequest)));
+        // Hanging up declines
+        drop(client);
+    });
+    assert!(!host.request_rematch()?);
+    handle.join().expect("client thread panicked");
+    Ok(())
+}
+
+#[test]
+fn split_halves_send_while_the_receiver_blocks() -> Result<()> {
+    use host::network_protocol::GameMessage;
+    use std::time::Duration;
+
+    let (host, mut client) = connected_pair()?;
+    let (mut tx, mut rx) = host.split()?;
+    // Blocks until the client starts answering, which it only does after
+    // receiving what the sender half writes in the meantime
+    let reader = thread::spawn(move || -> Result<Vec<u64>> {
+        (0..5).map(|_| Ok(rx.receive_enveloped()?.seq)).collect()
+    });
+    thread::sleep(Duration::from_millis(50));
+
+    for i in 0..5 {
+        tx.send_enveloped(&GameMessage::TakeShot { position: Position::new(i, 0) })?;
+        let env = client.receive_enveloped()?;
+        assert!(matches!(env.payload, GameMessage::TakeShot { position } if position == Position::new(i, 0)));
+        client.send_enveloped(&GameMessage::Error { message: format!("ack {i}") })?;
+    }
+    // The handshake used seq 0 in each direction
+    assert_eq!(reader.join().expect("reader thread panicked")?, vec![1, 2, 3, 4, 5]);
+
+    // The sender half keeps numbering after the shots, chat included
+    tx.send_chat("host", "gg")?;
+    tx.send_enveloped(&GameMessage::GameOver { winner: "host".into() })?;
+    assert_eq!(client.receive_enveloped()?.seq, 7);
+    assert_eq!(client.chat_log(), [("host".to_string(), "gg".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn mismatched_fleet_fails_the_handshake() -> Result<()> {
+    use core::{FleetSpec, ShipType};
+    use host::error::NetworkError;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+    let mut host = NetworkConnection::from_stream(server, Some(vec![7u8; 32]));
+    let mut client = NetworkConnection::from_stream(client, Some(vec![7u8; 32]));
+    client.set_fleet_spec(FleetSpec::with_ships(vec![ShipType::Cruiser, ShipType::Submarine, ShipType::Destroyer], core::BoardDims::new(8, 6).unwrap()));
+
+    let board = GameState::new([0; 16]);
+    let (placement, commit) = (board.hash_ships_only(), board.commit());
+    let handle = thread::spawn(move || client.handshake_as_client("client", placement, commit, None));
+    let host_err = host.handshake_as_host("host", placement, commit, None).expect_err("host must not accept an 8x6 peer");
+    let client_err = handle.join().expect("client thread panicked").expect_err("client must not accept a 10x10 host");
+
+    assert!(matches!(&client_err, NetworkError::FleetMismatch { ours, theirs } if ours.dims.height == 6 && theirs.dims.height == 10), "unexpected error: {client_err}");
+    assert!(client_err.to_string().contains("peer plays a 10x10 board"), "{client_err}");
+    // The host learns why from the client's Error
+    assert!(host_err.to_string().contains("but we play 8x6"), "{host_err}");
+    Ok(())
+}
+
+#[test]
+fn coordinators_handshake_over_the_in_memory_transport() -> Result<()> {
+    use core::{Direction, ShipType};
+    use host::error::NetworkError;
+    use host::game::GameCoordinator;
+    use host::memory_transport::connection_pair;
+
+    let board = |row: u32| {
+        let mut state = GameState::new([row as u8; 16]);
+        for (i, st) in ShipType::ALL.into_iter().enumerate() {
+            assert!(state.place_ship(st, Position::new(0, row + i as u32), Direction::Horizontal));
+        }
+        state
+    };
+    let (host_board, client_board) = (board(0), board(5));
+    let (host_commit, client_commit) = (host_board.commit(), client_board.commit());
+    let (host_net, client_net) = connection_pair(Some(vec![7u8; 32]));
+    let mut host = GameCoordinator::new(host_board, host_commit, host_net, "host".into(), true);
+    let mut client = GameCoordinator::new(client_board, client_commit, client_net, "client".into(), false);
+
+    let handle = thread::spawn(move || -> Result<GameCoordinator> {
+        client.handshake()?;
+        Ok(client)
+    });
+    host.handshake()?;
+    let client = handle.join().expect("client handshake thread panicked")?;
+
+    assert_eq!((host.opponent_name.as_deref(), client.opponent_name.as_deref()), (Some("client"), Some("host")));
+    assert_eq!((host.opponent_commit, client.opponent_commit), (Some(client_commit), Some(host_commit)));
+    assert_ne!(host.starts_first, client.starts_first, "the coin flip picks exactly one first shooter");
+
+    // Envelopes keep flowing, and dropping one end reads as a closed socket
+    let mut client_net = client.network;
+    host.network.send_framed(b"after handshake")?;
+    assert_eq!(client_net.recv_framed()?, b"after handshake");
+    drop(host);
+    assert!(matches!(client_net.recv_framed(), Err(NetworkError::Closed)));
+    Ok(())
+}
+
+#[test]
+fn coordinators_handshake_with_a_smaller_fleet() -> Result<()> {
+    use core::{Direction, FleetSpec, ShipType};
+    use host::game::GameCoordinator;
+    use host::memory_transport::connection_pair;
+
+    let fleet = FleetSpec::with_ships(vec![ShipType::Cruiser, ShipType::Destroyer], core::BoardDims::new(6, 6).unwrap());
+    let board = |row: u32| {
+        let mut state = GameState::new([row as u8; 16]);
+        fleet.apply_rules(&mut state);
+        for (i, &st) in fleet.ships.iter().enumerate() {
+            assert!(state.place_ship(st, Position::new(0, row + i as u32), Direction::Horizontal));
+        }
+        state
+    };
+    let (host_board, client_board) = (board(0), board(3));
+    let (host_commit, client_commit) = (host_board.commit(), client_board.commit());
+    let (mut host_net, mut client_net) = connection_pair(Some(vec![7u8; 32]));
+    host_net.set_fleet_spec(fleet.clone());
+    client_net.set_fleet_spec(fleet.clone());
+    let mut host = GameCoordinator::new(host_board, host_commit, host_net, "host".into(), true);
+    let mut client = GameCoordinator::new(client_board, client_commit, client_net, "client".into(), false);
+    let handle = thread::spawn(move || -> Result<GameCoordinator> {
+        client.handshake()?;
+        Ok(client)
+    });
+    host.handshake()?;
+    let client = handle.join().expect("client handshake thread panicked")?;
+    assert_eq!((host.opponent_commit, client.opponent_commit), (Some(client_commit), Some(host_commit)));
+    Ok(())
+}
+
+#[test]
+fn hit_streak_survives_a_saved_and_resumed_session() -> Result<()> {
+    use core::{Direction, Player, ShipType, TurnRules, TurnState};
+    use host::game::{save_turn_in, GameCoordinator, SavedTurn};
+    use host::memory_transport::connection_pair;
+
+    fn coordinators() -> Result<(GameCoordinator, GameCoordinator)> {
+        let board = |row: u32| {
+            let mut state = GameState::new([row as u8; 16]);
+            for (i, st) in ShipType::ALL.into_iter().enumerate() {
+                assert!(state.place_ship(st, Position::new(0, row + i as u32), Direction::Horizontal));
+            }
+            state
+        };
+        let (a, b) = (board(0), board(5));
+        let (host_net, client_net) = connection_pair(Some(vec![7u8; 32]));
+        let mut host = GameCoordinator::new(a.clone(), a.commit(), host_net, "host".into(), true);
+        let mut client = GameCoordinator::new(b.clone(), b.commit(), client_net, "client".into(), false);
+        let handle = thread::spawn(move || -> Result<GameCoordinator> {
+            client.handshake()?;
+            Ok(client)
+        });
+        host.handshake()?;
+        Ok((host, handle.join().expect("client handshake thread panicked")?))
+    }
+
+    let dir = std::env::temp_dir().join(format!("sessions-{}", uuid::Uuid::new_v4()));
+    let match_id = uuid::Uuid::new_v4();
+    let (host, client) = coordinators()?;
+    let first = if host.local_player() == Player::One { "host" } else { "client" };
+
+    // Player One hits twice in a row and is owed a third shot when the link drops
+    let mut turn = TurnState::default();
+    turn.advance(&TurnRules::default(), Player::One, &HitType::Hit);
+    turn.advance(&TurnRules::default(), Player::One, &HitType::Hit);
+    assert_eq!(turn, TurnState { to_move: Player::One, streak: 2 });
+    for coord in [&host, &client] {
+        save_turn_in(&dir.join(&coord.player_name), match_id, &SavedTurn { local_player: coord.local_player(), turn })?;
+    }
+    drop((host, client));
+
+    // The reconnect flips a fresh coin, but resuming restores the old seats
+    let (mut host, mut client) = coordinators()?;
+    for coord in [&mut host, &mut client] {
+        assert!(coord.resume_turn_from(&dir.join(&coord.player_name), match_id)?);
+        assert_eq!(coord.turn.streak, 2);
+    }
+    let shooter = if host.turn.to_move == host.local_player() { "host" } else { "client" };
+    assert_eq!(shooter, first, "the player mid-streak keeps the shot");
+    assert_eq!(client.turn.to_move == client.local_player(), first == "client");
+    assert!(!host.resume_turn_from(&dir, uuid::Uuid::new_v4())?, "nothing saved for another match");
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn repeated_invalid_shots_end_in_a_forfeit() -> Result<()> {
+    use core::{Direction, GameOutcome, ShipType, TurnState};
+    use host::game::GameCoordinator;
+    use host::memory_transport::connection_pair;
+    use host::network_protocol::GameMessage;
+    use std::sync::mpsc;
+
+    let board = |row: u32| {
+        let mut state = GameState::new([row as u8; 16]);
+        for (i, st) in ShipType::ALL.into_iter().enumerate() {
+            assert!(state.place_ship(st, Position::new(0, row + i as u32), Direction::Horizontal));
+        }
+        state
+    };
+    let (a, b) = (board(0), board(5));
+    let (host_net, client_net) = connection_pair(Some(vec![7u8; 32]));
+    let mut host = GameCoordinator::new(a.clone(), a.commit(), host_net, "host".into(), true);
+    let mut client = GameCoordinator::new(b.clone(), b.commit(), client_net, "client".into(), false);
+    let handle = thread::spawn(move || -> Result<GameCoordinator> {
+        client.handshake()?;
+        Ok(client)
+    });
+    host.handshake()?;
+    let client = handle.join().expect("client handshake thread panicked")?;
+
+    // The host defends and gives up on the client after three invalid shots
+    host.invalid_shot_limit = Some(3);
+    host.turn = TurnState { to_move: host.local_player().other(), streak: 0 };
+    host.local_state.apply_shot(Position::new(9, 9));
+    let host_seat = host.local_player();
+    let (_commands, rx) = mpsc::channel();
+    let (tx, _events) = mpsc::channel();
+    let defender = thread::spawn(move || host.play_game_channels(rx, tx).map(|result| (result.outcome, host.invalid_shots)));
+
+    // The client plays by hand, firing where no honest coordinator would
+    let mut offender = client.network;
+    for (shot, refusal) in [(Position::new(9, 9), "already shot"), (Position::new(core::BOARD_SIZE as u32, 0), "out of bounds")] {
+        offender.send_enveloped(&GameMessage::TakeShot { position: shot })?;
+        match offender.receive_enveloped()?.payload {
+            GameMessage::Error { message } => assert!(message.contains(refusal), "{}", message),
+            other => panic!("expected an Error refusing {:?}, got {:?}", shot, other),
+        }
+    }
+    offender.send_enveloped(&GameMessage::TakeShot { position: Position::new(9, 9) })?;
+    match offender.receive_enveloped()?.payload {
+        GameMessage::Forfeit { reason } => assert!(reason.starts_with("3 invalid shots"), "{}", reason),
+        other => panic!("expected a Forfeit, got {:?}", other),
+    }
+
+    let (outcome, invalid_shots) = defender.join().expect("defender thread panicked")?;
+    assert_eq!(outcome, GameOutcome::Winner(host_seat));
+    assert_eq!(invalid_shots, 3);
+    Ok(())
+}
+
+#[test]
+fn handshake_fails_without_a_required_setup_proof() -> Result<()> {
+    use core::{Direction, ShipType};
+    use host::game::GameCoordinator;
+    use host::memory_transport::connection_pair;
+
+    let board = |row: u32| {
+        let mut state = GameState::new([row as u8; 16]);
+        for (i, st) in ShipType::ALL.into_iter().enumerate() {
+            assert!(state.place_ship(st, Position::new(0, row + i as u32), Direction::Horizontal));
+        }
+        state
+    };
+    let (a, b) = (board(0), board(5));
+    let (host_net, client_net) = connection_pair(Some(vec![7u8; 32]));
+    let mut host = GameCoordinator::new(a.clone(), a.commit(), host_net, "host".into(), true);
+    let mut client = GameCoordinator::new(b.clone(), b.commit(), client_net, "client".into(), false);
+    host.require_setup_proof = true;
+    let handle = thread::spawn(move || client.handshake());
+
+    let err = host.handshake().expect_err("the client sent no setup proof");
+    assert!(err.to_string().contains("no setup proof"), "{}", err);
+    drop(host);
+    assert!(handle.join().expect("client handshake thread panicked").is_err(), "the host hung up mid-handshake");
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use core::{GameState, HitType, Position, RoundCommit};
+use host::proofs::{check_commit_chain, extract_round_commits_from_journal, journal_words};
+use risc0_zkvm::sha::Digest;
+
+// Structural checks on journal commits. These run without a prover by
+// building RoundCommit sequences directly.
+
+/// Distinct digests standing in for successive board commitments.
+fn digests(n: usize) -> Vec<Digest> {
+    (0..n).map(|i| GameState::new([i as u8; 16]).commit()).collect()
+}
+
+fn commit(old_state: Digest, new_state: Digest, x: u32, y: u32) -> RoundCommit {
+    RoundCommit { match_id: uuid::Uuid::nil(), seq: 0, old_state, new_state, shot: Position::new(x, y), hit: HitType::Miss, sunk_cells: vec![], revealed_water: vec![], invalid: None }
+}
+
+#[test]
+fn valid_chain_is_accepted() {
+    let d = digests(4);
+    let commits = vec![commit(d[0], d[1], 0, 0), commit(d[1], d[2], 1, 0), commit(d[2], d[3], 2, 0)];
+    assert!(check_commit_chain(&commits).is_ok());
+}
+
+#[test]
+fn broken_chain_is_rejected() {
+    let d = digests(4);
+    // Second commit does not start from the first commit's new_state
+    let commits = vec![commit(d[0], d[1], 0, 0), commit(d[2], d[3], 1, 0)];
+    let err = check_commit_chain(&commits).unwrap_err();
+    assert!(err.to_string().contains("chain broken"), "{err}");
+}
+
+#[test]
+fn duplicate_cell_is_rejected() {
+    let d = digests(3);
+    let commits = vec![commit(d[0], d[1], 4, 4), commit(d[1], d[2], 4, 4)];
+    let err = check_commit_chain(&commits).unwrap_err();
+    assert!(err.to_string().contains("repeats shot"), "{err}");
+}
+
+#[test]
+fn salvo_with_an_invalid_shot_is_not_a_miss() {
+    use core::ShotError;
+    use host::error::ProofError;
+    use host::proofs::check_salvo_commits;
+
+    let d = digests(2);
+    let shots = [Position::new(0, 0), Position::new(1, 0)];
+    let rejected = RoundCommit { invalid: Some(ShotError::AlreadyShot), ..commit(d[1], d[1], 1, 0) };
+    let commits = vec![commit(d[0], d[1], 0, 0), rejected];
+    let err = check_salvo_commits(&commits, d[0], &shots, None, None).unwrap_err();
+    assert!(matches!(err, ProofError::InvalidShot(ShotError::AlreadyShot)), "{err}");
+}
+
+#[test]
+fn journal_words_are_little_endian() {
+    // Bytes as the RISC-V guest lays them out; the decoded words must not
+    // depend on the verifier's native byte order.
+    let bytes = [0x01, 0x00, 0x00, 0x00, 0x78, 0x56, 0x34, 0x12, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(journal_words(&bytes).unwrap(), vec![1, 0x1234_5678, u32::MAX]);
+    assert!(journal_words(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn ragged_journal_is_rejected() {
+    assert!(journal_words(&[1, 0, 0]).is_err());
+    let err = extract_round_commits_from_journal(&[0; 33]).unwrap_err();
+    assert!(err.to_string().contains("multiple of 4"), "{err}");
+}
+
+#[test]
+fn future_proof_version_is_rejected_before_decoding() {
+    use host::error::ProofError;
+    use host::network_protocol::{ProofData, PROOF_DATA_VERSION};
+    use host::verify::verify_proofdata;
+
+    let d = digests(2);
+    let current = ProofData::from_bytes(vec![1, 2, 3], commit(d[0], d[1], 0, 0));
+    let decoded = ProofData::decode(&bincode::serialize(&current).unwrap()).unwrap();
+    assert_eq!((decoded.version, decoded.receipt_bytes), (PROOF_DATA_VERSION, vec![1, 2, 3]));
+
+    // A future layout: the tag is readable, the rest is not
+    let mut future = bincode::serialize(&(PROOF_DATA_VERSION + 1, "a layout this build has never seen")).unwrap();
+    future.truncate(12);
+    match ProofData::decode(&future) {
+        Err(ProofError::UnsupportedVersion { found, supported }) => assert_eq!((found, supported), (PROOF_DATA_VERSION + 1, PROOF_DATA_VERSION)),
+        other => panic!("expected UnsupportedVersion, got {:?}", other),
+    }
+    let tagged = ProofData { version: PROOF_DATA_VERSION + 1, ..current.clone() };
+    let err = verify_proofdata(&tagged).unwrap_err();
+    assert!(err.to_string().contains(&format!("unsupported proof version {}", PROOF_DATA_VERSION + 1)), "{err}");
+
+    // JSON from before the tag existed reads as legacy version 0
+    let mut legacy = serde_json::to_value(&current).unwrap();
+    legacy.as_object_mut().unwrap().remove("version");
+    let legacy: ProofData = serde_json::from_value(legacy).unwrap();
+    assert_eq!(legacy.version, 0);
+    assert!(legacy.check_version().is_ok());
+}
+
+#[test]
+fn hidden_sinking_is_opened_only_after_the_game() {
+    use core::{Direction, DisclosureMode, ShipType, ShipTypeOpening};
+    use host::proofs::check_ship_type_reveal;
+
+    let mut board = GameState::new([3; 16]);
+    assert!(board.place_ship(ShipType::Destroyer, Position::new(2, 2), Direction::Horizontal));
+    assert!(board.place_ship(ShipType::Submarine, Position::new(2, 5), Direction::Horizontal));
+    let mode = DisclosureMode::HiddenShipType { key: [9; 32] };
+
+    // What the guest journals for the sinking shot
+    let old_state = board.commit();
+    board.apply_shot(Position::new(2, 2));
+    let (hit, new_state) = board.apply_shot_committed(Position::new(3, 2));
+    assert_eq!(hit, Some(HitType::Sunk(ShipType::Destroyer)));
+    let hit = mode.disclose(hit.unwrap());
+    let sunk_cells = board.sunk_footprint(&hit);
+    let rc = RoundCommit { match_id: uuid::Uuid::nil(), seq: 0, old_state, new_state, shot: Position::new(3, 2), hit, sunk_cells, revealed_water: vec![], invalid: None };
+    assert!(rc.sunk_cells.is_empty(), "the footprint is withheld too");
+    assert!(check_commit_chain(std::slice::from_ref(&rc)).is_ok());
+
+    // Mid-game the commit names no ship type, and no guess opens it
+    // without the nonce
+    let journaled = serde_json::to_string(&rc).unwrap();
+    assert!(!journaled.contains("Destroyer"), "{journaled}");
+    for st in ShipType::ALL {
+        assert!(!ShipTypeOpening { ship_type: st, nonce: [0; 32] }.opens(&rc.hit));
+    }
+
+    // After the game the defender's opening verifies against the revealed board
+    let opening = mode.opening(ShipType::Destroyer).unwrap();
+    assert_eq!(check_ship_type_reveal(&rc, &opening, &board).unwrap(), HitType::Sunk(ShipType::Destroyer));
+    let lie = ShipTypeOpening { ship_type: ShipType::Submarine, ..opening };
+    assert!(check_ship_type_reveal(&rc, &lie, &board).is_err(), "the commitment binds the type");
+    let elsewhere = RoundCommit { shot: Position::new(2, 5), ..rc.clone() };
+    assert!(check_ship_type_reveal(&elsewhere, &opening, &board).is_err(), "the revealed board must put that ship under the shot");
+}
+
+#[test]
+fn invalid_guest_input_is_rejected_before_proving() {
+    use core::{Direction, DisclosureMode, FleetSpec, Player, ShipType, TurnState, MAX_SHOTS_PER_PROOF};
+    use host::error::ProofError;
+    use host::proofs::{validate_guest_input, GuestInput};
+
+    let mut board = GameState::new([0; 16]);
+    for (i, st) in ShipType::ALL.into_iter().enumerate() {
+        assert!(board.place_ship(st, Position::new(0, 2 * i as u32), Direction::Horizontal));
+    }
+    let input = GuestInput { initial: board, shots: vec![Position::new(0, 0)], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: TurnState::default(), fleet: FleetSpec::classic(), disclosure: DisclosureMode::Open };
+    assert!(validate_guest_input(&input).is_ok());
+
+    let incomplete = GuestInput { initial: GameState::new([0; 16]), ..input.clone() };
+    let off_board = GuestInput { shots: vec![Position::new(0, 0), Position::new(10, 3)], ..input.clone() };
+    let too_many = GuestInput { shots: vec![Position::new(1, 1); MAX_SHOTS_PER_PROOF + 1], ..input.clone() };
+    let owners = GuestInput { turn_owner: vec![Player::One, Player::Two], ..input.clone() };
+    for (name, bad) in [("incomplete board", incomplete), ("off-board shot", off_board), ("too many shots", too_many), ("turn_owner length", owners)] {
+        // Validation is pure, so this returns at once even with no prover
+        match validate_guest_input(&bad) {
+            Err(ProofError::InvalidInput(msg)) => assert!(!msg.is_empty(), "{name}"),
+            other => panic!("{name}: expected InvalidInput, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn oversized_shot_list_is_refused_by_every_prover_entry_point() {
+    use core::{Direction, DisclosureMode, FleetSpec, ShipType, TurnState, MAX_SHOTS_PER_PROOF};
+    use host::error::ProofError;
+    use host::proofs::{execute_only, produce_and_verify_proof, GuestInput};
+
+    let mut board = GameState::new([0; 16]);
+    for (i, st) in ShipType::ALL.into_iter().enumerate() {
+        assert!(board.place_ship(st, Position::new(0, 2 * i as u32), Direction::Horizontal));
+    }
+    let shots: Vec<Position> = Position::all_in_bounds().chain([Position::new(0, 0)]).collect();
+    assert_eq!(shots.len(), MAX_SHOTS_PER_PROOF + 1);
+    let input = GuestInput { initial: board, shots, match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: TurnState::default(), fleet: FleetSpec::classic(), disclosure: DisclosureMode::Open };
+
+    // Without a validate_guest_input call first, and before any prover work
+    assert!(matches!(produce_and_verify_proof(&input), Err(ProofError::InvalidInput(_))));
+    assert!(matches!(execute_only(&input), Err(ProofError::InvalidInput(_))));
+}
+
+#[test]
+fn invalid_board_gets_no_setup_proof() {
+    use core::{Direction, FleetSpec, Ship, ShipType};
+    use host::error::ProofError;
+    use host::proofs::{prove_board_setup, verify_setup_proof};
+
+    let mut board = GameState::new([0; 16]);
+    for (i, st) in ShipType::ALL.into_iter().enumerate() {
+        assert!(board.place_ship(st, Position::new(0, 2 * i as u32), Direction::Horizontal));
+    }
+    let mut missing_ship = board.clone();
+    missing_ship.ships.pop();
+    let mut overlapping = board.clone();
+    overlapping.ships[4] = Ship::new(ShipType::Destroyer, Position::new(0, 0), Direction::Vertical);
+    let mut off_board = board.clone();
+    off_board.ships[0] = Ship::new(ShipType::Carrier, Position::new(7, 1), Direction::Horizontal);
+    let small = FleetSpec { dims: core::BoardDims::new(8, 8).unwrap(), ..FleetSpec::classic() };
+    let mut off_spec = board.clone();
+    off_spec.ships[0] = Ship::new(ShipType::Carrier, Position::new(5, 1), Direction::Horizontal);
+    // Stacking passes `check()` once the board itself allows overlap; the agreed rules do not
+    let mut stacked = overlapping.clone();
+    stacked.overlap = core::OverlapRule::Allowed;
+    assert!(stacked.check());
+    for (name, bad, fleet) in [("missing ship", &missing_ship, FleetSpec::classic()), ("overlapping ships", &overlapping, FleetSpec::classic()), ("stacked under its own overlap rule", &stacked, FleetSpec::classic()), ("ship off the board", &off_board, FleetSpec::classic()), ("ship outside the agreed fleet's board", &off_spec, small.clone())] {
+        // Refused before any prover work, so this holds without a toolchain too
+        match prove_board_setup(bad, &fleet) {
+            Err(ProofError::InvalidInput(msg)) => assert!(!msg.is_empty(), "{name}"),
+            other => panic!("{name}: expected InvalidInput, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    let proof = match prove_board_setup(&board, &FleetSpec::classic()) {
+        Ok(pd) => pd,
+        Err(e) => {
+            eprintln!("prover unavailable or failed, skipping setup proof round trip: {}", e);
+            return;
+        }
+    };
+    let placement = board.hash_ships_only();
+    assert!(verify_setup_proof(&proof, placement, board.commit(), &FleetSpec::classic()).is_ok());
+    assert!(matches!(verify_setup_proof(&proof, placement, missing_ship.commit(), &FleetSpec::classic()), Err(ProofError::BaseStateMismatch)));
+    assert!(matches!(verify_setup_proof(&proof, missing_ship.hash_ships_only(), board.commit(), &FleetSpec::classic()), Err(ProofError::PlacementMismatch)));
+    assert!(matches!(verify_setup_proof(&proof, placement, board.commit(), &small), Err(ProofError::FleetMismatch)));
+}
@@ -17,7 +17,7 @@ fn prover_and_network_smoke() -> Result<()> {
     state.place_ship(ShipType::Submarine, Position::new(0,6), Direction::Horizontal);
     state.place_ship(ShipType::Destroyer, Position::new(0,8), Direction::Horizontal);
 
-    let guest_input = GuestInput { initial: state.clone(), shots: vec![Position::new(0,0)], match_id: uuid::Uuid::nil(), seq: 0 };
+    let guest_input = GuestInput { initial: state.clone(), shots: vec![Position::new(0,0)], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
 
     // Try to produce & verify a proof. If the prover is unavailable, skip.
     let receipt = match produce_and_verify_proof(&guest_input) {
@@ -45,7 +45,241 @@ fn prover_and_network_smoke() -> Result<()> {
     let rec2 = receipt_from_proofdata(&pd_back)?;
     let commits2 = extract_round_commits(&rec2)?;
     assert_eq!(commits, commits2);
-    let _verified_commits = verify_remote_round_proof(&rec2, &state, Position::new(0,0), None, None)?;
+    let _verified_commits = verify_remote_round_proof(&rec2, state.commit(), Position::new(0,0), None, None)?;
 
     Ok(())
 }
+
+/// Selecting a backend the build cannot serve must fail with a descriptive
+/// error before any proving is attempted.
+#[test]
+fn unavailable_prover_backend_is_rejected() {
+    use host::proofs::{BonsaiConfig, GuestInput, ProverBackend, produce_and_verify_proof_with};
+    use core::GameState;
+
+    let input = GuestInput { initial: GameState::new([0; 16]), shots: vec![], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+
+    let bonsai = ProverBackend::Bonsai(BonsaiConfig { api_url: "https://api.example".into(), api_key: String::new() });
+    let err = produce_and_verify_proof_with(&input, &bonsai).unwrap_err();
+    assert!(err.to_string().contains("api_key"), "unexpected error: {err}");
+
+    if !cfg!(feature = "cuda") {
+        let err = produce_and_verify_proof_with(&input, &ProverBackend::Cuda).unwrap_err();
+        assert!(err.to_string().contains("`cuda` feature"), "unexpected error: {err}");
+    }
+}
+
+/// The execution-only path must report the same commits the host computes
+/// by applying the shots to a clone of the initial state.
+#[test]
+fn execute_only_matches_direct_application() -> Result<()> {
+    use host::proofs::{GuestInput, execute_only};
+    use core::{GameState, RoundCommit, ShipType, Direction, Position};
+
+    let mut state = GameState::new([3; 16]);
+    state.place_ship(ShipType::Carrier, Position::new(0,0), Direction::Horizontal);
+    state.place_ship(ShipType::Battleship, Position::new(0,2), Direction::Horizontal);
+    state.place_ship(ShipType::Cruiser, Position::new(0,4), Direction::Horizontal);
+    state.place_ship(ShipType::Submarine, Position::new(0,6), Direction::Horizontal);
+    state.place_ship(ShipType::Destroyer, Position::new(0,8), Direction::Horizontal);
+
+    let shots = vec![Position::new(0,8), Position::new(9,9), Position::new(1,8)];
+    let match_id = uuid::Uuid::new_v4();
+    let input = GuestInput { initial: state.clone(), shots: shots.clone(), match_id, seq: 7, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+
+    let commits = match execute_only(&input) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("executor unavailable, skipping execute_only test: {}", e);
+            return Ok(());
+        }
+    };
+
+    let mut expected = Vec::new();
+    let mut sim = state.clone();
+    for shot in shots {
+        let old_state = sim.commit();
+        let hit = sim.apply_shot(shot).expect("scripted shots are valid");
+        let sunk_cells = sim.sunk_footprint(&hit);
+        let revealed_water = sim.sunk_surroundings(&hit);
+        expected.push(RoundCommit { match_id, seq: 7, old_state, new_state: sim.commit(), shot, hit, sunk_cells, revealed_water, invalid: None });
+    }
+    assert_eq!(commits, expected);
+    Ok(())
+}
+
+/// The guest enforces the fleet's `TurnRules` when `turn_owner` is given:
+/// a player who missed may not shoot again, so the guest panics instead of
+/// committing the play.
+#[test]
+fn guest_rejects_turn_order_violation() {
+    use host::proofs::{GuestInput, execute_only};
+    use core::{GameState, Player, Position, ShipType, Direction, TurnState};
+
+    let mut state = GameState::new([0; 16]);
+    state.place_ship(ShipType::Carrier, Position::new(0,0), Direction::Horizontal);
+    state.place_ship(ShipType::Battleship, Position::new(0,2), Direction::Horizontal);
+    state.place_ship(ShipType::Cruiser, Position::new(0,4), Direction::Horizontal);
+    state.place_ship(ShipType::Submarine, Position::new(0,6), Direction::Horizontal);
+    state.place_ship(ShipType::Destroyer, Position::new(0,8), Direction::Horizontal);
+
+    // (9,9) misses, so the turn passes to Player Two
+    let input = |owners: Vec<Player>| GuestInput {
+        initial: state.clone(),
+        shots: vec![Position::new(9,9), Position::new(0,0)],
+        match_id: uuid::Uuid::nil(),
+        seq: 0,
+        turn_owner: owners,
+        turn: TurnState::default(),
+        fleet: core::FleetSpec::classic(),
+        disclosure: core::DisclosureMode::Open,
+    };
+    if let Err(e) = execute_only(&input(vec![Player::One, Player::Two])) {
+        eprintln!("executor unavailable, skipping turn order test: {}", e);
+        return;
+    }
+    // Player One fires again anyway
+    let err = execute_only(&input(vec![Player::One, Player::One])).expect_err("guest must reject out-of-turn play");
+    assert!(format!("{:#}", err).contains("turn order violation"), "unexpected error: {:#}", err);
+}
+
+#[test]
+fn cancelled_proof_returns_promptly() {
+    use host::error::ProofError;
+    use host::proofs::{produce_and_verify_proof_cancellable, GuestInput};
+    use core::{GameState, Position};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    let input = GuestInput { initial: GameState::new([0; 16]), shots: vec![Position::new(0, 0)], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+    let cancel = Arc::new(AtomicBool::new(true));
+    let start = Instant::now();
+    let err = produce_and_verify_proof_cancellable(&input, cancel, None).expect_err("cancelled proof must not succeed");
+    assert!(matches!(err, ProofError::Cancelled), "unexpected error: {err}");
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+/// Writes `entries` as a receipt log for `match_id` in a fresh temp dir.
+fn write_receipt_log(match_id: uuid::Uuid, entries: &[host::proofs::ReceiptLogEntry]) -> Result<std::path::PathBuf> {
+    let dir = std::env::temp_dir().join(format!("receipts-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let lines: Vec<String> = entries.iter().map(serde_json::to_string).collect::<Result<_, _>>()?;
+    std::fs::write(dir.join(format!("{}.log", match_id)), lines.join("\n") + "\n")?;
+    Ok(dir)
+}
+
+#[test]
+fn latest_commit_for_match_returns_last_verified_entry() -> Result<()> {
+    use host::proofs::{latest_commit_for_match_in, list_saved_matches_in, produce_and_verify_proof, extract_round_commits, GuestInput, ReceiptLogEntry};
+    use host::network_protocol::PROOF_DATA_VERSION;
+    use base64::{engine::general_purpose, Engine as _};
+
+    let match_id = uuid::Uuid::new_v4();
+    let (state, _) = host::game_round::demo_boards();
+    let mut entries = Vec::new();
+    for (seq, shots) in [vec![core::Position::new(0, 0)], vec![core::Position::new(0, 0), core::Position::new(5, 5)]].into_iter().enumerate() {
+        let input = GuestInput { initial: state.clone(), shots, match_id, seq: seq as u64, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+        let receipt = match produce_and_verify_proof(&input) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("prover unavailable or failed, skipping receipt log test: {}", e);
+                return Ok(());
+            }
+        };
+        let commit = extract_round_commits(&receipt)?.last().unwrap().clone();
+        entries.push(ReceiptLogEntry { version: PROOF_DATA_VERSION, seq: seq as u64, receipt_b64: general_purpose::STANDARD.encode(bincode::serialize(&receipt)?), commit });
+    }
+    let expected = entries[1].commit.clone();
+    let dir = write_receipt_log(match_id, &entries)?;
+
+    assert_eq!(latest_commit_for_match_in(&dir, match_id)?, Some(expected));
+    assert_eq!(list_saved_matches_in(&dir)?, vec![match_id]);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+/// Entries that do not verify are skipped rather than trusted, so a log
+/// of only bad lines (or no log at all) yields nothing to resume from.
+#[test]
+fn latest_commit_for_match_skips_unverifiable_entries() -> Result<()> {
+    use host::proofs::{latest_commit_for_match_in, list_saved_matches_in, ReceiptLogEntry};
+    use host::network_protocol::PROOF_DATA_VERSION;
+    use core::{Position, RoundCommit, HitType};
+
+    let match_id = uuid::Uuid::new_v4();
+    let commit = |seq: u64| RoundCommit { old_state: risc0_zkvm::sha::Digest::default(), new_state: risc0_zkvm::sha::Digest::default(), shot: Position::new(0, 0), hit: HitType::Miss, match_id, seq, sunk_cells: vec![], revealed_water: vec![], invalid: None };
+    let entries = [
+        ReceiptLogEntry { version: 0, seq: 0, receipt_b64: "not base64!".into(), commit: commit(0) },
+        ReceiptLogEntry { version: PROOF_DATA_VERSION, seq: 1, receipt_b64: "AAAA".into(), commit: commit(1) },
+    ];
+    let dir = write_receipt_log(match_id, &entries)?;
+    std::fs::write(dir.join("notes.txt"), "ignored")?;
+
+    assert_eq!(latest_commit_for_match_in(&dir, match_id)?, None);
+    assert_eq!(latest_commit_for_match_in(&dir, uuid::Uuid::new_v4())?, None);
+    assert_eq!(list_saved_matches_in(&dir)?, vec![match_id]);
+    std::fs::remove_dir_all(&dir)?;
+    assert!(list_saved_matches_in(&dir)?.is_empty());
+    Ok(())
+}
+
+/// A shooter only knows the defender's commitment, never the board; that
+/// digest alone must be enough to verify the round, and a stale one must
+/// be rejected.
+#[test]
+fn shooter_verifies_round_with_only_a_digest() -> Result<()> {
+    use host::proofs::{produce_and_verify_proof, verify_remote_round_proof, verify_shot_result_for_shooter, GuestInput};
+    use core::Position;
+
+    let (defender, _) = host::game_round::demo_boards();
+    let shot = Position::new(0, 0);
+    let input = GuestInput { initial: defender.clone(), shots: vec![shot], match_id: uuid::Uuid::nil(), seq: 0, turn_owner: vec![], turn: core::TurnState::default(), fleet: core::FleetSpec::classic(), disclosure: core::DisclosureMode::Open };
+    let receipt = match produce_and_verify_proof(&input) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("prover unavailable or failed, skipping digest-only verification test: {}", e);
+            return Ok(());
+        }
+    };
+    let opponent_commit = defender.commit();
+    drop(defender);
+
+    let commits = verify_remote_round_proof(&receipt, opponent_commit, shot, None, None)?;
+    let rc = verify_shot_result_for_shooter(&receipt, opponent_commit, shot, None, None, None)?;
+    assert_eq!(commits.last(), Some(&rc));
+    assert!(verify_remote_round_proof(&receipt, rc.new_state, shot, None, None).is_err(), "stale commitment must be rejected");
+    Ok(())
+}
+
+/// `doctor` reports the stage that failed: an unusable backend before
+/// anything runs, and otherwise exits 0 only when proving really works.
+#[test]
+fn doctor_reports_the_failing_stage() {
+    use host::doctor::{exit_code, run, DoctorStage};
+    use host::proofs::{BonsaiConfig, ProverBackend};
+
+    let mut stages = Vec::new();
+    let unconfigured = ProverBackend::Bonsai(BonsaiConfig { api_url: String::new(), api_key: String::new() });
+    let result = run(&unconfigured, &mut |s| stages.push(s.to_string()));
+    let failure = result.as_ref().unwrap_err();
+    assert_eq!(failure.stage, DoctorStage::Backend);
+    assert!(failure.guidance().contains("api_key"), "{}", failure.guidance());
+    assert!(stages.is_empty(), "nothing passes before the backend check: {:?}", stages);
+    assert_eq!(exit_code(&result), 1);
+
+    // The local prover works only where the toolchain is installed
+    let mut stages = Vec::new();
+    let result = run(&ProverBackend::Local, &mut |s| stages.push(s.to_string()));
+    match &result {
+        Ok(()) => {
+            assert_eq!(stages.len(), 4);
+            assert_eq!(exit_code(&result), 0);
+        }
+        Err(failure) => {
+            eprintln!("prover unavailable here; doctor said: {} ({})", failure, failure.guidance());
+            assert_ne!(failure.stage, DoctorStage::Backend, "the local backend is always selectable");
+            assert_eq!(exit_code(&result), 1);
+        }
+    }
+}
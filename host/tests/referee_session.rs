@@ -0,0 +1,132 @@
+use anyhow::Result;
+use core::{GameState, HitType, Player, Position, RoundCommit};
+use host::network::{bind_match_secret, envelope_auth_token, NetworkConnection};
+use host::network_protocol::{Envelope, GameMessage, ProofData};
+use host::referee::RefereeSession;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use uuid::Uuid;
+
+// Referee tests with two mock players. Envelopes are built and signed by
+// hand so no prover is needed.
+
+const SECRET: [u8; 32] = [9u8; 32];
+
+fn signed(secret: &[u8], match_id: Uuid, seq: u64, payload: GameMessage) -> Result<Envelope> {
+    let mut env = Envelope::new(match_id, seq, payload);
+    env.auth_token = Some(envelope_auth_token(secret, &env)?);
+    Ok(env)
+}
+
+/// Referee that has seen both BoardReady messages and Player One's TakeShot
+/// at (4, 2). Returns the session, match id, Player Two's commitment and
+/// the board-bound secret used after the handshake.
+fn referee_after_shot() -> Result<(RefereeSession, Uuid, risc0_zkvm::sha::Digest, Vec<u8>)> {
+    let mut referee = RefereeSession::with_ephemeral_key(SECRET.to_vec())?;
+    let match_id = Uuid::new_v4();
+    let (b1, b2) = (GameState::new([1; 16]), GameState::new([2; 16]));
+    let (p1, p2) = (b1.hash_ships_only(), b2.hash_ships_only());
+    let (c1, c2) = (b1.commit(), b2.commit());
+    referee.observe(Player::One, signed(&SECRET, match_id, 0, GameMessage::BoardReady { commitment: p1, state_commitment: c1, player_name: "one".into(), proof: None, coin_commitment: p1, fleet: core::FleetSpec::classic() })?)?;
+    referee.observe(Player::Two, signed(&SECRET, match_id, 0, GameMessage::BoardReady { commitment: p2, state_commitment: c2, player_name: "two".into(), proof: None, coin_commitment: p2, fleet: core::FleetSpec::classic() })?)?;
+    let bound = bind_match_secret(&SECRET, &p1, &p2);
+    referee.observe(Player::One, signed(&bound, match_id, 1, GameMessage::TakeShot { position: Position::new(4, 2) })?)?;
+    Ok((referee, match_id, c2, bound))
+}
+
+fn forged_result(match_id: Uuid, old_state: risc0_zkvm::sha::Digest, proven: HitType, claimed: HitType) -> GameMessage {
+    let commit = RoundCommit { match_id, seq: 1, old_state, new_state: old_state, shot: Position::new(4, 2), hit: proven, sunk_cells: vec![], revealed_water: vec![], invalid: None };
+    GameMessage::ShotResult { position: Position::new(4, 2), hit_type: claimed, proof: ProofData::from_bytes(vec![0; 8], commit) }
+}
+
+#[test]
+fn referee_rejects_shot_result_with_forged_hmac() -> Result<()> {
+    let (mut referee, match_id, c2, _) = referee_after_shot()?;
+    let forged = signed(&[0u8; 32], match_id, 1, forged_result(match_id, c2, HitType::Miss, HitType::Miss))?;
+    let err = referee.observe(Player::Two, forged).expect_err("forged HMAC must be rejected");
+    assert!(err.to_string().contains("HMAC"), "unexpected error: {err}");
+    assert_eq!(referee.entries().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn referee_flags_shot_result_that_contradicts_its_proof() -> Result<()> {
+    let (mut referee, match_id, c2, bound) = referee_after_shot()?;
+    // Correctly authenticated, but the defender claims a miss while the
+    // attached commit records a hit
+    let lie = signed(&bound, match_id, 1, forged_result(match_id, c2, HitType::Hit, HitType::Miss))?;
+    let err = referee.observe(Player::Two, lie).expect_err("contradictory ShotResult must be rejected");
+    assert!(err.to_string().contains("disagrees"), "unexpected error: {err}");
+
+    // Replaying an already-accepted sequence number is also rejected
+    let replay = signed(&bound, match_id, 1, GameMessage::TakeShot { position: Position::new(0, 0) })?;
+    assert!(referee.observe(Player::One, replay).is_err());
+    Ok(())
+}
+
+#[test]
+fn referee_rejects_boards_for_different_fleets() -> Result<()> {
+    let mut referee = RefereeSession::with_ephemeral_key(SECRET.to_vec())?;
+    let match_id = Uuid::new_v4();
+    let board = GameState::new([1; 16]);
+    let (placement, commit) = (board.hash_ships_only(), board.commit());
+    let small = core::FleetSpec::with_ships(vec![core::ShipType::Cruiser, core::ShipType::Destroyer], core::BoardDims::new(8, 8).unwrap());
+    referee.observe(Player::One, signed(&SECRET, match_id, 0, GameMessage::BoardReady { commitment: placement, state_commitment: commit, player_name: "one".into(), proof: None, coin_commitment: placement, fleet: core::FleetSpec::classic() })?)?;
+    let other = signed(&SECRET, match_id, 0, GameMessage::BoardReady { commitment: placement, state_commitment: commit, player_name: "two".into(), proof: None, coin_commitment: placement, fleet: small })?;
+    let err = referee.observe(Player::Two, other).expect_err("mismatched fleets must be rejected");
+    assert!(err.to_string().contains("fleet"), "unexpected error: {err}");
+    assert_eq!(referee.entries().len(), 1);
+    Ok(())
+}
+
+fn tcp_pair() -> Result<(TcpStream, TcpStream)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let a = TcpStream::connect(listener.local_addr()?)?;
+    let (b, _) = listener.accept()?;
+    Ok((a, b))
+}
+
+#[test]
+fn broadcast_mode_produces_signed_transcript() -> Result<()> {
+    let (host_sock, client_sock) = tcp_pair()?;
+    let (host_to_ref, ref_from_host) = tcp_pair()?;
+    let (client_to_ref, ref_from_client) = tcp_pair()?;
+
+    let referee_thread = thread::spawn(move || -> Result<RefereeSession> {
+        let mut referee = RefereeSession::with_ephemeral_key(SECRET.to_vec())?;
+        referee.run(ref_from_host, ref_from_client)?;
+        Ok(referee)
+    });
+
+    let mut host = NetworkConnection::from_stream(host_sock, Some(SECRET.to_vec()));
+    let mut client = NetworkConnection::from_stream(client_sock, Some(SECRET.to_vec()));
+    host.set_referee(host_to_ref);
+    client.set_referee(client_to_ref);
+
+    let board = GameState::new([0; 16]);
+    let (placement, commit) = (board.hash_ships_only(), board.commit());
+    let handle = thread::spawn(move || -> Result<NetworkConnection> {
+        client.handshake_as_client("client", placement, commit, None)?;
+        client.receive_enveloped()?;
+        Ok(client)
+    });
+    host.handshake_as_host("host", placement, commit, None)?;
+    host.send_enveloped(&GameMessage::TakeShot { position: Position::new(1, 1) })?;
+    let client = handle.join().expect("client thread panicked")?;
+    drop(host);
+    drop(client);
+
+    let referee = referee_thread.join().expect("referee thread panicked")?;
+    let mut transcript = referee.transcript()?;
+    assert_eq!(transcript.entries.len(), 3);
+    // Each player's stream is read on its own thread, so only per-player
+    // order is fixed: Player One's BoardReady comes before its TakeShot
+    let from_one: Vec<_> = transcript.entries.iter().filter(|e| e.from == Player::One).map(|e| &e.envelope.payload).collect();
+    assert!(matches!(from_one[..], [GameMessage::BoardReady { .. }, GameMessage::TakeShot { .. }]));
+    transcript.verify()?;
+
+    // Any edit to the recorded envelopes invalidates the signature
+    transcript.entries[2].envelope.seq = 7;
+    assert!(transcript.verify().is_err());
+    Ok(())
+}
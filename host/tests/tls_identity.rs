@@ -0,0 +1,164 @@
+use anyhow::Result;
+use host::network::{certificate_fingerprint, NetworkConfig, NetworkConnection};
+use openssl::x509::X509;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+
+// Full TLS connection over loopback using the self-signed certificate in
+// `tests/fixtures` (CN=localhost, SAN IP 127.0.0.1). Kept in its own test
+// binary because `host_on`/`connect` read their paths from process-wide
+// environment variables. Tests of `host_with`/`connect_with` leave the
+// environment alone so they can run alongside.
+
+/// `openssl x509 -in tests/fixtures/self_signed.crt -noout -fingerprint -sha256`
+const FIXTURE_FINGERPRINT: &str = "C9:81:48:14:92:44:BE:03:34:98:1F:F2:DC:83:7A:DC:33:B9:90:BB:63:63:E6:3A:81:A2:92:8B:61:72:CC:75";
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn certificate_fingerprint_matches_openssl_cli() -> Result<()> {
+    let cert = X509::from_pem(&std::fs::read(fixture("self_signed.crt"))?)?;
+    assert_eq!(certificate_fingerprint(&cert)?, FIXTURE_FINGERPRINT);
+    Ok(())
+}
+
+#[test]
+fn client_sees_host_certificate_fingerprint() -> Result<()> {
+    std::env::set_var("BATTLE_SERVER_CERT", fixture("self_signed.crt"));
+    std::env::set_var("BATTLE_SERVER_KEY", fixture("self_signed.key"));
+    std::env::set_var("BATTLE_CA_CERT", fixture("self_signed.crt"));
+    std::env::remove_var("BATTLE_CLIENT_CERT");
+    std::env::remove_var("BATTLE_CLIENT_KEY");
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let handle = thread::spawn(move || NetworkConnection::host_on(listener));
+    let client = NetworkConnection::connect("127.0.0.1", port)?;
+    let host = handle.join().expect("host thread panicked")?;
+
+    assert_eq!(client.peer_fingerprint().as_deref(), Some(FIXTURE_FINGERPRINT));
+    // The host does not ask for client certificates
+    assert_eq!(host.peer_fingerprint(), None);
+    Ok(())
+}
+
+#[test]
+fn connect_with_retry_waits_for_a_late_host() -> Result<()> {
+    use std::time::Duration;
+    std::env::set_var("BATTLE_SERVER_CERT", fixture("self_signed.crt"));
+    std::env::set_var("BATTLE_SERVER_KEY", fixture("self_signed.key"));
+    std::env::set_var("BATTLE_CA_CERT", fixture("self_signed.crt"));
+    std::env::remove_var("BATTLE_CLIENT_CERT");
+    std::env::remove_var("BATTLE_CLIENT_KEY");
+
+    // Reserve a free port, then leave it closed until the host starts
+    let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        NetworkConnection::host_on(TcpListener::bind(("127.0.0.1", port))?)
+    });
+
+    assert!(NetworkConnection::connect("127.0.0.1", port).is_err(), "nothing is listening yet");
+    // 50 + 100 + 200 + 400 ms of backoff outlasts the host's delay
+    let client = NetworkConnection::connect_with_retry("127.0.0.1", port, 6, Duration::from_millis(50))?;
+    let host = handle.join().expect("host thread panicked")?;
+    assert_eq!(client.peer_fingerprint().as_deref(), Some(FIXTURE_FINGERPRINT));
+    assert_eq!(host.peer_fingerprint(), None);
+    Ok(())
+}
+
+/// Write a fresh self-signed P-256 certificate for localhost/127.0.0.1
+/// (usable as its own CA) and its key into a temp dir.
+fn generate_self_signed(tag: &str) -> Result<(PathBuf, PathBuf, String)> {
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+    use openssl::x509::X509NameBuilder;
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", "localhost")?;
+    let name = name.build();
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    let serial = BigNum::from_u32(rand::random::<u32>() | 1)?.to_asn1_integer()?;
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(&name)?;
+    builder.set_issuer_name(&name)?;
+    builder.set_pubkey(&key)?;
+    let (not_before, not_after) = (Asn1Time::days_from_now(0)?, Asn1Time::days_from_now(1)?);
+    builder.set_not_before(&not_before)?;
+    builder.set_not_after(&not_after)?;
+    builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+    builder.append_extension(SubjectAlternativeName::new().dns("localhost").ip("127.0.0.1").build(&builder.x509v3_context(None, None))?)?;
+    builder.sign(&key, MessageDigest::sha256())?;
+    let cert = builder.build();
+
+    let dir = std::env::temp_dir().join(format!("battleship-tls-{}-{}-{}", tag, std::process::id(), uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let (cert_path, key_path) = (dir.join("cert.pem"), dir.join("key.pem"));
+    std::fs::write(&cert_path, cert.to_pem()?)?;
+    std::fs::write(&key_path, key.private_key_to_pem_pkcs8()?)?;
+    Ok((cert_path, key_path, certificate_fingerprint(&cert)?))
+}
+
+#[test]
+fn explicit_config_uses_the_given_certificate_instead_of_the_environment() -> Result<()> {
+    let (cert, key, fingerprint) = generate_self_signed("explicit")?;
+    assert_ne!(fingerprint, FIXTURE_FINGERPRINT);
+    let server = NetworkConfig { cert: Some(cert.clone()), key: Some(key), ca: None, require_client_cert: false };
+    let client = NetworkConfig { ca: Some(cert), ..NetworkConfig::default() };
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let handle = thread::spawn(move || NetworkConnection::host_on_with(listener, &server));
+    let client = NetworkConnection::connect_with("127.0.0.1", port, &client)?;
+    let host = handle.join().expect("host thread panicked")?;
+
+    assert_eq!(client.peer_fingerprint().as_deref(), Some(fingerprint.as_str()));
+    assert_eq!(host.peer_fingerprint(), None);
+    Ok(())
+}
+
+#[test]
+fn require_client_cert_refuses_anonymous_clients_and_sees_presented_ones() -> Result<()> {
+    let (server_cert, server_key, _) = generate_self_signed("mtls-server")?;
+    let (client_cert, client_key, client_fingerprint) = generate_self_signed("mtls-client")?;
+    // Each side trusts the other's self-signed certificate as its CA
+    let server = NetworkConfig { cert: Some(server_cert.clone()), key: Some(server_key), ca: Some(client_cert.clone()), require_client_cert: true };
+    let anonymous = NetworkConfig { ca: Some(server_cert.clone()), ..NetworkConfig::default() };
+    let identified = NetworkConfig { cert: Some(client_cert), key: Some(client_key), ca: Some(server_cert), require_client_cert: false };
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let host_config = server.clone();
+    let handle = thread::spawn(move || NetworkConnection::host_on_with(listener, &host_config));
+    // Under TLS 1.3 the client may only notice once it reads; either way the host refuses
+    let _ = NetworkConnection::connect_with("127.0.0.1", port, &anonymous);
+    assert!(handle.join().expect("host thread panicked").is_err(), "a client without a certificate is refused");
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let handle = thread::spawn(move || NetworkConnection::host_on_with(listener, &server));
+    let _client = NetworkConnection::connect_with("127.0.0.1", port, &identified)?;
+    let host = handle.join().expect("host thread panicked")?;
+    assert_eq!(host.peer_fingerprint().as_deref(), Some(client_fingerprint.as_str()));
+    Ok(())
+}
+
+#[test]
+fn explicit_config_reports_missing_paths() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let err = NetworkConnection::host_on_with(listener, &NetworkConfig::default()).err().expect("no certificate configured");
+    assert!(err.to_string().contains("NetworkConfig::cert"), "{}", err);
+    let err = NetworkConnection::connect_with("127.0.0.1", 1, &NetworkConfig::default()).err().expect("no CA configured");
+    assert!(err.to_string().contains("NetworkConfig::ca"), "{}", err);
+}
@@ -4,19 +4,29 @@ use serde::{Deserialize};
 // Import canonical types from the core crate. `GameState::commit()` and
 // `RoundCommit` are used to produce the public commitments that the
 // verifier will later check.
-use core::{GameState, RoundCommit, HitType, Position};
+use core::{DisclosureMode, FleetSpec, GameState, RoundCommit, HitType, Player, Position, TurnState, MAX_SHOTS_PER_PROOF};
 use uuid::Uuid;
 
 /// Input supplied to the guest prover.
 /// - `initial`: the initial board placement (authoritative GameState)
 /// - `shots`: a list of shots (in order) for which the guest will emit
 ///   per-round commits.
+/// - `turn_owner`: who fired each shot, or empty to skip the turn-order
+///   check (salvos). Checked against `turn`, the turn state before the
+///   first shot, under the fleet's `TurnRules`.
+/// - `fleet`: the board parameters both peers agreed on in the handshake.
+/// - `disclosure`: whether a sinking names the ship type or only commits
+///   to it.
 #[derive(Deserialize)]
 struct GuestInput {
     initial: GameState,
     shots: Vec<Position>,
     match_id: Uuid,
     seq: u64,
+    turn_owner: Vec<Player>,
+    turn: TurnState,
+    fleet: FleetSpec,
+    disclosure: DisclosureMode,
 }
 
 fn main() {
@@ -25,33 +35,61 @@ fn main() {
     let input: GuestInput = env::read();
 
     // Start from the provided initial board state. For ZK protocols the
-    // prover should ensure the initial state satisfies invariants (e.g.,
-    // `check()`), otherwise the proof should fail.
+    // prover should ensure the initial state is a complete, legal board
+    // of the agreed fleet, otherwise the proof should fail.
     let mut state: GameState = input.initial;
     if !state.check() {
         // Invalid initial board -> abort proof generation.
         panic!("initial GameState failed validation");
     }
+    if !state.is_placement_complete(&input.fleet) {
+        panic!("initial GameState is not exactly the agreed fleet");
+    }
+
+    // Bounds proving cost; the host checks this too (`validate_guest_input`)
+    if input.shots.len() > MAX_SHOTS_PER_PROOF {
+        panic!("more than MAX_SHOTS_PER_PROOF shots");
+    }
+
+    if !input.turn_owner.is_empty() && input.turn_owner.len() != input.shots.len() {
+        panic!("turn_owner must be empty or have one entry per shot");
+    }
 
     // Commit the initial board state and publish it to the journal so the
-    // verifier can observe the initial commit value.
+    // verifier can observe the initial commit value. With no shots this is
+    // the whole journal: a setup proof that the committed board is legal.
     let initial_commit = state.commit();
     env::commit(&initial_commit);
+    // Then the placement hash the defender announced in `BoardReady`, so a
+    // verifier can tie the proof to the ships fixed at the start of the match.
+    env::commit(&state.hash_ships_only());
+    // Followed by the match parameters, so a verifier can reject proofs
+    // made for a different board, fleet or rules.
+    env::commit(&input.fleet.digest());
+    // And the turn the shots were fired in, so the shooter can check the
+    // proof against the turn it tracked itself.
+    env::commit(&input.turn);
+    env::commit(&input.turn_owner);
 
     // For each shot, record the old/new state commits and the hit result
     // in a `RoundCommit` which is written to the journal. The round commit
     // is bound to the provided match/session id and sequence number so the
     // verifier can tie proofs to network-level associated data.
-    for shot in input.shots {
-        let old_state = state.commit();
-
+    // Whose shot is next, for the turn-order check.
+    let mut turn = input.turn;
+    // Each round starts from the previous round's new state
+    let mut old_state = initial_commit;
+    for (i, shot) in input.shots.into_iter().enumerate() {
         // Apply the shot. Per the core API, `apply_shot` returns `None`
         // for out-of-bounds or already-shot cells. Instead of panicking we
         // treat such cases as a harmless no-op and record a Miss. This
         // prevents the guest from aborting the proof when a remote peer
-        // requests an invalid/repeated shot; the host should still reject
-        // repeated shots at the protocol level if desired.
-        let hit = match state.apply_shot(shot) {
+        // requests an invalid/repeated shot. The reason is committed in
+        // `invalid`, so the host can still tell the fault from a real miss
+        // and penalise it at the protocol level.
+        let invalid = state.check_shot(shot).err();
+        let (hit, new_state) = state.apply_shot_committed(shot);
+        let hit = match hit {
             Some(h) => h,
             None => {
                 // Do not mutate state; represent as a Miss so the proof
@@ -60,9 +98,30 @@ fn main() {
             }
         };
 
-        let new_state = state.commit();
+        // Rule-compliant play: only the player to move may shoot, and the
+        // turn passes as the agreed `TurnRules` say. A violation fails the
+        // proof rather than producing a commit for illegal play.
+        if let Some(&owner) = input.turn_owner.get(i) {
+            if owner != turn.to_move {
+                panic!("turn order violation: {:?} shot while {:?} was to move", owner, turn.to_move);
+            }
+            turn.advance(&input.fleet.turn_rules, owner, &hit);
+        }
+
+        // Under hidden disclosure a sinking only commits to the ship type.
+        // Nothing below reveals cells for it, as the footprint's length
+        // would give the type away.
+        let hit = input.disclosure.disclose(hit);
+
+        // A sunk ship's cells are public from now on; reveal them so the
+        // shooter can mark the whole ship
+        let sunk_cells = state.sunk_footprint(&hit);
+        // Under no-touching rules the water around it is public too, and
+        // apply_shot has already marked it
+        let revealed_water = state.sunk_surroundings(&hit);
 
-        let round = RoundCommit { match_id: input.match_id, seq: input.seq, old_state, new_state, shot, hit };
+        let round = RoundCommit { match_id: input.match_id, seq: input.seq, old_state, new_state, shot, hit, sunk_cells, revealed_water, invalid };
         env::commit(&round);
+        old_state = new_state;
     }
 }